@@ -0,0 +1,149 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Weather observed, or expected, at a place.
+///
+/// The metrics other than the temperature are [None] when the source of the
+/// weather does not give them.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Observation {
+    /// Main condition, in lowercase (e.g. `clear`, `rain`).
+    pub condition: String,
+    /// Temperature, in degrees Celsius.
+    pub temp: f64,
+    /// Feels-like temperature, in degrees Celsius.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feels_like: Option<f64>,
+    /// Relative humidity, in percent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub humidity: Option<f64>,
+    /// Wind speed, in meters per second.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wind: Option<f64>,
+    /// Rain volume over 3 hours, in millimeters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rain: Option<f64>,
+}
+
+impl Observation {
+    /// Create a new [`Observation`](Self), without the other metrics.
+    pub fn new(condition: String, temp: f64) -> Self {
+        Self {
+            condition,
+            temp,
+            feels_like: None,
+            humidity: None,
+            wind: None,
+            rain: None,
+        }
+    }
+
+    /// Returns this [Observation] with its feels-like temperature.
+    pub fn with_feels_like(self, feels_like: f64) -> Self {
+        Self {
+            feels_like: Some(feels_like),
+            ..self
+        }
+    }
+
+    /// Returns this [Observation] with its relative humidity.
+    pub fn with_humidity(self, humidity: f64) -> Self {
+        Self {
+            humidity: Some(humidity),
+            ..self
+        }
+    }
+
+    /// Returns this [Observation] with its wind speed.
+    pub fn with_wind(self, wind: f64) -> Self {
+        Self {
+            wind: Some(wind),
+            ..self
+        }
+    }
+
+    /// Returns this [Observation] with its rain volume.
+    pub fn with_rain(self, rain: f64) -> Self {
+        Self {
+            rain: Some(rain),
+            ..self
+        }
+    }
+}
+
+/// Weather expected at a given time.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ForecastStep {
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub observation: Observation,
+}
+
+/// Weather forecast of a town, checked by the
+/// [Restriction::Meteo](crate::promocode::restriction::Restriction::Meteo)s.
+///
+/// Its steps are in chronological order, the first one standing for the
+/// current weather.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct Forecast {
+    steps: Vec<ForecastStep>,
+}
+
+impl Forecast {
+    /// Create a new [`Forecast`](Self), sorting its steps.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if `steps` is empty.
+    pub fn new(mut steps: Vec<ForecastStep>) -> Result<Self, String> {
+        if steps.is_empty() {
+            return Err("`steps` must not be empty".to_string());
+        }
+        steps.sort_by_key(|step| step.at);
+
+        Ok(Self { steps })
+    }
+
+    /// Returns the steps, in chronological order.
+    pub fn steps(&self) -> &[ForecastStep] {
+        &self.steps
+    }
+
+    /// Returns the first step, standing for the current weather.
+    pub fn current(&self) -> &ForecastStep {
+        &self.steps[0]
+    }
+
+    /// Returns the steps before `until`, or at least the first one.
+    pub fn until(&self, until: DateTime<Utc>) -> &[ForecastStep] {
+        let count = self.steps.iter().take_while(|step| step.at < until).count();
+        &self.steps[..count.max(1)]
+    }
+}
+
+/// The current weather only.
+impl From<Observation> for Forecast {
+    fn from(observation: Observation) -> Self {
+        Self {
+            steps: vec![ForecastStep {
+                at: Utc::now(),
+                observation,
+            }],
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Forecast {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct ForecastUnsafe {
+            steps: Vec<ForecastStep>,
+        }
+
+        let data = ForecastUnsafe::deserialize(deserializer)?;
+        Forecast::new(data.steps).map_err(serde::de::Error::custom)
+    }
+}
@@ -1,3 +1,6 @@
+pub mod forecast;
 pub mod promocode;
 pub mod promocode_request;
 pub mod promocode_response;
+pub mod signed_promocode;
+pub mod voucher_batch;
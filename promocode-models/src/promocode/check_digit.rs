@@ -0,0 +1,196 @@
+use promocode_util::validate_type::string::NonBlankString;
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+use std::collections::HashSet;
+
+/// Luhn mod N check digit computed over an alphabet.
+///
+/// Characters outside the alphabet (e.g. `-` separators) are ignored, so that
+/// a prefix like `SUMMER-` does not need to be part of the alphabet. The check
+/// digit is the last character of a code.
+///
+/// When `prefixes` is not empty, only the codes starting with one of them are
+/// expected to embed a check digit.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct CheckDigit {
+    alphabet: NonBlankString,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    prefixes: Vec<NonBlankString>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    suggest: bool,
+}
+
+impl CheckDigit {
+    /// Create a new [`CheckDigit`](Self)
+    ///
+    /// # Errors
+    ///
+    /// This function fails if one of field is not correct.
+    pub fn new(alphabet: String, prefixes: Vec<String>, suggest: bool) -> Result<Self, String> {
+        let alphabet = match NonBlankString::new(alphabet) {
+            Err(err_alphabet) => return Err(format!("`alphabet` {}", err_alphabet)),
+            Ok(value) => value,
+        };
+
+        let mut alphabet_chars = HashSet::new();
+        for c in alphabet.to_string().chars() {
+            if !alphabet_chars.insert(c) {
+                return Err(format!("`alphabet` contains `{}` twice.", c));
+            }
+        }
+        if alphabet_chars.len() < 2 {
+            return Err("`alphabet` must contain at least 2 characters.".to_string());
+        }
+
+        let prefixes = match prefixes
+            .into_iter()
+            .map(NonBlankString::new)
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Err(err_prefix) => return Err(format!("`prefixes` {}", err_prefix)),
+            Ok(value) => value,
+        };
+
+        Ok(Self {
+            alphabet,
+            prefixes,
+            suggest,
+        })
+    }
+
+    /// Returns the alphabet as [String] type
+    pub fn alphabet(&self) -> String {
+        self.alphabet.clone().get()
+    }
+
+    /// Returns `true` if "did you mean" suggestions should be given for the
+    /// codes with an invalid check digit.
+    pub fn suggest(&self) -> bool {
+        self.suggest
+    }
+
+    /// Returns `true` if `code` is expected to embed a check digit.
+    pub fn applies_to(&self, code: &str) -> bool {
+        self.prefix_len(code).is_some()
+    }
+
+    /// Returns the number of characters of the longest prefix `code` starts
+    /// with, `0` without prefixes, or [None] if `code` is not expected to
+    /// embed a check digit.
+    fn prefix_len(&self, code: &str) -> Option<usize> {
+        if self.prefixes.is_empty() {
+            return Some(0);
+        }
+
+        self.prefixes
+            .iter()
+            .map(|prefix| prefix.to_string())
+            .filter(|prefix| code.starts_with(prefix.as_str()))
+            .map(|prefix| prefix.chars().count())
+            .max()
+    }
+
+    /// Computes the check digit to append to `payload`.
+    pub fn compute(&self, payload: &str) -> char {
+        let alphabet: Vec<char> = self.alphabet.to_string().chars().collect();
+        let n = alphabet.len();
+
+        let sum = self.luhn_sum(payload, 2);
+        alphabet[(n - sum % n) % n]
+    }
+
+    /// Returns `true` if `code` does not need a check digit or ends with a
+    /// valid one.
+    pub fn is_valid(&self, code: &str) -> bool {
+        if !self.applies_to(code) {
+            return true;
+        }
+
+        let n = self.alphabet.to_string().chars().count();
+        self.code_points(code).len() >= 2 && self.luhn_sum(code, 1).is_multiple_of(n)
+    }
+
+    /// Returns the valid codes at one typo of `code`: a single wrong character
+    /// or two swapped adjacent characters.
+    ///
+    /// The prefix of `code` is kept as is, so that every suggestion embeds a
+    /// check digit. No suggestion is given if `code` does not need one.
+    pub fn suggestions(&self, code: &str) -> Vec<String> {
+        let prefix_len = match self.prefix_len(code) {
+            None => return vec![],
+            Some(value) => value,
+        };
+        let alphabet: Vec<char> = self.alphabet.to_string().chars().collect();
+        let chars: Vec<char> = code.chars().collect();
+        let mut suggestions: Vec<String> = vec![];
+        let mut push_if_valid = |candidate: Vec<char>| {
+            let candidate: String = candidate.into_iter().collect();
+            if candidate != code && self.is_valid(&candidate) && !suggestions.contains(&candidate) {
+                suggestions.push(candidate);
+            }
+        };
+
+        for index in prefix_len..chars.len() {
+            if !alphabet.contains(&chars[index]) {
+                continue;
+            }
+
+            for replacement in &alphabet {
+                let mut candidate = chars.clone();
+                candidate[index] = *replacement;
+                push_if_valid(candidate);
+            }
+
+            if index + 1 < chars.len() && alphabet.contains(&chars[index + 1]) {
+                let mut candidate = chars.clone();
+                candidate.swap(index, index + 1);
+                push_if_valid(candidate);
+            }
+        }
+
+        suggestions
+    }
+
+    /// Returns the positions in the alphabet of the characters of `code`.
+    fn code_points(&self, code: &str) -> Vec<usize> {
+        let alphabet = self.alphabet.to_string();
+        code.chars()
+            .filter_map(|c| alphabet.chars().position(|it| it == c))
+            .collect()
+    }
+
+    /// Luhn mod N sum of `code`, read from right to left starting with
+    /// `factor`.
+    fn luhn_sum(&self, code: &str, mut factor: usize) -> usize {
+        let n = self.alphabet.to_string().chars().count();
+
+        self.code_points(code)
+            .into_iter()
+            .rev()
+            .fold(0, |sum, code_point| {
+                let addend = factor * code_point;
+                factor = if factor == 2 { 1 } else { 2 };
+                sum + addend / n + addend % n
+            })
+    }
+}
+
+impl<'de> Deserialize<'de> for CheckDigit {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+        struct CheckDigitUnsafe {
+            alphabet: String,
+            #[serde(default)]
+            prefixes: Vec<String>,
+            #[serde(default)]
+            suggest: bool,
+        }
+
+        match CheckDigitUnsafe::deserialize(deserializer) {
+            Ok(data) => CheckDigit::new(data.alphabet, data.prefixes, data.suggest).map_err(Error::custom),
+            Err(err) => Err(Error::custom(err)),
+        }
+    }
+}
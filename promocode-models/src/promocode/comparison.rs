@@ -0,0 +1,80 @@
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+
+/// Comparison of a weather metric, the bounds being included as for the
+/// `@age` restriction.
+///
+/// One of `lt`, `eq` or `gt` must be present, `eq` alone.
+///
+/// `eq` is an exact match: it suits the metrics measured as round values,
+/// e.g. `{"eq": 0}` for no rain, and `lt` with `gt` should be preferred for
+/// the others, e.g. `{"gt": 54.5, "lt": 55.5}` rather than `{"eq": 55}`.
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+pub struct Comparison {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lt: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    eq: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gt: Option<f64>,
+}
+
+impl Comparison {
+    /// Create a new [`Comparison`](Self)
+    ///
+    /// # Errors
+    ///
+    /// This function fails if `Comparison` is not correct.
+    pub fn new(lt: Option<f64>, eq: Option<f64>, gt: Option<f64>) -> Result<Self, String> {
+        if [lt, eq, gt]
+            .into_iter()
+            .flatten()
+            .any(|value| !value.is_finite())
+        {
+            return Err("`lt`, `eq` and `gt` must be finite numbers.".to_string());
+        }
+
+        match (gt, eq, lt) {
+            (None, None, None) => Err("One of `lt`, `eq` or `gt` must be present.".to_string()),
+            (None, Some(_), None) | (Some(_), None, None) | (None, None, Some(_)) => Ok(Self { lt, eq, gt }),
+            (Some(gt_f64), None, Some(lt_f64)) => {
+                if gt_f64 < lt_f64 {
+                    Ok(Self { lt, eq, gt })
+                } else {
+                    Err("`gt` cannot be lower than `lt`.".to_string())
+                }
+            },
+            (_, _, _) => Err("Unsupported comparison.".to_string()),
+        }
+    }
+
+    /// Returns `true` if `value` satisfies this [Comparison].
+    pub fn matches(&self, value: f64) -> bool {
+        self.lt.is_none_or(|lt| value <= lt) && self.eq.is_none_or(|eq| value == eq) && self.gt.is_none_or(|gt| gt <= value)
+    }
+
+    /// Returns `true` if the bounds of this [Comparison] are between `min`
+    /// and `max`.
+    pub fn is_within(&self, min: f64, max: f64) -> bool {
+        [self.lt, self.eq, self.gt]
+            .into_iter()
+            .flatten()
+            .all(|value| min <= value && value <= max)
+    }
+}
+
+impl<'de> Deserialize<'de> for Comparison {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct ComparisonUnsafe {
+            lt: Option<f64>,
+            eq: Option<f64>,
+            gt: Option<f64>,
+        }
+
+        let data = ComparisonUnsafe::deserialize(deserializer)?;
+        Comparison::new(data.lt, data.eq, data.gt).map_err(Error::custom)
+    }
+}
@@ -0,0 +1,103 @@
+use promocode_util::validate_type::number::BoundedU8;
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+
+/// How the steps of a [ForecastWindow] are aggregated.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Aggregation {
+    /// One of the steps matches.
+    Any,
+    /// Every step matches.
+    All,
+    /// The lowest value of the steps.
+    Min,
+    /// The highest value of the steps.
+    Max,
+}
+
+/// Next `hours` of the forecast checked by a
+/// [Restriction::Meteo](crate::promocode::restriction::Restriction::Meteo),
+/// instead of the current weather.
+///
+/// E.g. `{"hours": 6, "is": "any", "temp": "max"}` accepts a rain at any time
+/// of the next 6 hours, and compares the highest temperature.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct ForecastWindow {
+    hours: BoundedU8<1, { ForecastWindow::MAX_HOURS }>,
+    /// Aggregation of the conditions, [Aggregation::Any] or [Aggregation::All].
+    /// A step matches if both its condition and its metrics match.
+    is: Aggregation,
+    /// Aggregation of the temperatures, [Aggregation::Min] or
+    /// [Aggregation::Max].
+    temp: Aggregation,
+}
+
+impl ForecastWindow {
+    /// Longest horizon, the one of the 5 days forecast.
+    pub const MAX_HOURS: u8 = 120;
+
+    /// Create a new [`ForecastWindow`](Self)
+    ///
+    /// # Errors
+    ///
+    /// This function fails if `hours` is not between `1` and [MAX_HOURS](Self::MAX_HOURS),
+    /// or if an aggregation does not apply to its field.
+    pub fn new(hours: u8, is: Aggregation, temp: Aggregation) -> Result<Self, String> {
+        let hours = match BoundedU8::new(hours) {
+            Err(err_hours) => return Err(format!("`hours` {}", err_hours)),
+            Ok(value) => value,
+        };
+
+        if !matches!(is, Aggregation::Any | Aggregation::All) {
+            return Err("`is` must be aggregated with `any` or `all`".to_string());
+        }
+
+        if !matches!(temp, Aggregation::Min | Aggregation::Max) {
+            return Err("`temp` must be aggregated with `min` or `max`".to_string());
+        }
+
+        Ok(Self { hours, is, temp })
+    }
+
+    /// Returns the number of hours of forecast.
+    pub fn hours(&self) -> u8 {
+        self.hours.get()
+    }
+
+    /// Returns the aggregation of the conditions.
+    pub fn is(&self) -> Aggregation {
+        self.is
+    }
+
+    /// Returns the aggregation of the temperatures.
+    pub fn temp(&self) -> Aggregation {
+        self.temp
+    }
+}
+
+impl<'de> Deserialize<'de> for ForecastWindow {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        fn default_is() -> Aggregation {
+            Aggregation::Any
+        }
+
+        fn default_temp() -> Aggregation {
+            Aggregation::Max
+        }
+
+        #[derive(Deserialize)]
+        struct ForecastWindowUnsafe {
+            hours: u8,
+            #[serde(default = "default_is")]
+            is: Aggregation,
+            #[serde(default = "default_temp")]
+            temp: Aggregation,
+        }
+
+        let data = ForecastWindowUnsafe::deserialize(deserializer)?;
+        ForecastWindow::new(data.hours, data.is, data.temp).map_err(Error::custom)
+    }
+}
@@ -0,0 +1,170 @@
+use chrono::{DateTime, Utc};
+use promocode_util::validate_type::string::NonBlankString;
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+
+/// Descriptive data attached to a [Promocode](crate::promocode::Promocode).
+///
+/// `created_at`, `updated_at` and `version` are maintained by the server
+/// through [Metadata::mark_created] and [Metadata::mark_updated], whatever the
+/// client sent.
+#[derive(Serialize, Clone, PartialEq, Debug, Default)]
+pub struct Metadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<NonBlankString>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<NonBlankString>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    owner: Option<NonBlankString>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    campaign: Option<NonBlankString>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    created_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    updated_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "is_zero")]
+    version: u64,
+}
+
+fn is_zero(version: &u64) -> bool {
+    *version == 0
+}
+
+impl Metadata {
+    /// Create a new [`Metadata`](Self)
+    ///
+    /// # Errors
+    ///
+    /// This function fails if one of field is not correct.
+    pub fn new(description: Option<String>, tags: Vec<String>, owner: Option<String>, campaign: Option<String>) -> Result<Self, String> {
+        let description = match description.map(NonBlankString::new).transpose() {
+            Err(err_description) => return Err(format!("`description` {}", err_description)),
+            Ok(value) => value,
+        };
+
+        let mut checked_tags: Vec<NonBlankString> = vec![];
+        for tag in tags {
+            match NonBlankString::new(tag) {
+                Err(err_tag) => return Err(format!("`tags` {}", err_tag)),
+                Ok(value) if checked_tags.contains(&value) => return Err(format!("`tags` contains `{}` twice", value)),
+                Ok(value) => checked_tags.push(value),
+            }
+        }
+
+        let owner = match owner.map(NonBlankString::new).transpose() {
+            Err(err_owner) => return Err(format!("`owner` {}", err_owner)),
+            Ok(value) => value,
+        };
+
+        let campaign = match campaign.map(NonBlankString::new).transpose() {
+            Err(err_campaign) => return Err(format!("`campaign` {}", err_campaign)),
+            Ok(value) => value,
+        };
+
+        Ok(Self {
+            description,
+            tags: checked_tags,
+            owner,
+            campaign,
+            created_at: None,
+            updated_at: None,
+            version: 0,
+        })
+    }
+
+    /// Returns the description as [String] type
+    pub fn description(&self) -> Option<String> {
+        self.description.clone().map(|it| it.get())
+    }
+
+    /// Returns the tags as [String] type
+    pub fn tags(&self) -> Vec<String> {
+        self.tags.iter().map(|it| it.clone().get()).collect()
+    }
+
+    /// Returns the owning team as [String] type
+    pub fn owner(&self) -> Option<String> {
+        self.owner.clone().map(|it| it.get())
+    }
+
+    /// Returns the parent campaign as [String] type
+    pub fn campaign(&self) -> Option<String> {
+        self.campaign.clone().map(|it| it.get())
+    }
+
+    /// Returns the creation date, if the promocode was stored.
+    pub fn created_at(&self) -> Option<DateTime<Utc>> {
+        self.created_at
+    }
+
+    /// Returns the last modification date, if the promocode was stored.
+    pub fn updated_at(&self) -> Option<DateTime<Utc>> {
+        self.updated_at
+    }
+
+    /// Returns the version, incremented on every write. `0` if the promocode
+    /// was never stored.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns `true` if every tag of `tags` is attached to this [Metadata].
+    pub fn has_tags(&self, tags: &[String]) -> bool {
+        tags.iter()
+            .all(|tag| self.tags.iter().any(|it| it.to_string() == *tag))
+    }
+
+    /// Sets both `created_at` and `updated_at` to `now`, and the version to
+    /// `1`.
+    pub fn mark_created(&mut self, now: DateTime<Utc>) {
+        self.created_at = Some(now);
+        self.updated_at = Some(now);
+        self.version = 1;
+    }
+
+    /// Sets `updated_at` to `now` and increments the version, keeping
+    /// `created_at` untouched.
+    pub fn mark_updated(&mut self, now: DateTime<Utc>) {
+        self.updated_at = Some(now);
+        self.version += 1;
+    }
+
+    /// Copies the fields maintained by the server from `previous`, the stored
+    /// [Metadata] this one replaces.
+    pub fn inherit(&mut self, previous: &Metadata) {
+        self.created_at = previous.created_at;
+        self.updated_at = previous.updated_at;
+        self.version = previous.version;
+    }
+}
+
+impl<'de> Deserialize<'de> for Metadata {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+        struct MetadataUnsafe {
+            description: Option<String>,
+            #[serde(default)]
+            tags: Vec<String>,
+            owner: Option<String>,
+            campaign: Option<String>,
+            created_at: Option<DateTime<Utc>>,
+            updated_at: Option<DateTime<Utc>>,
+            #[serde(default)]
+            version: u64,
+        }
+
+        match MetadataUnsafe::deserialize(deserializer) {
+            Ok(data) => Metadata::new(data.description, data.tags, data.owner, data.campaign)
+                .map(|metadata| Metadata {
+                    created_at: data.created_at,
+                    updated_at: data.updated_at,
+                    version: data.version,
+                    ..metadata
+                })
+                .map_err(Error::custom),
+            Err(err) => Err(Error::custom(err)),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{forecast::Observation, promocode::comparison::Comparison};
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+
+/// Comparisons of the weather metrics other than the condition and the
+/// temperature, checked by a
+/// [Restriction::Meteo](crate::promocode::restriction::Restriction::Meteo).
+///
+/// E.g. `{"humidity": {"lt": 60}, "rain": {"eq": 0}}` accepts a dry weather.
+#[derive(Serialize, Clone, PartialEq, Debug, Default)]
+pub struct MeteoMetrics {
+    /// Feels-like temperature, in degrees Celsius.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    feels_like: Option<Comparison>,
+    /// Relative humidity, in percent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    humidity: Option<Comparison>,
+    /// Wind speed, in meters per second.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    wind: Option<Comparison>,
+    /// Rain volume over 3 hours, in millimeters.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rain: Option<Comparison>,
+}
+
+impl MeteoMetrics {
+    /// Create a new [`MeteoMetrics`](Self)
+    ///
+    /// # Errors
+    ///
+    /// This function fails if `humidity` is not between `0` and `100`, or if
+    /// `wind` or `rain` is negative.
+    pub fn new(feels_like: Option<Comparison>, humidity: Option<Comparison>, wind: Option<Comparison>, rain: Option<Comparison>) -> Result<Self, String> {
+        if humidity.is_some_and(|humidity| !humidity.is_within(0.0, 100.0)) {
+            return Err("`humidity` must be between 0 and 100.".to_string());
+        }
+        if wind.is_some_and(|wind| !wind.is_within(0.0, f64::MAX)) {
+            return Err("`wind` cannot be negative.".to_string());
+        }
+        if rain.is_some_and(|rain| !rain.is_within(0.0, f64::MAX)) {
+            return Err("`rain` cannot be negative.".to_string());
+        }
+
+        Ok(Self {
+            feels_like,
+            humidity,
+            wind,
+            rain,
+        })
+    }
+
+    /// Returns `true` if no metric is compared.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns `true` if `observation` satisfies every comparison, a metric
+    /// missing from `observation` satisfying none.
+    pub fn check(&self, observation: &Observation) -> bool {
+        [
+            (self.feels_like, observation.feels_like),
+            (self.humidity, observation.humidity),
+            (self.wind, observation.wind),
+            (self.rain, observation.rain),
+        ]
+        .into_iter()
+        .all(|(comparison, value)| match comparison {
+            None => true,
+            Some(comparison) => value.is_some_and(|value| comparison.matches(value)),
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for MeteoMetrics {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // A misspelled metric would otherwise leave it unrestricted.
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct MeteoMetricsUnsafe {
+            feels_like: Option<Comparison>,
+            humidity: Option<Comparison>,
+            wind: Option<Comparison>,
+            rain: Option<Comparison>,
+        }
+
+        let data = MeteoMetricsUnsafe::deserialize(deserializer)?;
+        MeteoMetrics::new(data.feels_like, data.humidity, data.wind, data.rain).map_err(Error::custom)
+    }
+}
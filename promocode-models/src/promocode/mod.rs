@@ -1,16 +1,31 @@
 use crate::{
-    promocode::restriction::Restriction,
+    promocode::restriction::{Restriction, RestrictionKind},
+    promocode_request::arguments::Arguments,
     promocode_response::{reason::Reasons, PromocodeResponse},
+    signed_promocode::SignedPromocode,
 };
 use avantage::Avantage;
+use chrono::NaiveDate;
+use metadata::Metadata;
+use name_policy::NamePolicy;
 use promocode_util::validate_type::string::NonBlankString;
 use restrictions::{Restrictions, RestrictionsExt};
 use serde::{de::Error, Deserialize, Deserializer, Serialize};
+use status::PromocodeStatus;
+use weather_policy::WeatherPolicy;
 
 pub mod avantage;
+pub mod check_digit;
+pub mod comparison;
+pub mod forecast_window;
+pub mod metadata;
+pub mod meteo_metrics;
+pub mod name_policy;
 pub mod restriction;
 pub mod restrictions;
+pub mod status;
 pub mod temp;
+pub mod weather_policy;
 
 #[derive(Serialize, Clone, PartialEq, Debug)]
 pub struct Promocode {
@@ -19,6 +34,10 @@ pub struct Promocode {
     pub avantage: Avantage,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub restrictions: Restrictions,
+    #[serde(skip_serializing_if = "WeatherPolicy::is_default")]
+    pub weather_policy: WeatherPolicy,
+    #[serde(flatten)]
+    pub metadata: Metadata,
 }
 
 impl Promocode {
@@ -62,9 +81,31 @@ impl Promocode {
             name,
             avantage,
             restrictions,
+            weather_policy: WeatherPolicy::default(),
+            metadata: Metadata::default(),
         })
     }
 
+    /// Returns this [Promocode] with the given [WeatherPolicy].
+    pub fn with_weather_policy(self, weather_policy: WeatherPolicy) -> Self {
+        Self {
+            weather_policy,
+            ..self
+        }
+    }
+
+    /// Returns this [Promocode] with the given [Metadata].
+    ///
+    /// # Errors
+    ///
+    /// This function fails if `metadata` is not correct.
+    pub fn with_metadata(self, metadata: Result<Metadata, String>) -> Result<Self, String> {
+        match metadata {
+            Err(err) => Err(format!("`metadata` > {}", err)),
+            Ok(metadata) => Ok(Self { metadata, ..self }),
+        }
+    }
+
     /// Create a new [Promocode] (unchecked)
     ///
     /// # Safety
@@ -103,9 +144,37 @@ impl Promocode {
             name: NonBlankString::new_unchecked(name),
             avantage,
             restrictions,
+            weather_policy: WeatherPolicy::default(),
+            metadata: Metadata::default(),
         }
     }
 
+    /// Returns this [Promocode] with its name normalized by `policy`.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the name does not comply with `policy`,
+    /// including its check digit, or if it starts with the prefix reserved to
+    /// the [SignedPromocode] codes.
+    pub fn normalize_name(self, policy: &NamePolicy) -> Result<Self, String> {
+        let name = match policy
+            .validate(self.name.to_string().as_str())
+            .map(NonBlankString::new)
+        {
+            Err(err_name) => return Err(format!("`name` {}", err_name)),
+            Ok(Err(err_name)) => return Err(format!("`name` {}", err_name)),
+            Ok(Ok(value)) => value,
+        };
+        if SignedPromocode::is_signed_code(&name.to_string()) {
+            return Err(format!(
+                "`name` cannot start with `{}`, reserved to the signed promocodes",
+                SignedPromocode::PREFIX
+            ));
+        }
+
+        Ok(Self { name, ..self })
+    }
+
     /// Returns the _id as [String] type
     pub fn _id(&self) -> String {
         self._id.clone().get()
@@ -116,6 +185,58 @@ impl Promocode {
         self.name.clone().get()
     }
 
+    /// Returns `true` if one of the restrictions, at any depth, is of the
+    /// given kind.
+    pub fn has_restriction_kind(&self, kind: RestrictionKind) -> bool {
+        self.restrictions.iter().any(|it| it.has_kind(kind))
+    }
+
+    /// Returns `true` if the [Restriction::Date]s accept `day`, the other
+    /// restrictions being assumed satisfied. A [Promocode] without
+    /// restrictions accepts any day.
+    pub fn accepts_date(&self, day: NaiveDate) -> bool {
+        self.restrictions.is_empty() || self.restrictions.iter().any(|it| it.accepts_date(day))
+    }
+
+    /// Checks if the request satisfies one of the restrictions when the
+    /// weather is unavailable, the [Restriction::Meteo]s answering as the
+    /// [WeatherPolicy] of this [Promocode] tells.
+    ///
+    /// # Arguments
+    ///
+    /// - `arguments` - Requested arguments.
+    pub fn check_weather_unavailable(&self, arguments: &Arguments) -> bool {
+        self.restrictions
+            .iter()
+            .any(|it| it.check_weather_unavailable(arguments, self.weather_policy))
+    }
+
+    /// Returns the [PromocodeStatus] on `today`.
+    pub fn status(&self, today: NaiveDate) -> PromocodeStatus {
+        if self.accepts_date(today) {
+            return PromocodeStatus::Active;
+        }
+
+        let last_day = self
+            .restrictions
+            .iter()
+            .filter_map(Restriction::date_span)
+            .map(|(_, last_day)| last_day)
+            .max();
+        match last_day {
+            Some(last_day) if last_day > today => PromocodeStatus::Upcoming,
+            _ => PromocodeStatus::Expired,
+        }
+    }
+
+    /// Returns `true` if `other` has the same content, ignoring the fields of
+    /// the [Metadata] maintained by the server.
+    pub fn same_content(&self, other: &Promocode) -> bool {
+        let mut other = other.clone();
+        other.metadata.inherit(&self.metadata);
+        *self == other
+    }
+
     /// Generate a response for a given promocode.
     ///
     /// # Arguments
@@ -148,8 +269,12 @@ impl<'de> Deserialize<'de> for Promocode {
             _id: String,
             name: String,
             pub avantage: Avantage,
-            #[serde(skip_serializing_if = "Vec::is_empty")]
+            #[serde(default, skip_serializing_if = "Vec::is_empty")]
             pub restrictions: Restrictions,
+            #[serde(default)]
+            pub weather_policy: WeatherPolicy,
+            #[serde(flatten)]
+            pub metadata: Metadata,
         }
 
         match PromocodeUnsafe::deserialize(deserializer) {
@@ -159,6 +284,8 @@ impl<'de> Deserialize<'de> for Promocode {
                 Ok(data.avantage),
                 data.restrictions.iter().map(|it| Ok(it.clone())).collect(),
             )
+            .map(|promocode| promocode.with_weather_policy(data.weather_policy))
+            .and_then(|promocode| promocode.with_metadata(Ok(data.metadata)))
             .map_err(Error::custom),
             Err(err) => Err(Error::custom(err)),
         }
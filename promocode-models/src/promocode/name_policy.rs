@@ -0,0 +1,223 @@
+use crate::promocode::check_digit::CheckDigit;
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+
+/// Case folding applied to a promocode name.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum CaseFolding {
+    #[default]
+    #[serde(rename = "preserve")]
+    Preserve,
+    #[serde(rename = "upper")]
+    Upper,
+    #[serde(rename = "lower")]
+    Lower,
+}
+
+/// Naming policy of the promocodes.
+///
+/// The same policy must be applied when a [Promocode](crate::promocode::Promocode)
+/// is stored and when a name is looked up, so that `summer20` and `SUMMER20`
+/// designate the same promocode.
+///
+/// Normalization is done in this order: trimming, case folding, confusable
+/// character mapping (e.g. `O` to `0`), then the result is checked against
+/// the allowed charset and the length bounds (in characters).
+///
+/// An optional [CheckDigit] lets obvious typos be detected before any lookup.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct NamePolicy {
+    trim: bool,
+    case_folding: CaseFolding,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    confusables: HashMap<char, char>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    allowed_charset: Option<String>,
+    min_length: usize,
+    max_length: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    check_digit: Option<CheckDigit>,
+}
+
+impl Default for NamePolicy {
+    /// Keeps names as given, without any charset or length restriction:
+    /// trimming and case folding are opt-in.
+    fn default() -> Self {
+        Self {
+            trim: false,
+            case_folding: CaseFolding::default(),
+            confusables: HashMap::new(),
+            allowed_charset: None,
+            min_length: 1,
+            max_length: usize::MAX,
+            check_digit: None,
+        }
+    }
+}
+
+impl NamePolicy {
+    /// Create a new [`NamePolicy`](Self)
+    ///
+    /// # Errors
+    ///
+    /// This function fails if one of field is not correct.
+    pub fn new(
+        trim: bool,
+        case_folding: CaseFolding,
+        confusables: HashMap<char, char>,
+        allowed_charset: Option<String>,
+        min_length: usize,
+        max_length: usize,
+    ) -> Result<Self, String> {
+        if min_length == 0 {
+            return Err("`min_length` must be greater than 0.".to_string());
+        }
+        if min_length > max_length {
+            return Err("`max_length` cannot be lower than `min_length`.".to_string());
+        }
+        if allowed_charset
+            .as_ref()
+            .is_some_and(|charset| charset.is_empty())
+        {
+            return Err("`allowed_charset` cannot be empty.".to_string());
+        }
+
+        Ok(Self {
+            trim,
+            case_folding,
+            confusables,
+            allowed_charset,
+            min_length,
+            max_length,
+            check_digit: None,
+        })
+    }
+
+    /// Returns this [NamePolicy] with the given [CheckDigit].
+    pub fn with_check_digit(self, check_digit: Option<CheckDigit>) -> Self {
+        Self {
+            check_digit,
+            ..self
+        }
+    }
+
+    /// Returns the [CheckDigit] of this policy, if any.
+    pub fn check_digit(&self) -> Option<&CheckDigit> {
+        self.check_digit.as_ref()
+    }
+
+    /// Trims `name` if this policy does, the only step of the normalization
+    /// applied to the case sensitive [SignedPromocode](crate::signed_promocode::SignedPromocode)
+    /// codes.
+    pub fn trim<'a>(&self, name: &'a str) -> &'a str {
+        if self.trim {
+            name.trim()
+        } else {
+            name
+        }
+    }
+
+    /// Normalizes `name` according to this policy.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the normalized name contains a character outside
+    /// of the allowed charset or if its length is out of bounds.
+    pub fn normalize(&self, name: &str) -> Result<String, String> {
+        let name = self.trim(name);
+
+        let name = match self.case_folding {
+            CaseFolding::Preserve => name.to_string(),
+            CaseFolding::Upper => name.to_uppercase(),
+            CaseFolding::Lower => name.to_lowercase(),
+        };
+
+        let name: String = name
+            .chars()
+            .map(|c| *self.confusables.get(&c).unwrap_or(&c))
+            .collect();
+
+        if let Some(charset) = &self.allowed_charset {
+            if let Some(forbidden) = name.chars().find(|c| !charset.contains(*c)) {
+                return Err(format!(
+                    "contains forbidden character `{}`",
+                    forbidden.escape_debug()
+                ));
+            }
+        }
+
+        let length = name.chars().count();
+        if length < self.min_length {
+            return Err(format!("is too short ({} < {})", length, self.min_length));
+        }
+        if length > self.max_length {
+            return Err(format!("is too long ({} > {})", length, self.max_length));
+        }
+
+        Ok(name)
+    }
+
+    /// Normalizes `name` according to this policy, then checks its check
+    /// digit if the policy has one.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if `name` cannot be normalized or if its check digit
+    /// is wrong.
+    pub fn validate(&self, name: &str) -> Result<String, String> {
+        let name = self.normalize(name)?;
+
+        match &self.check_digit {
+            Some(check_digit) if !check_digit.is_valid(&name) => Err("has an invalid check digit".to_string()),
+            _ => Ok(name),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for NamePolicy {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+        #[serde(default)]
+        struct NamePolicyUnsafe {
+            trim: bool,
+            case_folding: CaseFolding,
+            confusables: HashMap<char, char>,
+            allowed_charset: Option<String>,
+            min_length: usize,
+            max_length: usize,
+            check_digit: Option<CheckDigit>,
+        }
+
+        impl Default for NamePolicyUnsafe {
+            fn default() -> Self {
+                let default = NamePolicy::default();
+                Self {
+                    trim: default.trim,
+                    case_folding: default.case_folding,
+                    confusables: default.confusables,
+                    allowed_charset: default.allowed_charset,
+                    min_length: default.min_length,
+                    max_length: default.max_length,
+                    check_digit: default.check_digit,
+                }
+            }
+        }
+
+        match NamePolicyUnsafe::deserialize(deserializer) {
+            Ok(data) => NamePolicy::new(
+                data.trim,
+                data.case_folding,
+                data.confusables,
+                data.allowed_charset,
+                data.min_length,
+                data.max_length,
+            )
+            .map(|name_policy| name_policy.with_check_digit(data.check_digit))
+            .map_err(Error::custom),
+            Err(err) => Err(Error::custom(err)),
+        }
+    }
+}
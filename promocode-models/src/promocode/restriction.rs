@@ -1,16 +1,30 @@
 use crate::{
+    forecast::{Forecast, Observation},
     promocode::{
+        forecast_window::{Aggregation, ForecastWindow},
+        meteo_metrics::MeteoMetrics,
         restrictions::{RestrictionsExt, SubRestrictions},
         temp::Temp,
+        weather_policy::WeatherPolicy,
     },
     promocode_request::arguments::Arguments,
 };
-use chrono::{NaiveDate, Utc};
+use chrono::{Duration, NaiveDate, Utc};
 use log::error;
 use promocode_util::validate_type::{number::BoundedU8, string::NonBlankString};
 use serde::{de::Error, Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Kind of a [Restriction] holding a condition, as opposed to
+/// [Restriction::And] and [Restriction::Or] which combine them.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum RestrictionKind {
+    Date,
+    Age,
+    Meteo,
+}
+
 #[derive(Serialize, Clone, PartialEq, Debug)]
 pub enum Restriction {
     #[serde(rename = "@date")]
@@ -30,7 +44,17 @@ pub enum Restriction {
     },
 
     #[serde(rename = "@meteo")]
-    Meteo { is: NonBlankString, temp: Temp },
+    Meteo {
+        is: NonBlankString,
+        temp: Temp,
+        /// Checks the forecast of the next hours instead of the current
+        /// weather.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        forecast: Option<ForecastWindow>,
+        /// Feels-like temperature, humidity, wind and rain.
+        #[serde(flatten)]
+        metrics: MeteoMetrics,
+    },
 
     #[serde(rename = "@and")]
     And(SubRestrictions),
@@ -125,18 +149,9 @@ impl Restriction {
             }
         }
 
-        let lt = match convert_to_option_bounded_u8(lt, "lt".to_string()) {
-            Ok(value) => value,
-            Err(err) => return Err(err),
-        };
-        let eq = match convert_to_option_bounded_u8(eq, "eq".to_string()) {
-            Ok(value) => value,
-            Err(err) => return Err(err),
-        };
-        let gt = match convert_to_option_bounded_u8(gt, "gt".to_string()) {
-            Ok(value) => value,
-            Err(err) => return Err(err),
-        };
+        let lt = convert_to_option_bounded_u8(lt, "lt".to_string())?;
+        let eq = convert_to_option_bounded_u8(eq, "eq".to_string())?;
+        let gt = convert_to_option_bounded_u8(gt, "gt".to_string())?;
 
         if lt.is_none() && eq.is_none() && gt.is_none() {
             return Err("One of `lt`, `eq` or `gt` must be present.".to_string());
@@ -178,12 +193,54 @@ impl Restriction {
     ///
     /// This function fails if `Restriction::Meteo` is not correct.
     pub fn meteo(is: String, temp: Temp) -> Result<Self, String> {
+        Self::meteo_with(is, temp, None, Ok(MeteoMetrics::default()))
+    }
+
+    /// Create a new [`Restriction::Meteo`](Self) on a [ForecastWindow]
+    ///
+    /// # Errors
+    ///
+    /// This function fails if `Restriction::Meteo` or `forecast` is not
+    /// correct.
+    pub fn meteo_forecast(is: String, temp: Temp, forecast: Result<ForecastWindow, String>) -> Result<Self, String> {
+        Self::meteo_with(is, temp, Some(forecast), Ok(MeteoMetrics::default()))
+    }
+
+    /// Create a new [`Restriction::Meteo`](Self) also comparing the
+    /// [MeteoMetrics]
+    ///
+    /// # Errors
+    ///
+    /// This function fails if `Restriction::Meteo` or `metrics` is not
+    /// correct.
+    pub fn meteo_metrics(is: String, temp: Temp, metrics: Result<MeteoMetrics, String>) -> Result<Self, String> {
+        Self::meteo_with(is, temp, None, metrics)
+    }
+
+    /// Create a new [`Restriction::Meteo`](Self), on a [ForecastWindow] if
+    /// `forecast` is not [None], also comparing the [MeteoMetrics]
+    ///
+    /// # Errors
+    ///
+    /// This function fails if `Restriction::Meteo`, `forecast` or `metrics` is
+    /// not correct.
+    pub fn meteo_with(is: String, temp: Temp, forecast: Option<Result<ForecastWindow, String>>, metrics: Result<MeteoMetrics, String>) -> Result<Self, String> {
         let is = match NonBlankString::new(is) {
             Err(err_name) => return Err(format!("`is` {}", err_name)),
             Ok(value) => value,
         };
+        let forecast = match forecast.transpose() {
+            Err(err_forecast) => return Err(format!("`forecast` {}", err_forecast)),
+            Ok(value) => value,
+        };
+        let metrics = metrics?;
 
-        Ok(Self::Meteo { is, temp })
+        Ok(Self::Meteo {
+            is,
+            temp,
+            forecast,
+            metrics,
+        })
     }
 
     /// Create a new [Restriction::Meteo] (unchecked)
@@ -216,6 +273,8 @@ impl Restriction {
         Self::Meteo {
             is: NonBlankString::new_unchecked(is),
             temp,
+            forecast: None,
+            metrics: MeteoMetrics::default(),
         }
     }
 
@@ -275,14 +334,59 @@ impl Restriction {
     /// # Arguments
     ///
     /// - `arguments` - Requested arguments.
-    /// - `weather_and_temp` - The optional weather condition and temperature.
-    pub fn check_restriction_generic(&self, arguments: Arguments, weather_and_temp: Option<(String, f64)>) -> bool {
+    /// - `forecast` - The optional weather forecast.
+    pub fn check_restriction_generic(&self, arguments: Arguments, forecast: Option<Forecast>) -> bool {
         match self {
             Restriction::Date { after, before } => Self::check_restriction_date(after, before),
             Restriction::Age { lt, eq, gt } => Self::check_restriction_age(&arguments, lt, eq, gt),
-            Restriction::Meteo { is, temp } => Self::check_restriction_meteo(&weather_and_temp, is, temp),
-            Restriction::Or(or_restriction) => or_restriction.check_restriction_or(arguments.clone(), weather_and_temp.clone()),
-            Restriction::And(and_restriction) => and_restriction.check_restriction_and(arguments.clone(), weather_and_temp.clone()),
+            Restriction::Meteo {
+                is,
+                temp,
+                forecast: window,
+                metrics,
+            } => Self::check_restriction_meteo(&forecast, is, temp, window, metrics),
+            Restriction::Or(or_restriction) => or_restriction.check_restriction_or(arguments.clone(), forecast.clone()),
+            Restriction::And(and_restriction) => and_restriction.check_restriction_and(arguments.clone(), forecast.clone()),
+        }
+    }
+
+    /// Checks if the request satisfies this [Restriction] without the weather.
+    ///
+    /// Returns [None] if the outcome depends on a [Restriction::Meteo]. The
+    /// [Restriction::Or] and [Restriction::And] are decided as soon as one of
+    /// their cheap restrictions allows it.
+    ///
+    /// # Arguments
+    ///
+    /// - `arguments` - Requested arguments.
+    pub fn check_without_weather(&self, arguments: &Arguments) -> Option<bool> {
+        match self {
+            Restriction::Date { after, before } => Some(Self::check_restriction_date(after, before)),
+            Restriction::Age { lt, eq, gt } => Some(Self::check_restriction_age(arguments, lt, eq, gt)),
+            Restriction::Meteo { .. } => None,
+            Restriction::Or(restrictions) => restrictions.check_restriction_or_without_weather(arguments),
+            Restriction::And(restrictions) => restrictions.check_restriction_and_without_weather(arguments),
+        }
+    }
+
+    /// Checks if the request satisfies this [Restriction] when the weather is
+    /// unavailable, every [Restriction::Meteo] being satisfied only if
+    /// `weather_policy` is [WeatherPolicy::FailOpen].
+    ///
+    /// # Arguments
+    ///
+    /// - `arguments` - Requested arguments.
+    /// - `weather_policy` - What a [Restriction::Meteo] answers.
+    pub fn check_weather_unavailable(&self, arguments: &Arguments, weather_policy: WeatherPolicy) -> bool {
+        match self {
+            Restriction::Meteo { .. } => weather_policy == WeatherPolicy::FailOpen,
+            Restriction::Or(restrictions) => restrictions
+                .iter()
+                .any(|it| it.check_weather_unavailable(arguments, weather_policy)),
+            Restriction::And(restrictions) => restrictions
+                .iter()
+                .all(|it| it.check_weather_unavailable(arguments, weather_policy)),
+            Restriction::Date { .. } | Restriction::Age { .. } => self.check_without_weather(arguments) == Some(true),
         }
     }
 
@@ -295,12 +399,67 @@ impl Restriction {
     /// - `before` - Requested min date (default: [NaiveDate::MIN]).
     fn check_restriction_date(after: &NonBlankString, before: &NonBlankString) -> bool {
         let now = Utc::now().date_naive();
-        let after_date = NaiveDate::parse_from_str(after.clone().get().as_str(), "%Y-%m-%d").unwrap_or(NaiveDate::MIN);
-        let before_date = NaiveDate::parse_from_str(before.clone().get().as_str(), "%Y-%m-%d").unwrap_or(NaiveDate::MAX);
+        let (after_date, before_date) = Self::date_bounds(after, before);
 
         after_date <= now && now <= before_date
     }
 
+    /// Returns the first and last days of a [Restriction::Date].
+    fn date_bounds(after: &NonBlankString, before: &NonBlankString) -> (NaiveDate, NaiveDate) {
+        (
+            NaiveDate::parse_from_str(after.clone().get().as_str(), "%Y-%m-%d").unwrap_or(NaiveDate::MIN),
+            NaiveDate::parse_from_str(before.clone().get().as_str(), "%Y-%m-%d").unwrap_or(NaiveDate::MAX),
+        )
+    }
+
+    /// Returns `true` if this [Restriction] is, or contains, a restriction of
+    /// the given kind.
+    pub fn has_kind(&self, kind: RestrictionKind) -> bool {
+        match self {
+            Restriction::Date { .. } => kind == RestrictionKind::Date,
+            Restriction::Age { .. } => kind == RestrictionKind::Age,
+            Restriction::Meteo { .. } => kind == RestrictionKind::Meteo,
+            Restriction::And(restrictions) | Restriction::Or(restrictions) => restrictions.iter().any(|it| it.has_kind(kind)),
+        }
+    }
+
+    /// Returns `true` if the [Restriction::Date]s of this [Restriction] accept
+    /// `day`, the other restrictions being assumed satisfied.
+    pub fn accepts_date(&self, day: NaiveDate) -> bool {
+        match self {
+            Restriction::Date { after, before } => {
+                let (after_date, before_date) = Self::date_bounds(after, before);
+                after_date <= day && day <= before_date
+            },
+            Restriction::Age { .. } | Restriction::Meteo { .. } => true,
+            Restriction::And(restrictions) => restrictions.iter().all(|it| it.accepts_date(day)),
+            Restriction::Or(restrictions) => restrictions.iter().any(|it| it.accepts_date(day)),
+        }
+    }
+
+    /// Returns the first and last days the [Restriction::Date]s of this
+    /// [Restriction] may accept, or [None] if they accept no day at all.
+    ///
+    /// The span of a [Restriction::Or] may contain days between its windows
+    /// that none of them accepts.
+    pub fn date_span(&self) -> Option<(NaiveDate, NaiveDate)> {
+        match self {
+            Restriction::Date { after, before } => Some(Self::date_bounds(after, before)),
+            Restriction::Age { .. } | Restriction::Meteo { .. } => Some((NaiveDate::MIN, NaiveDate::MAX)),
+            Restriction::And(restrictions) => restrictions
+                .iter()
+                .map(Restriction::date_span)
+                .try_fold((NaiveDate::MIN, NaiveDate::MAX), |(first, last), span| {
+                    span.map(|(after, before)| (first.max(after), last.min(before)))
+                })
+                .filter(|(first, last)| first <= last),
+            Restriction::Or(restrictions) => restrictions
+                .iter()
+                .filter_map(Restriction::date_span)
+                .reduce(|(first, last), (after, before)| (first.min(after), last.max(before))),
+        }
+    }
+
     /// Checks if the request satisfies [Restriction::Age]. Returns a boolean
     /// indicating whether the request is valid or not.
     ///
@@ -332,17 +491,41 @@ impl Restriction {
     ///
     /// # Arguments
     ///
-    /// - `weather_and_temp` - Current weather and temperature from remote.
+    /// - `forecast` - Weather forecast from remote.
     /// - `is` - Requested weather.
     /// - `temp` - Requested temperature.
-    fn check_restriction_meteo(weather_and_temp: &Option<(String, f64)>, is: &NonBlankString, temp: &Temp) -> bool {
-        match weather_and_temp {
-            None => {
-                error!("Skip meteo check and return false because open_weather_sdk_unchecked is None.");
-                false
-            },
-            Some((ref remote_weather, remote_temp)) => &is.clone().get() == remote_weather && temp.gt as f64 <= *remote_temp,
-        }
+    /// - `window` - Requested [ForecastWindow], the current weather being
+    ///   checked if [None].
+    /// - `metrics` - Requested [MeteoMetrics], checked on each step of the
+    ///   window and aggregated as the weather.
+    fn check_restriction_meteo(forecast: &Option<Forecast>, is: &NonBlankString, temp: &Temp, window: &Option<ForecastWindow>, metrics: &MeteoMetrics) -> bool {
+        let Some(forecast) = forecast else {
+            error!("Skip meteo check and return false because the weather forecast is None.");
+            return false;
+        };
+
+        let is = is.clone().get();
+        let gt = temp.gt as f64;
+        let Some(window) = window else {
+            let current = &forecast.current().observation;
+            return current.condition == is && gt <= current.temp && metrics.check(current);
+        };
+
+        let steps = forecast.until(Utc::now() + Duration::hours(window.hours() as i64));
+        let observations = || steps.iter().map(|step| &step.observation);
+        // The condition and the metrics must match on the same step.
+        let step_matches = |observation: &Observation| observation.condition == is && metrics.check(observation);
+        let steps_match = match window.is() {
+            Aggregation::All => observations().all(step_matches),
+            _ => observations().any(step_matches),
+        };
+        let temps = observations().map(|observation| observation.temp);
+        let remote_temp = match window.temp() {
+            Aggregation::Min => temps.fold(f64::INFINITY, f64::min),
+            _ => temps.fold(f64::NEG_INFINITY, f64::max),
+        };
+
+        steps_match && gt <= remote_temp
     }
 }
 
@@ -388,9 +571,21 @@ impl<'de> Deserialize<'de> for Restriction {
             struct MeteoUnsafe {
                 is: String,
                 temp: Temp,
+                #[serde(default)]
+                forecast: Option<serde_json::Value>,
+                #[serde(flatten)]
+                metrics: serde_json::Value,
             }
             let meteo: MeteoUnsafe = serde_json::from_value(value).map_err(D::Error::custom)?;
-            return match Restriction::meteo(meteo.is, meteo.temp) {
+            let restriction = Restriction::meteo_with(
+                meteo.is,
+                meteo.temp,
+                meteo
+                    .forecast
+                    .map(|forecast| serde_json::from_value(forecast).map_err(|err| err.to_string())),
+                serde_json::from_value(meteo.metrics).map_err(|err| err.to_string()),
+            );
+            return match restriction {
                 Ok(result) => Ok(result),
                 Err(err) => Err(Error::custom(err)),
             };
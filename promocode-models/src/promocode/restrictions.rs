@@ -1,4 +1,4 @@
-use crate::{promocode::restriction::Restriction, promocode_request::arguments::Arguments};
+use crate::{forecast::Forecast, promocode::restriction::Restriction, promocode_request::arguments::Arguments};
 use promocode_util::validate_type::sequence::NonEmptyVec;
 
 /// A collection of `Restriction` objects
@@ -7,8 +7,42 @@ pub type Restrictions = Vec<Restriction>;
 /// Trait for extending the functionality of `Restrictions`.
 pub trait RestrictionsExt<T = Self> {
     fn from_vec(value: Vec<Result<Restriction, String>>) -> Result<T, String>;
-    fn check_restriction_or(&self, arguments: Arguments, weather_and_temp: Option<(String, f64)>) -> bool;
-    fn check_restriction_and(&self, arguments: Arguments, weather_and_temp: Option<(String, f64)>) -> bool;
+    fn check_restriction_or(&self, arguments: Arguments, forecast: Option<Forecast>) -> bool;
+    fn check_restriction_and(&self, arguments: Arguments, forecast: Option<Forecast>) -> bool;
+    fn check_restriction_or_without_weather(&self, arguments: &Arguments) -> Option<bool>;
+    fn check_restriction_and_without_weather(&self, arguments: &Arguments) -> Option<bool>;
+}
+
+/// Checks if the request satisfies one of `restrictions` without the weather.
+///
+/// Returns [None] if the outcome depends on a [Restriction::Meteo], i.e. if
+/// none of the restrictions is satisfied and some of them need the weather.
+pub(crate) fn any_without_weather<'a>(restrictions: impl Iterator<Item = &'a Restriction>, arguments: &Arguments) -> Option<bool> {
+    let mut outcome = Some(false);
+    for restriction in restrictions {
+        match restriction.check_without_weather(arguments) {
+            Some(true) => return Some(true),
+            Some(false) => {},
+            None => outcome = None,
+        }
+    }
+    outcome
+}
+
+/// Checks if the request satisfies all `restrictions` without the weather.
+///
+/// Returns [None] if the outcome depends on a [Restriction::Meteo], i.e. if
+/// none of the restrictions fails and some of them need the weather.
+pub(crate) fn all_without_weather<'a>(restrictions: impl Iterator<Item = &'a Restriction>, arguments: &Arguments) -> Option<bool> {
+    let mut outcome = Some(true);
+    for restriction in restrictions {
+        match restriction.check_without_weather(arguments) {
+            Some(false) => return Some(false),
+            Some(true) => {},
+            None => outcome = None,
+        }
+    }
+    outcome
 }
 
 impl RestrictionsExt for Restrictions {
@@ -33,10 +67,10 @@ impl RestrictionsExt for Restrictions {
     /// # Arguments
     ///
     /// - `arguments` - Requested arguments.
-    /// - `weather_and_temp` - The optional weather condition and temperature.
-    fn check_restriction_or(&self, arguments: Arguments, weather_and_temp: Option<(String, f64)>) -> bool {
+    /// - `forecast` - The optional weather forecast.
+    fn check_restriction_or(&self, arguments: Arguments, forecast: Option<Forecast>) -> bool {
         self.iter()
-            .any(|restriction| restriction.check_restriction_generic(arguments.clone(), weather_and_temp.clone()))
+            .any(|restriction| restriction.check_restriction_generic(arguments.clone(), forecast.clone()))
     }
 
     /// Checks if the request satisfies all the given [Restrictions]. Returns a
@@ -45,10 +79,37 @@ impl RestrictionsExt for Restrictions {
     /// # Arguments
     ///
     /// - `arguments` - Requested arguments.
-    /// - `weather_and_temp` - The optional weather condition and temperature.
-    fn check_restriction_and(&self, arguments: Arguments, weather_and_temp: Option<(String, f64)>) -> bool {
+    /// - `forecast` - The optional weather forecast.
+    fn check_restriction_and(&self, arguments: Arguments, forecast: Option<Forecast>) -> bool {
         self.iter()
-            .all(|restriction| restriction.check_restriction_generic(arguments.clone(), weather_and_temp.clone()))
+            .all(|restriction| restriction.check_restriction_generic(arguments.clone(), forecast.clone()))
+    }
+
+    /// Checks if the request satisfies one of the given [Restrictions] with
+    /// the cheap restrictions only, so that the weather is fetched only when
+    /// needed.
+    ///
+    /// Returns [None] if the outcome depends on the weather, to be decided by
+    /// [check_restriction_or](RestrictionsExt::check_restriction_or).
+    ///
+    /// # Arguments
+    ///
+    /// - `arguments` - Requested arguments.
+    fn check_restriction_or_without_weather(&self, arguments: &Arguments) -> Option<bool> {
+        any_without_weather(self.iter(), arguments)
+    }
+
+    /// Checks if the request satisfies all the given [Restrictions] with the
+    /// cheap restrictions only.
+    ///
+    /// Returns [None] if the outcome depends on the weather, to be decided by
+    /// [check_restriction_and](RestrictionsExt::check_restriction_and).
+    ///
+    /// # Arguments
+    ///
+    /// - `arguments` - Requested arguments.
+    fn check_restriction_and_without_weather(&self, arguments: &Arguments) -> Option<bool> {
+        all_without_weather(self.iter(), arguments)
     }
 }
 
@@ -77,12 +138,12 @@ impl RestrictionsExt for SubRestrictions {
     /// # Arguments
     ///
     /// - `arguments` - Requested arguments.
-    /// - `weather_and_temp` - The optional weather condition and temperature.
-    fn check_restriction_or(&self, arguments: Arguments, weather_and_temp: Option<(String, f64)>) -> bool {
+    /// - `forecast` - The optional weather forecast.
+    fn check_restriction_or(&self, arguments: Arguments, forecast: Option<Forecast>) -> bool {
         self.clone()
             .get()
             .iter()
-            .any(|restriction| restriction.check_restriction_generic(arguments.clone(), weather_and_temp.clone()))
+            .any(|restriction| restriction.check_restriction_generic(arguments.clone(), forecast.clone()))
     }
 
     /// Checks if the request satisfies all the given [SubRestrictions]. Returns a
@@ -91,11 +152,38 @@ impl RestrictionsExt for SubRestrictions {
     /// # Arguments
     ///
     /// - `arguments` - Requested arguments.
-    /// - `weather_and_temp` - The optional weather condition and temperature.
-    fn check_restriction_and(&self, arguments: Arguments, weather_and_temp: Option<(String, f64)>) -> bool {
+    /// - `forecast` - The optional weather forecast.
+    fn check_restriction_and(&self, arguments: Arguments, forecast: Option<Forecast>) -> bool {
         self.clone()
             .get()
             .iter()
-            .all(|restriction| restriction.check_restriction_generic(arguments.clone(), weather_and_temp.clone()))
+            .all(|restriction| restriction.check_restriction_generic(arguments.clone(), forecast.clone()))
+    }
+
+    /// Checks if the request satisfies one of the given [SubRestrictions] with
+    /// the cheap restrictions only, so that the weather is fetched only when
+    /// needed.
+    ///
+    /// Returns [None] if the outcome depends on the weather, to be decided by
+    /// [check_restriction_or](RestrictionsExt::check_restriction_or).
+    ///
+    /// # Arguments
+    ///
+    /// - `arguments` - Requested arguments.
+    fn check_restriction_or_without_weather(&self, arguments: &Arguments) -> Option<bool> {
+        any_without_weather(self.iter(), arguments)
+    }
+
+    /// Checks if the request satisfies all the given [SubRestrictions] with the
+    /// cheap restrictions only.
+    ///
+    /// Returns [None] if the outcome depends on the weather, to be decided by
+    /// [check_restriction_and](RestrictionsExt::check_restriction_and).
+    ///
+    /// # Arguments
+    ///
+    /// - `arguments` - Requested arguments.
+    fn check_restriction_and_without_weather(&self, arguments: &Arguments) -> Option<bool> {
+        all_without_weather(self.iter(), arguments)
     }
 }
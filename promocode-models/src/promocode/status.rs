@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// Status of a [Promocode](crate::promocode::Promocode) on a given day,
+/// according to its [Restriction::Date](crate::promocode::restriction::Restriction::Date)s.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum PromocodeStatus {
+    /// Not accepted yet, but on a later day.
+    Upcoming,
+    /// Accepted on that day, other restrictions permitting.
+    Active,
+    /// Not accepted anymore.
+    Expired,
+}
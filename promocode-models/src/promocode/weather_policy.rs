@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// What a [Restriction::Meteo](crate::promocode::restriction::Restriction::Meteo)
+/// answers when the weather is unavailable (e.g. the weather API is down or
+/// its quota exhausted).
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WeatherPolicy {
+    /// The restriction is not satisfied.
+    #[default]
+    FailClosed,
+    /// The restriction is satisfied.
+    FailOpen,
+}
+
+impl WeatherPolicy {
+    /// Returns `true` for the default [WeatherPolicy::FailClosed], which is
+    /// not serialized.
+    pub fn is_default(&self) -> bool {
+        *self == WeatherPolicy::default()
+    }
+}
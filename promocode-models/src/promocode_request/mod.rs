@@ -1,6 +1,11 @@
 use arguments::Arguments;
 use promocode_util::validate_type::string::NonBlankString;
-use serde::{de::Error, Deserialize, Deserializer, Serialize};
+use serde::{
+    de::{DeserializeSeed, Error},
+    Deserialize, Deserializer, Serialize,
+};
+
+use crate::{promocode::name_policy::NamePolicy, signed_promocode::SignedPromocode};
 
 pub mod arguments;
 pub mod meteo;
@@ -9,6 +14,19 @@ pub mod meteo;
 pub struct PromocodeRequest {
     promocode_name: NonBlankString,
     pub arguments: Arguments,
+    /// The name normalized by the [NamePolicy] the request was checked with.
+    #[serde(skip)]
+    normalized_name: Option<String>,
+}
+
+/// Name of a [PromocodeRequest] that does not comply with a [NamePolicy],
+/// e.g. because of a wrong check digit.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MalformedName {
+    /// The name, as it was typed.
+    pub promocode_name: String,
+    /// Why the name does not comply with the [NamePolicy].
+    pub reason: String,
 }
 
 impl PromocodeRequest {
@@ -31,9 +49,42 @@ impl PromocodeRequest {
         Ok(Self {
             promocode_name,
             arguments,
+            normalized_name: None,
         })
     }
 
+    /// Returns this [PromocodeRequest] with its name normalized and checked by
+    /// `policy`, check digit included.
+    ///
+    /// The name of a [SignedPromocode] is only trimmed, if `policy` does: it is
+    /// case sensitive and not looked up.
+    ///
+    /// # Errors
+    ///
+    /// This function fails with a [MalformedName] if the name does not comply
+    /// with `policy`.
+    pub fn with_name_policy(self, policy: &NamePolicy) -> Result<Self, MalformedName> {
+        let promocode_name = self.promocode_name();
+        let trimmed_name = policy.trim(&promocode_name);
+        if SignedPromocode::is_signed_code(trimmed_name) {
+            return Ok(Self {
+                normalized_name: Some(trimmed_name.to_string()),
+                ..self
+            });
+        }
+
+        match policy.validate(&promocode_name) {
+            Ok(normalized_name) => Ok(Self {
+                normalized_name: Some(normalized_name),
+                ..self
+            }),
+            Err(reason) => Err(MalformedName {
+                promocode_name,
+                reason,
+            }),
+        }
+    }
+
     /// Create a new [PromocodeRequest] (unchecked)
     ///
     /// # Safety
@@ -67,6 +118,7 @@ impl PromocodeRequest {
         Self {
             promocode_name: NonBlankString::new_unchecked(promocode_name),
             arguments,
+            normalized_name: None,
         }
     }
 
@@ -74,6 +126,21 @@ impl PromocodeRequest {
     pub fn promocode_name(&self) -> String {
         self.promocode_name.clone().get()
     }
+
+    /// Returns the name normalized by the [NamePolicy] of
+    /// [with_name_policy](Self::with_name_policy), or the name as it was typed
+    /// if the request was not checked.
+    pub fn normalized_name(&self) -> String {
+        self.normalized_name
+            .clone()
+            .unwrap_or_else(|| self.promocode_name())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct PromocodeRequestUnsafe {
+    promocode_name: String,
+    arguments: Arguments,
 }
 
 impl<'de> Deserialize<'de> for PromocodeRequest {
@@ -81,14 +148,40 @@ impl<'de> Deserialize<'de> for PromocodeRequest {
     where
         D: Deserializer<'de>,
     {
-        #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
-        struct PromocodeRequestUnsafe {
-            promocode_name: String,
-            arguments: Arguments,
+        match PromocodeRequestUnsafe::deserialize(deserializer) {
+            Ok(data) => PromocodeRequest::new(data.promocode_name, Ok(data.arguments)).map_err(Error::custom),
+            Err(err) => Err(Error::custom(err)),
         }
+    }
+}
+
+/// Deserializes a [PromocodeRequest] whose name is checked by a [NamePolicy],
+/// see [PromocodeRequest::with_name_policy].
+///
+/// A name that does not comply with the policy is not a deserialization
+/// error, but a [MalformedName]: obvious typos get a distinct response.
+pub struct PromocodeRequestSeed<'a> {
+    policy: &'a NamePolicy,
+}
 
+impl<'a> PromocodeRequestSeed<'a> {
+    /// Create a new [`PromocodeRequestSeed`](Self)
+    pub fn new(policy: &'a NamePolicy) -> Self {
+        Self { policy }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for PromocodeRequestSeed<'_> {
+    type Value = Result<PromocodeRequest, MalformedName>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
         match PromocodeRequestUnsafe::deserialize(deserializer) {
-            Ok(data) => PromocodeRequest::new(data.promocode_name, Ok(data.arguments)).map_err(Error::custom),
+            Ok(data) => PromocodeRequest::new(data.promocode_name, Ok(data.arguments))
+                .map(|promocode_request| promocode_request.with_name_policy(self.policy))
+                .map_err(Error::custom),
             Err(err) => Err(Error::custom(err)),
         }
     }
@@ -21,6 +21,13 @@ pub enum PromocodeResponse {
         status: Status,
         reasons: Reasons,
     },
+
+    Malformed {
+        promocode_name: NonBlankString,
+        status: Status,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        suggestion: Option<NonBlankString>,
+    },
 }
 
 impl PromocodeResponse {
@@ -130,6 +137,65 @@ impl PromocodeResponse {
             reasons,
         }
     }
+
+    /// Create a new [`PromocodeResponse::Malformed`](Self)
+    ///
+    /// A malformed promocode name is rejected before any lookup (e.g. because
+    /// of a wrong check digit). `suggestion` is an existing promocode name
+    /// close to the requested one.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if `PromocodeResponse::Malformed` is not correct.
+    pub fn malformed(promocode_name: String, suggestion: Option<String>) -> Result<Self, String> {
+        let promocode_name = match NonBlankString::new(promocode_name) {
+            Err(err_after) => return Err(format!("`promocode_name` {}", err_after)),
+            Ok(value) => value,
+        };
+
+        let suggestion = match suggestion.map(NonBlankString::new).transpose() {
+            Err(err_after) => return Err(format!("`suggestion` {}", err_after)),
+            Ok(value) => value,
+        };
+
+        Ok(PromocodeResponse::Malformed {
+            promocode_name,
+            status: Status::Malformed,
+            suggestion,
+        })
+    }
+
+    /// Create a new [PromocodeResponse::Malformed] (unchecked)
+    ///
+    /// # Safety
+    ///
+    /// This function is marked `unsafe` because it assumes that
+    /// `PromocodeResponse::Malformed` is correct, without performing any
+    /// validation.
+    /// It's up to the caller to ensure any `promocode_name` and `suggestion`
+    /// string passed is not blank.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use promocode_models::promocode_response::PromocodeResponse;
+    ///
+    /// let response = unsafe {
+    ///     PromocodeResponse::malformed_unchecked(
+    ///         "promocode_name".to_string(),
+    ///         None,
+    ///     )
+    /// };
+    /// ```
+    ///
+    /// This function assumes `PromocodeResponse::Malformed` is correct.
+    pub unsafe fn malformed_unchecked(promocode_name: String, suggestion: Option<String>) -> Self {
+        PromocodeResponse::Malformed {
+            promocode_name: NonBlankString::new_unchecked(promocode_name),
+            status: Status::Malformed,
+            suggestion: suggestion.map(|it| NonBlankString::new_unchecked(it)),
+        }
+    }
 }
 
 impl<'de> Deserialize<'de> for PromocodeResponse {
@@ -143,11 +209,18 @@ impl<'de> Deserialize<'de> for PromocodeResponse {
             status: Status,
             avantage: Option<Avantage>,
             reasons: Option<Reasons>,
+            suggestion: Option<NonBlankString>,
         }
 
         let data = PromocodeResponseUnknown::deserialize(deserializer)?;
 
         match (data.status.clone(), data.avantage, data.reasons) {
+            (Status::Malformed, None, None) => Ok(PromocodeResponse::Malformed {
+                promocode_name: data.promocode_name,
+                status: data.status,
+                suggestion: data.suggestion,
+            }),
+            (_, _, _) if data.suggestion.is_some() => Err(D::Error::custom("Invalid promocode response")),
             (Status::Accepted, Some(avantage), None) => Ok(PromocodeResponse::Accepted {
                 promocode_name: data.promocode_name,
                 status: data.status,
@@ -6,4 +6,6 @@ pub enum Status {
     Accepted,
     #[serde(rename = "denied")]
     Denied,
+    #[serde(rename = "malformed")]
+    Malformed,
 }
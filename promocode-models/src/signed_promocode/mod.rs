@@ -0,0 +1,234 @@
+use crate::{
+    forecast::Forecast,
+    promocode::{
+        avantage::Avantage,
+        restriction::Restriction,
+        restrictions::{Restrictions, RestrictionsExt},
+        weather_policy::WeatherPolicy,
+    },
+    promocode_request::arguments::Arguments,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::NaiveDate;
+use hmac::{Hmac, Mac};
+use promocode_util::validate_type::string::NonBlankString;
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// A self-contained promocode, signed with a key held by the server.
+///
+/// It encodes its own [Avantage], expiry date and [Restrictions], so it can be
+/// validated without being stored. Once encoded, it looks like
+/// `SIG1.<payload>.<signature>` where the payload is the JSON representation
+/// of this struct and the signature its HMAC-SHA256, both in unpadded base64
+/// url.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct SignedPromocode {
+    name: NonBlankString,
+    pub avantage: Avantage,
+    expires: NaiveDate,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub restrictions: Restrictions,
+    #[serde(skip_serializing_if = "WeatherPolicy::is_default")]
+    pub weather_policy: WeatherPolicy,
+}
+
+impl SignedPromocode {
+    /// Prefix of every encoded [SignedPromocode].
+    pub const PREFIX: &'static str = "SIG1.";
+
+    /// Create a new [`SignedPromocode`](Self)
+    ///
+    /// # Errors
+    ///
+    /// This function fails if one of field is not correct.
+    pub fn new(name: String, avantage: Result<Avantage, String>, expires: String, restrictions: Vec<Result<Restriction, String>>) -> Result<Self, String> {
+        let name = match NonBlankString::new(name) {
+            Err(err_name) => return Err(format!("`name` {}", err_name)),
+            Ok(value) => value,
+        };
+
+        let avantage = match avantage {
+            Err(err) => return Err(format!("`avantage` > {}", err)),
+            Ok(value) => value,
+        };
+
+        let expires = match NaiveDate::parse_from_str(expires.as_str(), "%Y-%m-%d") {
+            Err(_) => return Err("Cannot parse `expires`.".to_string()),
+            Ok(value) => value,
+        };
+
+        let restrictions = match Restrictions::from_vec(restrictions) {
+            Err(err) => {
+                let err_fmt = err
+                    .lines()
+                    .map(|l| format!("\t{}", l))
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                return Err(format!("`restrictions` > {}", err_fmt));
+            },
+            Ok(value) => value,
+        };
+
+        Ok(Self {
+            name,
+            avantage,
+            expires,
+            restrictions,
+            weather_policy: WeatherPolicy::default(),
+        })
+    }
+
+    /// Returns this [SignedPromocode] with the given [WeatherPolicy].
+    pub fn with_weather_policy(self, weather_policy: WeatherPolicy) -> Self {
+        Self {
+            weather_policy,
+            ..self
+        }
+    }
+
+    /// Returns the name as [String] type
+    pub fn name(&self) -> String {
+        self.name.clone().get()
+    }
+
+    /// Returns the last day this [SignedPromocode] is valid.
+    pub fn expires(&self) -> NaiveDate {
+        self.expires
+    }
+
+    /// Returns `true` if `code` looks like an encoded [SignedPromocode].
+    pub fn is_signed_code(code: &str) -> bool {
+        code.starts_with(Self::PREFIX)
+    }
+
+    /// Encodes and signs this [SignedPromocode] with `key`.
+    pub fn encode(&self, key: &[u8]) -> String {
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap());
+        let signature = URL_SAFE_NO_PAD.encode(Self::sign(key, &payload).finalize().into_bytes());
+
+        format!("{}{}.{}", Self::PREFIX, payload, signature)
+    }
+
+    /// Verifies the signature of `code` with `key`, then decodes it.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if `code` is not an encoded [SignedPromocode], if
+    /// it was tampered with or signed with another key.
+    pub fn decode(code: &str, key: &[u8]) -> Result<Self, String> {
+        let (payload, signature) = match code
+            .strip_prefix(Self::PREFIX)
+            .and_then(|it| it.split_once('.'))
+        {
+            None => return Err("Signed promocode is malformed.".to_string()),
+            Some(value) => value,
+        };
+
+        let signature = match URL_SAFE_NO_PAD.decode(signature) {
+            Err(_) => return Err("Signed promocode is malformed.".to_string()),
+            Ok(value) => value,
+        };
+        if Self::sign(key, payload).verify_slice(&signature).is_err() {
+            return Err("Signed promocode has an invalid signature.".to_string());
+        }
+
+        match URL_SAFE_NO_PAD.decode(payload) {
+            Err(_) => Err("Signed promocode is malformed.".to_string()),
+            Ok(payload) => serde_json::from_slice(&payload).map_err(|err| format!("Signed promocode is invalid: {}", err)),
+        }
+    }
+
+    /// Checks if this [SignedPromocode] is not expired on `today` and if the
+    /// request satisfies one of its [Restrictions], if any.
+    ///
+    /// Its expiry is enough to restrict a [SignedPromocode]: without
+    /// [Restrictions], it accepts every request until it expires, whereas a
+    /// stored [Promocode](crate::promocode::Promocode) without [Restrictions]
+    /// denies every request.
+    ///
+    /// # Arguments
+    ///
+    /// - `arguments` - Requested arguments.
+    /// - `forecast` - The optional weather forecast.
+    /// - `today` - The current date.
+    pub fn check(&self, arguments: Arguments, forecast: Option<Forecast>, today: NaiveDate) -> bool {
+        today <= self.expires && (self.restrictions.is_empty() || self.restrictions.check_restriction_or(arguments, forecast))
+    }
+
+    /// Checks this [SignedPromocode] as [check](Self::check) does, without
+    /// the weather.
+    ///
+    /// Returns [None] if the outcome depends on the weather.
+    ///
+    /// # Arguments
+    ///
+    /// - `arguments` - Requested arguments.
+    /// - `today` - The current date.
+    pub fn check_without_weather(&self, arguments: &Arguments, today: NaiveDate) -> Option<bool> {
+        if today > self.expires {
+            return Some(false);
+        }
+        if self.restrictions.is_empty() {
+            return Some(true);
+        }
+        self.restrictions
+            .check_restriction_or_without_weather(arguments)
+    }
+
+    /// Checks this [SignedPromocode] as [check](Self::check) does, when the
+    /// weather is unavailable, the
+    /// [Restriction::Meteo](crate::promocode::restriction::Restriction::Meteo)s
+    /// answering as its [WeatherPolicy] tells.
+    ///
+    /// # Arguments
+    ///
+    /// - `arguments` - Requested arguments.
+    /// - `today` - The current date.
+    pub fn check_weather_unavailable(&self, arguments: &Arguments, today: NaiveDate) -> bool {
+        today <= self.expires
+            && (self.restrictions.is_empty()
+                || self
+                    .restrictions
+                    .iter()
+                    .any(|it| it.check_weather_unavailable(arguments, self.weather_policy)))
+    }
+
+    fn sign(key: &[u8], payload: &str) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(key).expect("HMAC can take key of any size");
+        mac.update(payload.as_bytes());
+        mac
+    }
+}
+
+impl<'de> Deserialize<'de> for SignedPromocode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+        struct SignedPromocodeUnsafe {
+            name: String,
+            avantage: Avantage,
+            expires: String,
+            #[serde(default)]
+            restrictions: Restrictions,
+            #[serde(default)]
+            weather_policy: WeatherPolicy,
+        }
+
+        match SignedPromocodeUnsafe::deserialize(deserializer) {
+            Ok(data) => SignedPromocode::new(
+                data.name,
+                Ok(data.avantage),
+                data.expires,
+                data.restrictions.iter().map(|it| Ok(it.clone())).collect(),
+            )
+            .map(|signed_promocode| signed_promocode.with_weather_policy(data.weather_policy))
+            .map_err(Error::custom),
+            Err(err) => Err(Error::custom(err)),
+        }
+    }
+}
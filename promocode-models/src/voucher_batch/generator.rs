@@ -0,0 +1,167 @@
+use crate::promocode::check_digit::CheckDigit;
+use promocode_util::validate_type::{number::BoundedUsize, string::NonBlankString};
+use rand::{seq::SliceRandom, Rng};
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+use std::collections::HashSet;
+
+/// Generator of random promocode names, e.g. `SUMMER-4F7KQ2`.
+///
+/// When `check_digit` is set, a [CheckDigit] computed over the alphabet is
+/// appended to every code, after the `length` random characters.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct CodeGenerator {
+    alphabet: NonBlankString,
+    length: BoundedUsize<1, 64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prefix: Option<NonBlankString>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    check_digit: bool,
+}
+
+impl CodeGenerator {
+    /// Create a new [`CodeGenerator`](Self)
+    ///
+    /// # Errors
+    ///
+    /// This function fails if one of field is not correct.
+    pub fn new(alphabet: String, length: usize, prefix: Option<String>, check_digit: bool) -> Result<Self, String> {
+        let alphabet = match NonBlankString::new(alphabet) {
+            Err(err_alphabet) => return Err(format!("`alphabet` {}", err_alphabet)),
+            Ok(value) => value,
+        };
+
+        let mut alphabet_chars = HashSet::new();
+        for c in alphabet.to_string().chars() {
+            if c.is_whitespace() {
+                return Err("`alphabet` cannot contain whitespaces.".to_string());
+            }
+            if !alphabet_chars.insert(c) {
+                return Err(format!("`alphabet` contains `{}` twice.", c));
+            }
+        }
+        if alphabet_chars.len() < 2 {
+            return Err("`alphabet` must contain at least 2 characters.".to_string());
+        }
+
+        let length = match BoundedUsize::new(length) {
+            Err(err_length) => return Err(format!("`length` is out of bounds: {}", err_length)),
+            Ok(value) => value,
+        };
+
+        let prefix = match prefix.map(NonBlankString::new).transpose() {
+            Err(err_prefix) => return Err(format!("`prefix` {}", err_prefix)),
+            Ok(value) => value,
+        };
+
+        Ok(Self {
+            alphabet,
+            length,
+            prefix,
+            check_digit,
+        })
+    }
+
+    /// Returns the [CheckDigit] appended to the codes, if any.
+    pub fn check_digit(&self) -> Option<CheckDigit> {
+        if self.check_digit {
+            CheckDigit::new(self.alphabet(), vec![], false).ok()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the alphabet as [String] type
+    pub fn alphabet(&self) -> String {
+        self.alphabet.clone().get()
+    }
+
+    /// Returns the number of distinct codes this generator can produce.
+    pub fn combinations(&self) -> u128 {
+        let alphabet_len = self.alphabet.to_string().chars().count() as u128;
+        alphabet_len.saturating_pow(self.length.get() as u32)
+    }
+
+    /// Generates one random code.
+    pub fn generate_one<R: Rng + ?Sized>(&self, rng: &mut R) -> String {
+        let alphabet: Vec<char> = self.alphabet.to_string().chars().collect();
+        let random_part: String = (0..self.length.get())
+            .map(|_| *alphabet.choose(rng).unwrap())
+            .collect();
+
+        let code = match &self.prefix {
+            None => random_part,
+            Some(prefix) => format!("{}{}", prefix, random_part),
+        };
+
+        match self.check_digit() {
+            None => code,
+            Some(check_digit) => format!("{}{}", code, check_digit.compute(&code)),
+        }
+    }
+
+    /// Generates `count` distinct codes accepted by `is_available`.
+    ///
+    /// `is_available` is called once per distinct candidate, and should reject
+    /// the names already in use.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the generator cannot produce `count` codes, or
+    /// if too many candidates were rejected.
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R, count: usize, mut is_available: impl FnMut(&str) -> bool) -> Result<Vec<String>, String> {
+        if self.combinations() < count as u128 {
+            return Err(format!(
+                "Cannot generate {} codes, only {} combinations are available.",
+                count,
+                self.combinations()
+            ));
+        }
+
+        let max_attempts = count.saturating_mul(16).max(1024);
+        let mut candidates = HashSet::with_capacity(count);
+        let mut codes = Vec::with_capacity(count);
+
+        for _ in 0..max_attempts {
+            if codes.len() == count {
+                break;
+            }
+
+            let code = self.generate_one(rng);
+            if candidates.insert(code.clone()) && is_available(&code) {
+                codes.push(code);
+            }
+        }
+
+        if codes.len() < count {
+            return Err(format!(
+                "Only {} unique codes out of {} were generated after {} attempts.",
+                codes.len(),
+                count,
+                max_attempts
+            ));
+        }
+
+        Ok(codes)
+    }
+}
+
+impl<'de> Deserialize<'de> for CodeGenerator {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+        struct CodeGeneratorUnsafe {
+            alphabet: String,
+            length: usize,
+            prefix: Option<String>,
+            #[serde(default)]
+            check_digit: bool,
+        }
+
+        match CodeGeneratorUnsafe::deserialize(deserializer) {
+            Ok(data) => CodeGenerator::new(data.alphabet, data.length, data.prefix, data.check_digit).map_err(Error::custom),
+            Err(err) => Err(Error::custom(err)),
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::promocode::{
+    avantage::Avantage,
+    metadata::Metadata,
+    restriction::Restriction,
+    restrictions::{Restrictions, RestrictionsExt},
+    Promocode,
+};
+use generator::CodeGenerator;
+use promocode_util::validate_type::{number::BoundedUsize, string::NonBlankString};
+use rand::Rng;
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+
+pub mod generator;
+
+/// A batch of single-use promocodes sharing the same [Avantage] and
+/// [Restrictions], linked to a parent campaign.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct VoucherBatch {
+    campaign: NonBlankString,
+    count: BoundedUsize<1, 100_000>,
+    pub generator: CodeGenerator,
+    pub avantage: Avantage,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub restrictions: Restrictions,
+    #[serde(flatten)]
+    pub metadata: Metadata,
+}
+
+impl VoucherBatch {
+    /// Create a new [`VoucherBatch`](Self)
+    ///
+    /// # Errors
+    ///
+    /// This function fails if one of field is not correct.
+    pub fn new(
+        campaign: String,
+        count: usize,
+        generator: Result<CodeGenerator, String>,
+        avantage: Result<Avantage, String>,
+        restrictions: Vec<Result<Restriction, String>>,
+    ) -> Result<Self, String> {
+        let campaign = match NonBlankString::new(campaign) {
+            Err(err_campaign) => return Err(format!("`campaign` {}", err_campaign)),
+            Ok(value) => value,
+        };
+
+        let count = match BoundedUsize::new(count) {
+            Err(err_count) => return Err(format!("`count` is out of bounds: {}", err_count)),
+            Ok(value) => value,
+        };
+
+        let generator = match generator {
+            Err(err) => return Err(format!("`generator` > {}", err)),
+            Ok(value) => value,
+        };
+
+        let avantage = match avantage {
+            Err(err) => return Err(format!("`avantage` > {}", err)),
+            Ok(value) => value,
+        };
+
+        let restrictions = match Restrictions::from_vec(restrictions) {
+            Err(err) => {
+                let err_fmt = err
+                    .lines()
+                    .map(|l| format!("\t{}", l))
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                return Err(format!("`restrictions` > {}", err_fmt));
+            },
+            Ok(value) => value,
+        };
+
+        Ok(Self {
+            campaign,
+            count,
+            generator,
+            avantage,
+            restrictions,
+            metadata: Metadata::default(),
+        })
+    }
+
+    /// Returns this [VoucherBatch] with the given [Metadata], shared by every
+    /// generated [Promocode].
+    ///
+    /// # Errors
+    ///
+    /// This function fails if `metadata` is not correct.
+    pub fn with_metadata(self, metadata: Result<Metadata, String>) -> Result<Self, String> {
+        match metadata {
+            Err(err) => Err(format!("`metadata` > {}", err)),
+            Ok(metadata) => Ok(Self { metadata, ..self }),
+        }
+    }
+
+    /// Returns the campaign as [String] type
+    pub fn campaign(&self) -> String {
+        self.campaign.clone().get()
+    }
+
+    /// Returns the number of codes to generate
+    pub fn count(&self) -> usize {
+        self.count.get()
+    }
+
+    /// Generates the [Promocode]s of this batch.
+    ///
+    /// Every [Promocode] is identified by `<campaign>:<code>`, and its
+    /// [Metadata] is linked to the campaign. `is_available` should reject the
+    /// names already in use (see [CodeGenerator::generate]).
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the codes cannot be generated.
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R, is_available: impl FnMut(&str) -> bool) -> Result<Vec<Promocode>, String> {
+        let metadata = Metadata::new(
+            self.metadata.description(),
+            self.metadata.tags(),
+            self.metadata.owner(),
+            Some(self.campaign()),
+        );
+
+        self.generator
+            .generate(rng, self.count(), is_available)?
+            .into_iter()
+            .map(|code| {
+                Promocode::new(
+                    format!("{}:{}", self.campaign, code),
+                    code,
+                    Ok(self.avantage.clone()),
+                    self.restrictions.iter().map(|it| Ok(it.clone())).collect(),
+                )
+                .and_then(|promocode| promocode.with_metadata(metadata.clone()))
+            })
+            .collect()
+    }
+}
+
+impl<'de> Deserialize<'de> for VoucherBatch {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+        struct VoucherBatchUnsafe {
+            campaign: String,
+            count: usize,
+            generator: CodeGenerator,
+            avantage: Avantage,
+            #[serde(default)]
+            restrictions: Restrictions,
+            #[serde(flatten)]
+            metadata: Metadata,
+        }
+
+        match VoucherBatchUnsafe::deserialize(deserializer) {
+            Ok(data) => VoucherBatch::new(
+                data.campaign,
+                data.count,
+                Ok(data.generator),
+                Ok(data.avantage),
+                data.restrictions.iter().map(|it| Ok(it.clone())).collect(),
+            )
+            .and_then(|voucher_batch| voucher_batch.with_metadata(Ok(data.metadata)))
+            .map_err(Error::custom),
+            Err(err) => Err(Error::custom(err)),
+        }
+    }
+}
+
+/// Names of the [Promocode]s stored for a [VoucherBatch].
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct GeneratedVoucherBatch {
+    pub campaign: String,
+    pub codes: Vec<String>,
+}
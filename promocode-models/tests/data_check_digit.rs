@@ -0,0 +1,135 @@
+use promocode_models::{
+    promocode::{check_digit::CheckDigit, name_policy::NamePolicy},
+    voucher_batch::generator::CodeGenerator,
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+#[test]
+fn check_digit_validation() {
+    assert!(CheckDigit::new("0123456789".to_string(), vec![], false).is_ok());
+    assert!(CheckDigit::new("ABCDEF0123".to_string(), vec!["SUMMER-".to_string()], true).is_ok());
+
+    assert!(CheckDigit::new("".to_string(), vec![], false).is_err());
+    assert!(CheckDigit::new("A".to_string(), vec![], false).is_err());
+    assert!(CheckDigit::new("ABA".to_string(), vec![], false).is_err());
+    assert!(CheckDigit::new("AB".to_string(), vec![" ".to_string()], false).is_err());
+}
+
+#[test]
+fn check_digit_luhn() {
+    let check_digit = CheckDigit::new("0123456789".to_string(), vec![], false).unwrap();
+
+    // Same as the credit card Luhn algorithm with a decimal alphabet.
+    assert_eq!(check_digit.compute("7992739871"), '3');
+    assert!(check_digit.is_valid("79927398713"));
+    assert!(!check_digit.is_valid("79927398710"));
+    assert!(!check_digit.is_valid("79927398731"));
+    assert!(!check_digit.is_valid("7"));
+
+    let check_digit = CheckDigit::new(
+        "ABCDEFGHJKLMNPQRSTUVWXYZ23456789".to_string(),
+        vec![],
+        false,
+    )
+    .unwrap();
+
+    let code = format!("SUMMER-4F7KQ2{}", check_digit.compute("SUMMER-4F7KQ2"));
+
+    assert!(check_digit.is_valid(&code));
+    assert!(!check_digit.is_valid(&code.replace("4F7", "4E7")));
+    assert!(!check_digit.is_valid(&code.replace("4F7", "F47")));
+}
+
+#[test]
+fn check_digit_prefixes() {
+    let check_digit = CheckDigit::new("0123456789".to_string(), vec!["CARD-".to_string()], false).unwrap();
+
+    assert!(check_digit.applies_to("CARD-79927398713"));
+    assert!(!check_digit.applies_to("WEATHERCODE"));
+
+    assert!(check_digit.is_valid("CARD-79927398713"));
+    assert!(!check_digit.is_valid("CARD-79927398710"));
+    assert!(check_digit.is_valid("WEATHERCODE"));
+}
+
+#[test]
+fn check_digit_suggestions() {
+    let check_digit = CheckDigit::new("0123456789".to_string(), vec![], true).unwrap();
+
+    let substitution_suggestions = check_digit.suggestions("79927398710");
+
+    assert!(substitution_suggestions.contains(&"79927398713".to_string()));
+    assert!(substitution_suggestions
+        .iter()
+        .all(|it| check_digit.is_valid(it)));
+
+    let transposition_suggestions = check_digit.suggestions("79927398173");
+
+    assert!(transposition_suggestions.contains(&"79927398713".to_string()));
+}
+
+#[test]
+fn check_digit_suggestions_keep_prefix() {
+    let check_digit = CheckDigit::new(
+        "ABCDEFGHJKLMNPQRSTUVWXYZ23456789".to_string(),
+        vec!["SUMMER-".to_string()],
+        true,
+    )
+    .unwrap();
+
+    let code = format!("SUMMER-4F7KQ2{}", check_digit.compute("SUMMER-4F7KQ2"));
+    let suggestions = check_digit.suggestions(&code.replace("4F7", "4E7"));
+
+    assert!(suggestions.contains(&code));
+    assert!(suggestions.iter().all(|it| it.starts_with("SUMMER-")));
+    assert!(suggestions.len() < 32);
+    assert!(check_digit.suggestions("WINTER-4E7KQ2Y").is_empty());
+}
+
+#[test]
+fn check_digit_generated_codes() {
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let code_generator = CodeGenerator::new(
+        "ABCDEF0123".to_string(),
+        6,
+        Some("SUMMER-".to_string()),
+        true,
+    )
+    .unwrap();
+    let check_digit = code_generator.check_digit().unwrap();
+
+    let codes = code_generator.generate(&mut rng, 100, |_| true).unwrap();
+
+    for code in codes {
+        assert_eq!(code.len(), "SUMMER-".len() + 6 + 1);
+        assert!(check_digit.is_valid(&code));
+    }
+
+    assert!(CodeGenerator::new("ABCDEF0123".to_string(), 6, None, false)
+        .unwrap()
+        .check_digit()
+        .is_none());
+}
+
+#[test]
+fn name_policy_check_digit() {
+    let name_policy_str = r#"{"trim":true,"case_folding":"upper","check_digit":{"alphabet":"0123456789","prefixes":["CARD-"],"suggest":true}}"#;
+
+    let name_policy = serde_json::from_str::<NamePolicy>(name_policy_str).unwrap();
+
+    assert_eq!(
+        name_policy.check_digit(),
+        Some(&CheckDigit::new("0123456789".to_string(), vec!["CARD-".to_string()], true).unwrap())
+    );
+    assert_eq!(
+        name_policy.validate(" card-79927398713"),
+        Ok("CARD-79927398713".to_string())
+    );
+    assert!(name_policy.validate("card-79927398710").is_err());
+    assert_eq!(name_policy.validate("summer20"), Ok("SUMMER20".to_string()));
+    assert_eq!(
+        name_policy.normalize("card-79927398710"),
+        Ok("CARD-79927398710".to_string())
+    );
+}
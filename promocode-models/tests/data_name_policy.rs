@@ -0,0 +1,164 @@
+use promocode_models::promocode::{
+    avantage::Avantage,
+    name_policy::{CaseFolding, NamePolicy},
+    Promocode,
+};
+use std::collections::HashMap;
+
+#[test]
+fn name_policy_validation() {
+    let name_policy_valid = NamePolicy::new(true, CaseFolding::Upper, HashMap::new(), None, 1, 16);
+
+    assert!(name_policy_valid.is_ok());
+
+    let name_policy_with_0_min_length = NamePolicy::new(true, CaseFolding::Upper, HashMap::new(), None, 0, 16);
+
+    assert!(name_policy_with_0_min_length.is_err());
+
+    let name_policy_with_max_length_lower_than_min_length = NamePolicy::new(true, CaseFolding::Upper, HashMap::new(), None, 8, 4);
+
+    assert!(name_policy_with_max_length_lower_than_min_length.is_err());
+
+    let name_policy_with_empty_charset = NamePolicy::new(
+        true,
+        CaseFolding::Upper,
+        HashMap::new(),
+        Some("".to_string()),
+        1,
+        16,
+    );
+
+    assert!(name_policy_with_empty_charset.is_err());
+}
+
+#[test]
+fn name_policy_normalize() {
+    let default_name_policy = NamePolicy::default();
+
+    // Names are kept as given by default.
+    assert_eq!(
+        default_name_policy.normalize("  Summer20\t"),
+        Ok("  Summer20\t".to_string())
+    );
+    assert!(default_name_policy.normalize("").is_err());
+
+    let upper_name_policy = NamePolicy::new(true, CaseFolding::Upper, HashMap::new(), None, 1, 255).unwrap();
+
+    assert_eq!(
+        upper_name_policy.normalize("summer20"),
+        Ok("SUMMER20".to_string())
+    );
+    assert_eq!(
+        upper_name_policy.normalize("  Summer20\t"),
+        Ok("SUMMER20".to_string())
+    );
+    assert!(upper_name_policy.normalize("   ").is_err());
+
+    let preserve_name_policy = NamePolicy::new(false, CaseFolding::Preserve, HashMap::new(), None, 1, 255).unwrap();
+
+    assert_eq!(
+        preserve_name_policy.normalize(" Summer20"),
+        Ok(" Summer20".to_string())
+    );
+
+    let strict_name_policy = NamePolicy::new(
+        true,
+        CaseFolding::Upper,
+        HashMap::from([('O', '0'), ('I', '1')]),
+        Some("ABCDEFGHJKLMNPQRSTUVWXYZ0123456789".to_string()),
+        4,
+        8,
+    )
+    .unwrap();
+
+    assert_eq!(
+        strict_name_policy.normalize("winter10"),
+        Ok("W1NTER10".to_string())
+    );
+    assert_eq!(strict_name_policy.normalize("COOL"), Ok("C00L".to_string()));
+    assert!(strict_name_policy.normalize("summer-20").is_err());
+    assert!(strict_name_policy.normalize("abc").is_err());
+    assert!(strict_name_policy.normalize("summer2024").is_err());
+}
+
+#[test]
+fn name_policy_serde() {
+    let name_policy_str =
+        r#"{"trim":true,"case_folding":"lower","confusables":{"0":"o"},"allowed_charset":"abcdefghijklmnopqrstuvwxyz0123456789","max_length":12}"#;
+
+    let deserialized_result = serde_json::from_str::<NamePolicy>(name_policy_str);
+
+    assert!(deserialized_result.is_ok());
+    let deserialized = deserialized_result.unwrap();
+
+    assert_eq!(
+        deserialized,
+        NamePolicy::new(
+            true,
+            CaseFolding::Lower,
+            HashMap::from([('0', 'o')]),
+            Some("abcdefghijklmnopqrstuvwxyz0123456789".to_string()),
+            1,
+            12,
+        )
+        .unwrap()
+    );
+    assert_eq!(deserialized.normalize("C00L"), Ok("cool".to_string()));
+
+    assert_eq!(
+        serde_json::from_str::<NamePolicy>("{}").unwrap(),
+        NamePolicy::default()
+    );
+    assert!(serde_json::from_str::<NamePolicy>(r#"{"min_length":0}"#).is_err());
+}
+
+#[test]
+fn promocode_normalize_name() {
+    let promocode = Promocode::new(
+        "id".to_string(),
+        " summer20 ".to_string(),
+        Avantage::new(20),
+        vec![],
+    )
+    .unwrap();
+
+    let normalized_promocode = promocode.clone().normalize_name(&NamePolicy::default());
+
+    assert_eq!(
+        normalized_promocode.map(|it| it.name()),
+        Ok(" summer20 ".to_string())
+    );
+
+    let upper_name_policy = NamePolicy::new(true, CaseFolding::Upper, HashMap::new(), None, 1, 255).unwrap();
+    let normalized_promocode = promocode.clone().normalize_name(&upper_name_policy);
+
+    assert!(normalized_promocode.is_ok());
+    assert_eq!(normalized_promocode.unwrap().name(), "SUMMER20".to_string());
+
+    let strict_name_policy = NamePolicy::new(
+        true,
+        CaseFolding::Upper,
+        HashMap::new(),
+        Some("ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_string()),
+        1,
+        8,
+    )
+    .unwrap();
+
+    assert!(promocode.normalize_name(&strict_name_policy).is_err());
+
+    // The prefix of the signed promocodes is reserved, once normalized.
+    let promocode = Promocode::new(
+        "id".to_string(),
+        " sig1.summer20".to_string(),
+        Avantage::new(20),
+        vec![],
+    )
+    .unwrap();
+
+    assert!(promocode
+        .clone()
+        .normalize_name(&NamePolicy::default())
+        .is_ok());
+    assert!(promocode.normalize_name(&upper_name_policy).is_err());
+}
@@ -1,8 +1,15 @@
-use promocode_models::promocode::{
-    avantage::Avantage,
-    restriction::Restriction::{self},
-    temp::Temp,
-    Promocode,
+use promocode_models::{
+    promocode::{
+        avantage::Avantage,
+        comparison::Comparison,
+        forecast_window::{Aggregation, ForecastWindow},
+        meteo_metrics::MeteoMetrics,
+        restriction::Restriction::{self},
+        temp::Temp,
+        weather_policy::WeatherPolicy,
+        Promocode,
+    },
+    promocode_request::{arguments::Arguments, meteo::Meteo},
 };
 
 #[test]
@@ -132,7 +139,7 @@ fn promocode_ser_de() {
 
     let promocode_str = "{\"_id\":\"...\",\"name\":\"WeatherCode\",\"avantage\":{\"percent\":20},\"restrictions\":[{\"@date\":{\"after\":\"2019-01-01\",\"before\":\"2020-06-30\"}},{\"@or\":[{\"@age\":{\"eq\":40}},{\"@and\":[{\"@age\":{\"lt\":30,\"gt\":15}},{\"@meteo\":{\"is\":\"clear\",\"temp\":{\"gt\":15}}}]}]}]}";
 
-    let deserialized_result = serde_json::from_str::<Promocode>(&promocode_str);
+    let deserialized_result = serde_json::from_str::<Promocode>(promocode_str);
 
     assert!(deserialized_result.is_ok());
     let deserialized = deserialized_result.unwrap();
@@ -140,3 +147,137 @@ fn promocode_ser_de() {
     assert_eq!(promocode, deserialized);
     assert_eq!(serialized, promocode_str);
 }
+
+#[test]
+fn promocode_weather_policy() {
+    let promocode = Promocode::new(
+        "...".to_string(),
+        "WeatherCode".to_string(),
+        Avantage::new(20),
+        vec![Restriction::and(vec![
+            Restriction::age(None, None, Some(18)),
+            Restriction::meteo("clear".to_string(), Temp { gt: 15 }),
+        ])],
+    )
+    .unwrap();
+    let adult = Arguments::new(25, Meteo::new("Lyon".to_string())).unwrap();
+    let child = Arguments::new(12, Meteo::new("Lyon".to_string())).unwrap();
+
+    // Fail closed by default, and not serialized.
+    assert_eq!(promocode.weather_policy, WeatherPolicy::FailClosed);
+    assert!(!promocode.check_weather_unavailable(&adult));
+    assert!(!serde_json::to_string(&promocode)
+        .unwrap()
+        .contains("weather_policy"));
+
+    let promocode = promocode.with_weather_policy(WeatherPolicy::FailOpen);
+    assert!(promocode.check_weather_unavailable(&adult));
+    assert!(!promocode.check_weather_unavailable(&child));
+
+    let serialized = serde_json::to_string(&promocode).unwrap();
+    assert!(serialized.contains("\"weather_policy\":\"fail_open\""));
+    assert_eq!(
+        serde_json::from_str::<Promocode>(&serialized).unwrap(),
+        promocode
+    );
+
+    let unknown_policy = serialized.replace("fail_open", "sometimes");
+    assert!(serde_json::from_str::<Promocode>(&unknown_policy).is_err());
+}
+
+#[test]
+fn promocode_meteo_forecast() {
+    let window = ForecastWindow::new(6, Aggregation::Any, Aggregation::Max);
+    let restriction = Restriction::meteo_forecast("rain".to_string(), Temp { gt: 15 }, window.clone()).unwrap();
+
+    let serialized = serde_json::to_string(&restriction).unwrap();
+    assert_eq!(
+        serialized,
+        r#"{"@meteo":{"is":"rain","temp":{"gt":15},"forecast":{"hours":6,"is":"any","temp":"max"}}}"#
+    );
+    assert_eq!(
+        serde_json::from_str::<Restriction>(&serialized).unwrap(),
+        restriction
+    );
+
+    // The aggregations default to `any` and `max`.
+    assert_eq!(
+        serde_json::from_str::<Restriction>(r#"{"@meteo":{"is":"rain","temp":{"gt":15},"forecast":{"hours":6}}}"#).unwrap(),
+        restriction
+    );
+
+    // Without a window, the current weather is checked.
+    assert!(
+        !serde_json::to_string(&Restriction::meteo("rain".to_string(), Temp { gt: 15 }).unwrap())
+            .unwrap()
+            .contains("forecast")
+    );
+
+    assert!(ForecastWindow::new(0, Aggregation::Any, Aggregation::Max).is_err());
+    assert!(ForecastWindow::new(121, Aggregation::Any, Aggregation::Max).is_err());
+    assert!(ForecastWindow::new(6, Aggregation::Min, Aggregation::Max).is_err());
+    assert!(ForecastWindow::new(6, Aggregation::Any, Aggregation::All).is_err());
+    assert!(Restriction::meteo_forecast(
+        "rain".to_string(),
+        Temp { gt: 15 },
+        ForecastWindow::new(0, Aggregation::Any, Aggregation::Max)
+    )
+    .is_err());
+    for invalid in [
+        r#"{"@meteo":{"is":"rain","temp":{"gt":15},"forecast":{"hours":0}}}"#,
+        r#"{"@meteo":{"is":"rain","temp":{"gt":15},"forecast":{"hours":6,"is":"max"}}}"#,
+        r#"{"@meteo":{"is":"rain","temp":{"gt":15},"forecast":{"hours":6,"temp":"all"}}}"#,
+        r#"{"@meteo":{"is":"rain","temp":{"gt":15},"forecast":{"hours":6,"temp":"median"}}}"#,
+    ] {
+        assert!(
+            serde_json::from_str::<Restriction>(invalid).is_err(),
+            "{}",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn promocode_meteo_metrics() {
+    let metrics = MeteoMetrics::new(
+        Some(Comparison::new(None, None, Some(18.0)).unwrap()),
+        Some(Comparison::new(Some(60.0), None, None).unwrap()),
+        None,
+        Some(Comparison::new(None, Some(0.0), None).unwrap()),
+    );
+    let restriction = Restriction::meteo_metrics("clear".to_string(), Temp { gt: 15 }, metrics).unwrap();
+
+    let serialized = serde_json::to_string(&restriction).unwrap();
+    assert_eq!(
+        serialized,
+        r#"{"@meteo":{"is":"clear","temp":{"gt":15},"feels_like":{"gt":18.0},"humidity":{"lt":60.0},"rain":{"eq":0.0}}}"#
+    );
+    assert_eq!(
+        serde_json::from_str::<Restriction>(&serialized).unwrap(),
+        restriction
+    );
+    assert_eq!(
+        serde_json::from_str::<Restriction>(r#"{"@meteo":{"is":"clear","temp":{"gt":15},"feels_like":{"gt":18},"humidity":{"lt":60},"rain":{"eq":0}}}"#)
+            .unwrap(),
+        restriction
+    );
+
+    assert!(Comparison::new(None, None, None).is_err());
+    assert!(Comparison::new(Some(10.0), None, Some(20.0)).is_err());
+    assert!(Comparison::new(Some(10.0), Some(5.0), None).is_err());
+    assert!(Comparison::new(None, Some(f64::NAN), None).is_err());
+    for invalid in [
+        r#"{"@meteo":{"is":"clear","temp":{"gt":15},"humidity":{}}}"#,
+        r#"{"@meteo":{"is":"clear","temp":{"gt":15},"humidity":{"lt":120}}}"#,
+        r#"{"@meteo":{"is":"clear","temp":{"gt":15},"wind":{"gt":-1}}}"#,
+        r#"{"@meteo":{"is":"clear","temp":{"gt":15},"rain":{"lt":2,"gt":5}}}"#,
+        r#"{"@meteo":{"is":"clear","temp":{"gt":15},"feels_like":{"gt":"warm"}}}"#,
+        r#"{"@meteo":{"is":"clear","temp":{"gt":15},"humdity":{"gt":80}}}"#,
+    ] {
+        assert!(
+            serde_json::from_str::<Restriction>(invalid).is_err(),
+            "{}",
+            invalid
+        );
+    }
+}
@@ -24,7 +24,7 @@ fn promocode_denied_serde() {
 
     let promocode_str = r#"{"promocode_name":"WeatherCode","status":"denied","reasons":{}}"#;
 
-    let deserialized_result = serde_json::from_str::<PromocodeResponse>(&promocode_str);
+    let deserialized_result = serde_json::from_str::<PromocodeResponse>(promocode_str);
 
     assert!(deserialized_result.is_ok());
     let deserialized = deserialized_result.unwrap();
@@ -0,0 +1,56 @@
+use promocode_models::promocode_response::PromocodeResponse;
+
+#[test]
+fn promocode_malformed_validation() {
+    let promocode_malformed_valid = PromocodeResponse::malformed("promocode_name".to_string(), None);
+
+    assert!(promocode_malformed_valid.is_ok());
+
+    let promocode_malformed_with_suggestion = PromocodeResponse::malformed(
+        "promocode_name".to_string(),
+        Some("promocode_nane".to_string()),
+    );
+
+    assert!(promocode_malformed_with_suggestion.is_ok());
+
+    let promocode_malformed_with_empty_promocode_name = PromocodeResponse::malformed("".to_string(), None);
+
+    assert!(promocode_malformed_with_empty_promocode_name.is_err());
+
+    let promocode_malformed_with_blank_suggestion = PromocodeResponse::malformed("promocode_name".to_string(), Some(" ".to_string()));
+
+    assert!(promocode_malformed_with_blank_suggestion.is_err());
+}
+
+#[test]
+fn promocode_malformed_serde() {
+    let promocode_malformed_valid = PromocodeResponse::malformed(
+        "SUMMER-4F7KQ2X".to_string(),
+        Some("SUMMER-4F7KQ2B".to_string()),
+    );
+
+    assert!(promocode_malformed_valid.is_ok());
+    let promocode_malformed = promocode_malformed_valid.unwrap();
+    let serialized_result = serde_json::to_string(&promocode_malformed);
+
+    assert!(serialized_result.is_ok());
+    let serialized = serialized_result.unwrap();
+
+    let promocode_str = r#"{"promocode_name":"SUMMER-4F7KQ2X","status":"malformed","suggestion":"SUMMER-4F7KQ2B"}"#;
+
+    let deserialized_result = serde_json::from_str::<PromocodeResponse>(promocode_str);
+
+    assert!(deserialized_result.is_ok());
+    let deserialized = deserialized_result.unwrap();
+
+    assert_eq!(promocode_malformed, deserialized);
+    assert_eq!(serialized, promocode_str);
+
+    let promocode_without_suggestion_str = r#"{"promocode_name":"SUMMER-4F7KQ2X","status":"malformed"}"#;
+
+    assert!(serde_json::from_str::<PromocodeResponse>(promocode_without_suggestion_str).is_ok());
+
+    let promocode_denied_with_suggestion_str = r#"{"promocode_name":"SUMMER-4F7KQ2X","status":"denied","reasons":{},"suggestion":"SUMMER-4F7KQ2B"}"#;
+
+    assert!(serde_json::from_str::<PromocodeResponse>(promocode_denied_with_suggestion_str).is_err());
+}
@@ -0,0 +1,174 @@
+use chrono::{TimeZone, Utc};
+use promocode_models::promocode::{avantage::Avantage, metadata::Metadata, Promocode};
+
+#[test]
+fn promocode_metadata_validation() {
+    let metadata_valid = Metadata::new(
+        Some("Summer campaign".to_string()),
+        vec!["summer".to_string(), "newsletter".to_string()],
+        Some("marketing".to_string()),
+        None,
+    );
+
+    assert!(metadata_valid.is_ok());
+
+    let metadata_empty = Metadata::new(None, vec![], None, None);
+
+    assert_eq!(metadata_empty, Ok(Metadata::default()));
+
+    let metadata_with_blank_description = Metadata::new(Some(" ".to_string()), vec![], None, None);
+
+    assert!(metadata_with_blank_description.is_err());
+
+    let metadata_with_blank_tag = Metadata::new(None, vec!["summer".to_string(), "".to_string()], None, None);
+
+    assert!(metadata_with_blank_tag.is_err());
+
+    let metadata_with_duplicated_tag = Metadata::new(
+        None,
+        vec!["summer".to_string(), "summer".to_string()],
+        None,
+        None,
+    );
+
+    assert!(metadata_with_duplicated_tag.is_err());
+
+    let metadata_with_blank_owner = Metadata::new(None, vec![], Some("".to_string()), None);
+
+    assert!(metadata_with_blank_owner.is_err());
+
+    let metadata_with_blank_campaign = Metadata::new(None, vec![], None, Some(" ".to_string()));
+
+    assert!(metadata_with_blank_campaign.is_err());
+}
+
+#[test]
+fn promocode_metadata_tags() {
+    let metadata = Metadata::new(
+        None,
+        vec!["summer".to_string(), "newsletter".to_string()],
+        None,
+        None,
+    )
+    .unwrap();
+
+    assert!(metadata.has_tags(&[]));
+    assert!(metadata.has_tags(&["summer".to_string()]));
+    assert!(metadata.has_tags(&["newsletter".to_string(), "summer".to_string()]));
+    assert!(!metadata.has_tags(&["winter".to_string()]));
+    assert!(!metadata.has_tags(&["summer".to_string(), "winter".to_string()]));
+}
+
+#[test]
+fn promocode_metadata_timestamps() {
+    let created_at = Utc.with_ymd_and_hms(2024, 6, 1, 8, 0, 0).unwrap();
+    let updated_at = Utc.with_ymd_and_hms(2024, 6, 2, 8, 0, 0).unwrap();
+
+    let mut metadata = Metadata::default();
+
+    assert!(metadata.created_at().is_none());
+    assert!(metadata.updated_at().is_none());
+
+    metadata.mark_created(created_at);
+
+    assert_eq!(metadata.created_at(), Some(created_at));
+    assert_eq!(metadata.updated_at(), Some(created_at));
+
+    metadata.mark_updated(updated_at);
+
+    assert_eq!(metadata.created_at(), Some(created_at));
+    assert_eq!(metadata.updated_at(), Some(updated_at));
+}
+
+#[test]
+fn promocode_metadata_version() {
+    let now = Utc.with_ymd_and_hms(2024, 6, 1, 8, 0, 0).unwrap();
+
+    let mut stored = Metadata::default();
+    assert_eq!(stored.version(), 0);
+
+    stored.mark_created(now);
+    assert_eq!(stored.version(), 1);
+    stored.mark_updated(now);
+    assert_eq!(stored.version(), 2);
+
+    // Whatever the client sent, the replacement follows the stored version.
+    let mut replacement: Metadata = serde_json::from_str(r#"{"tags":["summer"],"version":42}"#).unwrap();
+    assert_eq!(replacement.version(), 42);
+    replacement.inherit(&stored);
+    replacement.mark_updated(now);
+    assert_eq!(replacement.version(), 3);
+    assert_eq!(replacement.created_at(), Some(now));
+    assert_eq!(replacement.tags(), vec!["summer".to_string()]);
+
+    assert_eq!(
+        serde_json::to_string(&replacement).unwrap(),
+        r#"{"tags":["summer"],"created_at":"2024-06-01T08:00:00Z","updated_at":"2024-06-01T08:00:00Z","version":3}"#
+    );
+    assert_eq!(serde_json::to_string(&Metadata::default()).unwrap(), "{}");
+}
+
+#[test]
+fn promocode_same_content() {
+    let stored = Promocode::new(
+        "id".to_string(),
+        "name".to_string(),
+        Avantage::new(20),
+        vec![],
+    )
+    .unwrap();
+    let mut same = stored.clone();
+    same.metadata
+        .mark_created(Utc.with_ymd_and_hms(2024, 6, 1, 8, 0, 0).unwrap());
+
+    assert!(stored.same_content(&same));
+    assert!(!stored.same_content(
+        &Promocode::new(
+            "id".to_string(),
+            "name".to_string(),
+            Avantage::new(10),
+            vec![]
+        )
+        .unwrap()
+    ));
+}
+
+#[test]
+fn promocode_metadata_ser_de() {
+    let mut promocode = Promocode::new(
+        "id".to_string(),
+        "name".to_string(),
+        Avantage::new(20),
+        vec![],
+    )
+    .unwrap()
+    .with_metadata(Metadata::new(
+        Some("Summer campaign".to_string()),
+        vec!["summer".to_string()],
+        Some("marketing".to_string()),
+        None,
+    ))
+    .unwrap();
+    promocode
+        .metadata
+        .mark_created(Utc.with_ymd_and_hms(2024, 6, 1, 8, 0, 0).unwrap());
+
+    let serialized_result = serde_json::to_string(&promocode);
+
+    assert!(serialized_result.is_ok());
+    let serialized = serialized_result.unwrap();
+
+    let promocode_str = r#"{"_id":"id","name":"name","avantage":{"percent":20},"description":"Summer campaign","tags":["summer"],"owner":"marketing","created_at":"2024-06-01T08:00:00Z","updated_at":"2024-06-01T08:00:00Z","version":1}"#;
+
+    let deserialized_result = serde_json::from_str::<Promocode>(promocode_str);
+
+    assert!(deserialized_result.is_ok());
+    let deserialized = deserialized_result.unwrap();
+
+    assert_eq!(promocode, deserialized);
+    assert_eq!(serialized, promocode_str);
+
+    let promocode_with_blank_tag_str = r#"{"_id":"id","name":"name","avantage":{"percent":20},"tags":[" "]}"#;
+
+    assert!(serde_json::from_str::<Promocode>(promocode_with_blank_tag_str).is_err());
+}
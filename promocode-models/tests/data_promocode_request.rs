@@ -1,4 +1,12 @@
-use promocode_models::promocode_request::{arguments::Arguments, meteo::Meteo, PromocodeRequest};
+use promocode_models::{
+    promocode::{
+        check_digit::CheckDigit,
+        name_policy::{CaseFolding, NamePolicy},
+    },
+    promocode_request::{arguments::Arguments, meteo::Meteo, MalformedName, PromocodeRequest, PromocodeRequestSeed},
+};
+use serde::de::DeserializeSeed;
+use std::collections::HashMap;
 
 #[test]
 fn promocode_request_validation() {
@@ -33,7 +41,7 @@ fn promocode_request_serde() {
 
     let promocode_str = r#"{"promocode_name":"WeatherCode","arguments":{"age":25,"meteo":{"town":"Lyon"}}}"#;
 
-    let deserialized_result = serde_json::from_str::<PromocodeRequest>(&promocode_str);
+    let deserialized_result = serde_json::from_str::<PromocodeRequest>(promocode_str);
 
     assert!(deserialized_result.is_ok());
     let deserialized = deserialized_result.unwrap();
@@ -41,3 +49,35 @@ fn promocode_request_serde() {
     assert_eq!(promocode_request, deserialized);
     assert_eq!(serialized, promocode_str);
 }
+
+#[test]
+fn promocode_request_name_policy() {
+    let policy = NamePolicy::new(true, CaseFolding::Upper, HashMap::new(), None, 1, 255)
+        .unwrap()
+        .with_check_digit(Some(
+            CheckDigit::new("0123456789".to_string(), vec!["CARD-".to_string()], false).unwrap(),
+        ));
+    let read = |promocode_str: &str| PromocodeRequestSeed::new(&policy).deserialize(&mut serde_json::Deserializer::from_str(promocode_str));
+
+    let promocode_request = read(r#"{"promocode_name":" card-79927398713","arguments":{"age":25,"meteo":{"town":"Lyon"}}}"#)
+        .unwrap()
+        .unwrap();
+    assert_eq!(promocode_request.promocode_name(), " card-79927398713");
+    assert_eq!(promocode_request.normalized_name(), "CARD-79927398713");
+
+    // Without a check digit to verify.
+    let promocode_request = read(r#"{"promocode_name":"weathercode","arguments":{"age":25,"meteo":{"town":"Lyon"}}}"#)
+        .unwrap()
+        .unwrap();
+    assert_eq!(promocode_request.normalized_name(), "WEATHERCODE");
+
+    // A typo is a malformed name, not a deserialization error.
+    assert_eq!(
+        read(r#"{"promocode_name":"card-79927398710","arguments":{"age":25,"meteo":{"town":"Lyon"}}}"#).unwrap(),
+        Err(MalformedName {
+            promocode_name: "card-79927398710".to_string(),
+            reason: "has an invalid check digit".to_string(),
+        })
+    );
+    assert!(read(r#"{"promocode_name":" ","arguments":{"age":25,"meteo":{"town":"Lyon"}}}"#).is_err());
+}
@@ -0,0 +1,71 @@
+use chrono::NaiveDate;
+use promocode_models::promocode::{
+    avantage::Avantage,
+    restriction::{Restriction, RestrictionKind},
+    status::PromocodeStatus,
+    Promocode,
+};
+
+fn day(day: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(day, "%Y-%m-%d").unwrap()
+}
+
+fn date(after: &str, before: &str) -> Result<Restriction, String> {
+    Restriction::date(after.to_string(), before.to_string())
+}
+
+fn promocode(restrictions: Vec<Result<Restriction, String>>) -> Promocode {
+    Promocode::new(
+        "id".to_string(),
+        "name".to_string(),
+        Avantage::new(10),
+        restrictions,
+    )
+    .unwrap()
+}
+
+#[test]
+fn promocode_status() {
+    let summer = promocode(vec![date("2024-06-01", "2024-08-31")]);
+
+    assert_eq!(summer.status(day("2024-05-31")), PromocodeStatus::Upcoming);
+    assert_eq!(summer.status(day("2024-06-01")), PromocodeStatus::Active);
+    assert_eq!(summer.status(day("2024-08-31")), PromocodeStatus::Active);
+    assert_eq!(summer.status(day("2024-09-01")), PromocodeStatus::Expired);
+
+    // Between two windows.
+    let twice = promocode(vec![
+        date("2024-01-01", "2024-01-31"),
+        date("2024-12-01", "2024-12-31"),
+    ]);
+    assert_eq!(twice.status(day("2024-06-01")), PromocodeStatus::Upcoming);
+    assert!(!twice.accepts_date(day("2024-06-01")));
+    assert!(twice.accepts_date(day("2024-12-24")));
+
+    // Windows that never overlap.
+    let never = promocode(vec![Restriction::and(vec![
+        date("2024-01-01", "2024-01-31"),
+        date("2024-12-01", "2024-12-31"),
+    ])]);
+    assert_eq!(never.status(day("2023-06-01")), PromocodeStatus::Expired);
+
+    // Restrictions other than dates do not limit the days.
+    let adults = promocode(vec![Restriction::age(None, None, Some(18))]);
+    assert_eq!(adults.status(day("2024-06-01")), PromocodeStatus::Active);
+    assert_eq!(
+        promocode(vec![]).status(day("2024-06-01")),
+        PromocodeStatus::Active
+    );
+}
+
+#[test]
+fn promocode_restriction_kind() {
+    let promocode = promocode(vec![Restriction::or(vec![
+        Restriction::age(Some(30), None, None),
+        Restriction::and(vec![date("2024-06-01", "2024-08-31")]),
+    ])]);
+
+    assert!(promocode.has_restriction_kind(RestrictionKind::Age));
+    assert!(promocode.has_restriction_kind(RestrictionKind::Date));
+    assert!(!promocode.has_restriction_kind(RestrictionKind::Meteo));
+}
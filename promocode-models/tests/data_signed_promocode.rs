@@ -0,0 +1,191 @@
+use chrono::NaiveDate;
+use promocode_models::{
+    promocode::{avantage::Avantage, restriction::Restriction, temp::Temp, weather_policy::WeatherPolicy},
+    promocode_request::{arguments::Arguments, meteo::Meteo},
+    signed_promocode::SignedPromocode,
+};
+
+const KEY: &[u8] = b"secret";
+
+fn signed_promocode() -> SignedPromocode {
+    SignedPromocode::new(
+        "PARTNER-ACME".to_string(),
+        Avantage::new(10),
+        "2024-12-31".to_string(),
+        vec![Restriction::age(None, None, Some(18))],
+    )
+    .unwrap()
+}
+
+fn arguments(age: u8) -> Arguments {
+    Arguments::new(age, Meteo::new("Lyon".to_string())).unwrap()
+}
+
+fn date(date: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn signed_promocode_validation() {
+    assert!(SignedPromocode::new(
+        " ".to_string(),
+        Avantage::new(10),
+        "2024-12-31".to_string(),
+        vec![]
+    )
+    .is_err());
+    assert!(SignedPromocode::new(
+        "ACME".to_string(),
+        Avantage::new(101),
+        "2024-12-31".to_string(),
+        vec![]
+    )
+    .is_err());
+    assert!(SignedPromocode::new(
+        "ACME".to_string(),
+        Avantage::new(10),
+        "31/12/2024".to_string(),
+        vec![]
+    )
+    .is_err());
+
+    let signed_promocode: SignedPromocode = serde_json::from_str(
+        r#"{
+            "name": "PARTNER-ACME",
+            "avantage": { "percent": 10 },
+            "expires": "2024-12-31",
+            "restrictions": [ { "@age": { "gt": 18 } } ]
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(signed_promocode, self::signed_promocode());
+}
+
+#[test]
+fn signed_promocode_round_trip() {
+    let code = signed_promocode().encode(KEY);
+
+    assert!(SignedPromocode::is_signed_code(&code));
+    assert!(!SignedPromocode::is_signed_code("PARTNER-ACME"));
+    assert_eq!(SignedPromocode::decode(&code, KEY), Ok(signed_promocode()));
+}
+
+#[test]
+fn signed_promocode_tampered() {
+    let code = signed_promocode().encode(KEY);
+    let (payload, signature) = code
+        .strip_prefix(SignedPromocode::PREFIX)
+        .unwrap()
+        .split_once('.')
+        .unwrap();
+
+    let other = SignedPromocode::new(
+        "PARTNER-ACME".to_string(),
+        Avantage::new(90),
+        "2024-12-31".to_string(),
+        vec![],
+    )
+    .unwrap()
+    .encode(KEY);
+    let (other_payload, other_signature) = other
+        .strip_prefix(SignedPromocode::PREFIX)
+        .unwrap()
+        .split_once('.')
+        .unwrap();
+
+    // Payload of another code with the original signature, and vice versa.
+    assert!(SignedPromocode::decode(
+        &format!("{}{}.{}", SignedPromocode::PREFIX, other_payload, signature),
+        KEY
+    )
+    .is_err());
+    assert!(SignedPromocode::decode(
+        &format!("{}{}.{}", SignedPromocode::PREFIX, payload, other_signature),
+        KEY
+    )
+    .is_err());
+
+    assert!(SignedPromocode::decode(&code, b"other secret").is_err());
+    assert!(SignedPromocode::decode(&code.to_uppercase(), KEY).is_err());
+    assert!(SignedPromocode::decode("SIG1.", KEY).is_err());
+    assert!(SignedPromocode::decode("SIG1.payload", KEY).is_err());
+    assert!(SignedPromocode::decode("PARTNER-ACME", KEY).is_err());
+}
+
+#[test]
+fn signed_promocode_check() {
+    let signed_promocode = signed_promocode();
+
+    assert!(signed_promocode.check(arguments(25), None, date("2024-06-01")));
+    assert!(signed_promocode.check(arguments(25), None, date("2024-12-31")));
+    assert!(!signed_promocode.check(arguments(25), None, date("2025-01-01")));
+    assert!(!signed_promocode.check(arguments(16), None, date("2024-06-01")));
+
+    let without_restrictions = SignedPromocode::new(
+        "PARTNER-ACME".to_string(),
+        Avantage::new(10),
+        "2024-12-31".to_string(),
+        vec![],
+    )
+    .unwrap();
+
+    assert!(without_restrictions.check(arguments(16), None, date("2024-06-01")));
+    assert!(!without_restrictions.check(arguments(16), None, date("2025-01-01")));
+}
+
+#[test]
+fn signed_promocode_check_without_weather() {
+    let signed_promocode = signed_promocode();
+
+    assert_eq!(
+        signed_promocode.check_without_weather(&arguments(25), date("2024-06-01")),
+        Some(true)
+    );
+    assert_eq!(
+        signed_promocode.check_without_weather(&arguments(25), date("2025-01-01")),
+        Some(false)
+    );
+    assert_eq!(
+        signed_promocode.check_without_weather(&arguments(16), date("2024-06-01")),
+        Some(false)
+    );
+
+    let with_meteo = SignedPromocode::new(
+        "PARTNER-ACME".to_string(),
+        Avantage::new(10),
+        "2024-12-31".to_string(),
+        vec![Restriction::meteo("clear".to_string(), Temp { gt: 15 })],
+    )
+    .unwrap();
+
+    assert_eq!(
+        with_meteo.check_without_weather(&arguments(25), date("2024-06-01")),
+        None
+    );
+    assert_eq!(
+        with_meteo.check_without_weather(&arguments(25), date("2025-01-01")),
+        Some(false)
+    );
+}
+
+#[test]
+fn signed_promocode_weather_policy() {
+    let with_meteo = SignedPromocode::new(
+        "PARTNER-ACME".to_string(),
+        Avantage::new(10),
+        "2024-12-31".to_string(),
+        vec![Restriction::meteo("clear".to_string(), Temp { gt: 15 })],
+    )
+    .unwrap();
+
+    assert!(!with_meteo.check_weather_unavailable(&arguments(25), date("2024-06-01")));
+
+    let with_meteo = with_meteo.with_weather_policy(WeatherPolicy::FailOpen);
+    assert!(with_meteo.check_weather_unavailable(&arguments(25), date("2024-06-01")));
+    assert!(!with_meteo.check_weather_unavailable(&arguments(25), date("2025-01-01")));
+
+    // The policy is signed along with the code.
+    let code = with_meteo.encode(KEY);
+    assert_eq!(SignedPromocode::decode(&code, KEY), Ok(with_meteo));
+}
@@ -0,0 +1,206 @@
+use promocode_models::{
+    promocode::{avantage::Avantage, metadata::Metadata, restriction::Restriction},
+    voucher_batch::{generator::CodeGenerator, VoucherBatch},
+};
+use rand::{rngs::StdRng, SeedableRng};
+use std::collections::HashSet;
+
+#[test]
+fn code_generator_validation() {
+    assert!(CodeGenerator::new(
+        "ABCDEF0123".to_string(),
+        8,
+        Some("SUMMER-".to_string()),
+        false
+    )
+    .is_ok());
+    assert!(CodeGenerator::new("AB".to_string(), 1, None, false).is_ok());
+
+    assert!(CodeGenerator::new("".to_string(), 8, None, false).is_err());
+    assert!(CodeGenerator::new("A".to_string(), 8, None, false).is_err());
+    assert!(CodeGenerator::new("ABA".to_string(), 8, None, false).is_err());
+    assert!(CodeGenerator::new("A B".to_string(), 8, None, false).is_err());
+    assert!(CodeGenerator::new("AB".to_string(), 0, None, false).is_err());
+    assert!(CodeGenerator::new("AB".to_string(), 65, None, false).is_err());
+    assert!(CodeGenerator::new("AB".to_string(), 8, Some(" ".to_string()), false).is_err());
+}
+
+#[test]
+fn code_generator_generate() {
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let code_generator = CodeGenerator::new(
+        "ABCDEF0123".to_string(),
+        6,
+        Some("SUMMER-".to_string()),
+        false,
+    )
+    .unwrap();
+
+    assert_eq!(code_generator.combinations(), 1_000_000);
+
+    let code = code_generator.generate_one(&mut rng);
+
+    assert!(code.starts_with("SUMMER-"));
+    assert_eq!(code.len(), "SUMMER-".len() + 6);
+    assert!(code["SUMMER-".len()..]
+        .chars()
+        .all(|c| "ABCDEF0123".contains(c)));
+
+    let codes = code_generator.generate(&mut rng, 1000, |_| true).unwrap();
+
+    assert_eq!(codes.len(), 1000);
+    assert_eq!(codes.iter().collect::<HashSet<_>>().len(), 1000);
+
+    let small_code_generator = CodeGenerator::new("AB".to_string(), 2, None, false).unwrap();
+
+    let all_codes = small_code_generator
+        .generate(&mut rng, 4, |_| true)
+        .unwrap();
+
+    assert_eq!(
+        all_codes.into_iter().collect::<HashSet<_>>(),
+        HashSet::from([
+            "AA".to_string(),
+            "AB".to_string(),
+            "BA".to_string(),
+            "BB".to_string()
+        ])
+    );
+
+    assert!(small_code_generator
+        .generate(&mut rng, 5, |_| true)
+        .is_err());
+
+    let available_codes = small_code_generator
+        .generate(&mut rng, 2, |code| code.starts_with('A'))
+        .unwrap();
+
+    assert_eq!(
+        available_codes.into_iter().collect::<HashSet<_>>(),
+        HashSet::from(["AA".to_string(), "AB".to_string()])
+    );
+
+    assert!(small_code_generator
+        .generate(&mut rng, 3, |code| code.starts_with('A'))
+        .is_err());
+}
+
+#[test]
+fn voucher_batch_validation() {
+    let voucher_batch_valid = VoucherBatch::new(
+        "summer-mailing".to_string(),
+        100,
+        CodeGenerator::new("ABCDEF0123".to_string(), 8, None, false),
+        Avantage::new(10),
+        vec![Restriction::age(None, None, Some(18))],
+    );
+
+    assert!(voucher_batch_valid.is_ok());
+
+    let voucher_batch_with_empty_campaign = VoucherBatch::new(
+        "".to_string(),
+        100,
+        CodeGenerator::new("ABCDEF0123".to_string(), 8, None, false),
+        Avantage::new(10),
+        vec![],
+    );
+
+    assert!(voucher_batch_with_empty_campaign.is_err());
+
+    let voucher_batch_with_0_count = VoucherBatch::new(
+        "summer-mailing".to_string(),
+        0,
+        CodeGenerator::new("ABCDEF0123".to_string(), 8, None, false),
+        Avantage::new(10),
+        vec![],
+    );
+
+    assert!(voucher_batch_with_0_count.is_err());
+
+    let voucher_batch_with_bad_generator = VoucherBatch::new(
+        "summer-mailing".to_string(),
+        100,
+        CodeGenerator::new("A".to_string(), 8, None, false),
+        Avantage::new(10),
+        vec![],
+    );
+
+    assert!(voucher_batch_with_bad_generator.is_err());
+}
+
+#[test]
+fn voucher_batch_generate() {
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let voucher_batch = VoucherBatch::new(
+        "summer-mailing".to_string(),
+        50,
+        CodeGenerator::new(
+            "ABCDEF0123".to_string(),
+            8,
+            Some("SUMMER-".to_string()),
+            false,
+        ),
+        Avantage::new(10),
+        vec![Restriction::age(None, None, Some(18))],
+    )
+    .unwrap()
+    .with_metadata(Metadata::new(
+        None,
+        vec!["mailing".to_string()],
+        Some("marketing".to_string()),
+        None,
+    ))
+    .unwrap();
+
+    let promocodes = voucher_batch.generate(&mut rng, |_| true).unwrap();
+
+    assert_eq!(promocodes.len(), 50);
+    assert_eq!(
+        promocodes
+            .iter()
+            .map(|it| it.name())
+            .collect::<HashSet<_>>()
+            .len(),
+        50
+    );
+    for promocode in promocodes {
+        assert!(promocode.name().starts_with("SUMMER-"));
+        assert_eq!(
+            promocode._id(),
+            format!("summer-mailing:{}", promocode.name())
+        );
+        assert_eq!(promocode.avantage, voucher_batch.avantage);
+        assert_eq!(promocode.restrictions, voucher_batch.restrictions);
+        assert_eq!(
+            promocode.metadata.campaign(),
+            Some("summer-mailing".to_string())
+        );
+        assert_eq!(promocode.metadata.tags(), vec!["mailing".to_string()]);
+        assert_eq!(promocode.metadata.owner(), Some("marketing".to_string()));
+    }
+}
+
+#[test]
+fn voucher_batch_serde() {
+    let voucher_batch_str = r#"{"campaign":"summer-mailing","count":100,"generator":{"alphabet":"ABCDEF0123","length":8,"prefix":"SUMMER-"},"avantage":{"percent":10},"tags":["mailing"]}"#;
+
+    let deserialized_result = serde_json::from_str::<VoucherBatch>(voucher_batch_str);
+
+    assert!(deserialized_result.is_ok());
+    let deserialized = deserialized_result.unwrap();
+
+    assert_eq!(deserialized.campaign(), "summer-mailing".to_string());
+    assert_eq!(deserialized.count(), 100);
+    assert_eq!(deserialized.metadata.tags(), vec!["mailing".to_string()]);
+    assert_eq!(
+        serde_json::to_string(&deserialized).unwrap(),
+        voucher_batch_str
+    );
+
+    let voucher_batch_with_too_many_codes_str =
+        r#"{"campaign":"summer-mailing","count":1000000,"generator":{"alphabet":"ABCDEF0123","length":8},"avantage":{"percent":10}}"#;
+
+    assert!(serde_json::from_str::<VoucherBatch>(voucher_batch_with_too_many_codes_str).is_err());
+}
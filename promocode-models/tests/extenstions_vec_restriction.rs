@@ -1,6 +1,16 @@
 use chrono::{Datelike, TimeDelta, Utc};
 use promocode_models::{
-    promocode::{avantage::Avantage, restriction::Restriction, restrictions::RestrictionsExt, temp::Temp, Promocode},
+    forecast::{Forecast, ForecastStep, Observation},
+    promocode::{
+        avantage::Avantage,
+        comparison::Comparison,
+        forecast_window::{Aggregation, ForecastWindow},
+        meteo_metrics::MeteoMetrics,
+        restriction::Restriction,
+        restrictions::RestrictionsExt,
+        temp::Temp,
+        Promocode,
+    },
     promocode_request::{arguments::Arguments, meteo::Meteo, PromocodeRequest},
 };
 
@@ -100,36 +110,21 @@ fn check_request_date() {
     )
     .unwrap();
 
-    assert_eq!(
-        promocode_with_past_date
-            .restrictions
-            .check_restriction_or(request.arguments.clone(), None),
-        false
-    );
-    assert_eq!(
-        promocode_with_future_date
-            .restrictions
-            .check_restriction_or(request.arguments.clone(), None),
-        false
-    );
-    assert_eq!(
-        promocode_with_in_range_date
-            .restrictions
-            .check_restriction_or(request.arguments.clone(), None),
-        true
-    );
-    assert_eq!(
-        promocode_with_useless_case_date
-            .restrictions
-            .check_restriction_or(request.arguments.clone(), None),
-        true
-    );
-    assert_eq!(
-        promocode_with_today_date
-            .restrictions
-            .check_restriction_or(request.arguments.clone(), None),
-        true
-    );
+    assert!(!promocode_with_past_date
+        .restrictions
+        .check_restriction_or(request.arguments.clone(), None));
+    assert!(!promocode_with_future_date
+        .restrictions
+        .check_restriction_or(request.arguments.clone(), None));
+    assert!(promocode_with_in_range_date
+        .restrictions
+        .check_restriction_or(request.arguments.clone(), None));
+    assert!(promocode_with_useless_case_date
+        .restrictions
+        .check_restriction_or(request.arguments.clone(), None));
+    assert!(promocode_with_today_date
+        .restrictions
+        .check_restriction_or(request.arguments.clone(), None));
 }
 
 #[test]
@@ -174,117 +169,75 @@ fn check_request_age() {
         .unwrap()
     };
 
-    assert_eq!(
-        promocode_with_eq_30_age.restrictions.check_restriction_or(
-            request_base("age testing - eq 30".to_string(), 31).arguments,
-            None
-        ),
-        false
-    );
-    assert_eq!(
-        promocode_with_eq_30_age.restrictions.check_restriction_or(
-            request_base("age testing - eq 30".to_string(), 30).arguments,
-            None
-        ),
-        true
-    );
-    assert_eq!(
-        promocode_with_eq_30_age.restrictions.check_restriction_or(
-            request_base("age testing - eq 30".to_string(), 29).arguments,
-            None
-        ),
-        false
-    );
+    assert!(!promocode_with_eq_30_age.restrictions.check_restriction_or(
+        request_base("age testing - eq 30".to_string(), 31).arguments,
+        None
+    ));
+    assert!(promocode_with_eq_30_age.restrictions.check_restriction_or(
+        request_base("age testing - eq 30".to_string(), 30).arguments,
+        None
+    ));
+    assert!(!promocode_with_eq_30_age.restrictions.check_restriction_or(
+        request_base("age testing - eq 30".to_string(), 29).arguments,
+        None
+    ));
 
-    assert_eq!(
-        promocode_with_lt_30_age.restrictions.check_restriction_or(
-            request_base("age testing - lt 30".to_string(), 31).arguments,
+    assert!(!promocode_with_lt_30_age.restrictions.check_restriction_or(
+        request_base("age testing - lt 30".to_string(), 31).arguments,
+        None
+    ));
+    assert!(promocode_with_lt_30_age.restrictions.check_restriction_or(
+        request_base("age testing - lt 30".to_string(), 30).arguments,
+        None
+    ));
+    assert!(promocode_with_lt_30_age.restrictions.check_restriction_or(
+        request_base("age testing - lt 30".to_string(), 29).arguments,
+        None
+    ));
+
+    assert!(promocode_with_gt_30_age.restrictions.check_restriction_or(
+        request_base("age testing - gt 30".to_string(), 31).arguments,
+        None
+    ));
+    assert!(promocode_with_gt_30_age.restrictions.check_restriction_or(
+        request_base("age testing - gt 30".to_string(), 30).arguments,
+        None
+    ));
+    assert!(!promocode_with_gt_30_age.restrictions.check_restriction_or(
+        request_base("age testing - gt 30".to_string(), 29).arguments,
+        None
+    ));
+
+    assert!(!promocode_with_range_20_40_age
+        .restrictions
+        .check_restriction_or(
+            request_base("age testing - range 20..40".to_string(), 19).arguments,
             None
-        ),
-        false
-    );
-    assert_eq!(
-        promocode_with_lt_30_age.restrictions.check_restriction_or(
-            request_base("age testing - lt 30".to_string(), 30).arguments,
+        ));
+    assert!(promocode_with_range_20_40_age
+        .restrictions
+        .check_restriction_or(
+            request_base("age testing - range 20..40".to_string(), 20).arguments,
             None
-        ),
-        true
-    );
-    assert_eq!(
-        promocode_with_lt_30_age.restrictions.check_restriction_or(
-            request_base("age testing - lt 30".to_string(), 29).arguments,
+        ));
+    assert!(promocode_with_range_20_40_age
+        .restrictions
+        .check_restriction_or(
+            request_base("age testing - range 20..40".to_string(), 30).arguments,
             None
-        ),
-        true
-    );
-
-    assert_eq!(
-        promocode_with_gt_30_age.restrictions.check_restriction_or(
-            request_base("age testing - gt 30".to_string(), 31).arguments,
-            None
-        ),
-        true
-    );
-    assert_eq!(
-        promocode_with_gt_30_age.restrictions.check_restriction_or(
-            request_base("age testing - gt 30".to_string(), 30).arguments,
+        ));
+    assert!(promocode_with_range_20_40_age
+        .restrictions
+        .check_restriction_or(
+            request_base("age testing - range 20..40".to_string(), 40).arguments,
             None
-        ),
-        true
-    );
-    assert_eq!(
-        promocode_with_gt_30_age.restrictions.check_restriction_or(
-            request_base("age testing - gt 30".to_string(), 29).arguments,
+        ));
+    assert!(!promocode_with_range_20_40_age
+        .restrictions
+        .check_restriction_or(
+            request_base("age testing - range 20..40".to_string(), 41).arguments,
             None
-        ),
-        false
-    );
-
-    assert_eq!(
-        promocode_with_range_20_40_age
-            .restrictions
-            .check_restriction_or(
-                request_base("age testing - range 20..40".to_string(), 19).arguments,
-                None
-            ),
-        false
-    );
-    assert_eq!(
-        promocode_with_range_20_40_age
-            .restrictions
-            .check_restriction_or(
-                request_base("age testing - range 20..40".to_string(), 20).arguments,
-                None
-            ),
-        true
-    );
-    assert_eq!(
-        promocode_with_range_20_40_age
-            .restrictions
-            .check_restriction_or(
-                request_base("age testing - range 20..40".to_string(), 30).arguments,
-                None
-            ),
-        true
-    );
-    assert_eq!(
-        promocode_with_range_20_40_age
-            .restrictions
-            .check_restriction_or(
-                request_base("age testing - range 20..40".to_string(), 40).arguments,
-                None
-            ),
-        true
-    );
-    assert_eq!(
-        promocode_with_range_20_40_age
-            .restrictions
-            .check_restriction_or(
-                request_base("age testing - range 20..40".to_string(), 41).arguments,
-                None
-            ),
-        false
-    );
+        ));
 }
 
 #[test]
@@ -303,89 +256,65 @@ fn check_request_meteo() {
     )
     .unwrap();
 
-    assert_eq!(
-        promocode_with_clear_15_meteo
-            .restrictions
-            .check_restriction_or(request.arguments.clone(), None),
-        false
-    );
-    assert_eq!(
-        promocode_with_clear_15_meteo
-            .restrictions
-            .check_restriction_or(
-                request.arguments.clone(),
-                Some(("not clear".to_string(), 1f64))
-            ),
-        false
-    );
-    assert_eq!(
-        promocode_with_clear_15_meteo
-            .restrictions
-            .check_restriction_or(
-                request.arguments.clone(),
-                Some(("not clear".to_string(), 15f64))
-            ),
-        false
-    );
-    assert_eq!(
-        promocode_with_clear_15_meteo
-            .restrictions
-            .check_restriction_or(
-                request.arguments.clone(),
-                Some(("not clear".to_string(), 42f64))
-            ),
-        false
-    );
+    assert!(!promocode_with_clear_15_meteo
+        .restrictions
+        .check_restriction_or(request.arguments.clone(), None));
+    assert!(!promocode_with_clear_15_meteo
+        .restrictions
+        .check_restriction_or(
+            request.arguments.clone(),
+            Some(Observation::new("not clear".to_string(), 1f64).into())
+        ));
+    assert!(!promocode_with_clear_15_meteo
+        .restrictions
+        .check_restriction_or(
+            request.arguments.clone(),
+            Some(Observation::new("not clear".to_string(), 15f64).into())
+        ));
+    assert!(!promocode_with_clear_15_meteo
+        .restrictions
+        .check_restriction_or(
+            request.arguments.clone(),
+            Some(Observation::new("not clear".to_string(), 42f64).into())
+        ));
 
-    assert_eq!(
-        promocode_with_clear_15_meteo
-            .restrictions
-            .check_restriction_or(request.arguments.clone(), Some(("Clear".to_string(), 1f64))),
-        false
-    );
-    assert_eq!(
-        promocode_with_clear_15_meteo
-            .restrictions
-            .check_restriction_or(
-                request.arguments.clone(),
-                Some(("Clear".to_string(), 15f64))
-            ),
-        false
-    );
-    assert_eq!(
-        promocode_with_clear_15_meteo
-            .restrictions
-            .check_restriction_or(
-                request.arguments.clone(),
-                Some(("Clear".to_string(), 42f64))
-            ),
-        false
-    );
+    assert!(!promocode_with_clear_15_meteo
+        .restrictions
+        .check_restriction_or(
+            request.arguments.clone(),
+            Some(Observation::new("Clear".to_string(), 1f64).into())
+        ));
+    assert!(!promocode_with_clear_15_meteo
+        .restrictions
+        .check_restriction_or(
+            request.arguments.clone(),
+            Some(Observation::new("Clear".to_string(), 15f64).into())
+        ));
+    assert!(!promocode_with_clear_15_meteo
+        .restrictions
+        .check_restriction_or(
+            request.arguments.clone(),
+            Some(Observation::new("Clear".to_string(), 42f64).into())
+        ));
 
-    assert_eq!(
-        promocode_with_clear_15_meteo
-            .restrictions
-            .check_restriction_or(request.arguments.clone(), Some(("clear".to_string(), 1f64))),
-        false
-    );
-    assert_eq!(
-        promocode_with_clear_15_meteo
-            .restrictions
-            .check_restriction_or(
-                request.arguments.clone(),
-                Some(("clear".to_string(), 15f64))
-            ),
-        true
-    );
-    assert_eq!(
-        promocode_with_clear_15_meteo
-            .restrictions
-            .check_restriction_or(
-                request.arguments.clone(),
-                Some(("clear".to_string(), 42f64))
-            ),
-        true
-    );
+    assert!(!promocode_with_clear_15_meteo
+        .restrictions
+        .check_restriction_or(
+            request.arguments.clone(),
+            Some(Observation::new("clear".to_string(), 1f64).into())
+        ));
+    assert!(promocode_with_clear_15_meteo
+        .restrictions
+        .check_restriction_or(
+            request.arguments.clone(),
+            Some(Observation::new("clear".to_string(), 15f64).into())
+        ));
+    assert!(promocode_with_clear_15_meteo
+        .restrictions
+        .check_restriction_or(
+            request.arguments.clone(),
+            Some(Observation::new("clear".to_string(), 42f64).into())
+        ));
 }
 
 #[test]
@@ -428,28 +357,243 @@ fn check_request_and_or() {
     )
     .unwrap();
 
+    assert!(!promocode_with_eq_19_age_or_20_40_age
+        .restrictions
+        .check_restriction_or(request_with_18_age.arguments, None));
+    assert!(!promocode_with_eq_19_age_or_20_40_age
+        .restrictions
+        .check_restriction_or(request_with_41_age.arguments, None));
+    assert!(promocode_with_eq_19_age_or_20_40_age
+        .restrictions
+        .check_restriction_or(request_with_19_age.arguments, None));
+    assert!(promocode_with_eq_19_age_or_20_40_age
+        .restrictions
+        .check_restriction_or(request_with_30_age.arguments, None));
+}
+
+#[test]
+fn check_request_without_weather() {
+    let arguments = |age: u8| Arguments::new(age, Meteo::new("Lyon".to_string())).unwrap();
+    let meteo = || Restriction::meteo("clear".to_string(), Temp { gt: 15 });
+
+    let restrictions = vec![
+        Restriction::age(None, Some(19), None).unwrap(),
+        Restriction::and(vec![Restriction::age(None, None, Some(20)), meteo()]).unwrap(),
+    ];
+    // Decided by the age alone.
+    assert_eq!(
+        restrictions.check_restriction_or_without_weather(&arguments(19)),
+        Some(true)
+    );
+    assert_eq!(
+        restrictions.check_restriction_or_without_weather(&arguments(18)),
+        Some(false)
+    );
+    // Decided by the weather.
+    assert_eq!(
+        restrictions.check_restriction_or_without_weather(&arguments(30)),
+        None
+    );
     assert_eq!(
-        promocode_with_eq_19_age_or_20_40_age
-            .restrictions
-            .check_restriction_or(request_with_18_age.arguments, None),
-        false
+        restrictions.check_restriction_and_without_weather(&arguments(19)),
+        Some(false)
     );
     assert_eq!(
-        promocode_with_eq_19_age_or_20_40_age
-            .restrictions
-            .check_restriction_or(request_with_41_age.arguments, None),
-        false
+        restrictions.check_restriction_and_without_weather(&arguments(30)),
+        Some(false)
     );
+
+    let only_meteo = vec![meteo().unwrap()];
+    assert_eq!(
+        only_meteo.check_restriction_or_without_weather(&arguments(30)),
+        None
+    );
+    assert_eq!(meteo().unwrap().check_without_weather(&arguments(30)), None);
+
+    let empty: Vec<Restriction> = vec![];
     assert_eq!(
-        promocode_with_eq_19_age_or_20_40_age
-            .restrictions
-            .check_restriction_or(request_with_19_age.arguments, None),
-        true
+        empty.check_restriction_or_without_weather(&arguments(30)),
+        Some(false)
     );
     assert_eq!(
-        promocode_with_eq_19_age_or_20_40_age
-            .restrictions
-            .check_restriction_or(request_with_30_age.arguments, None),
-        true
+        empty.check_restriction_and_without_weather(&arguments(30)),
+        Some(true)
     );
 }
+
+#[test]
+fn check_request_meteo_forecast() {
+    let now = Utc::now();
+    let forecast = Forecast::new(
+        [
+            ("clear", 20f64),
+            ("clear", 24f64),
+            ("rain", 18f64),
+            ("rain", 27f64),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(index, (condition, temp))| ForecastStep {
+            at: now + TimeDelta::hours(3 * index as i64),
+            observation: Observation::new(condition.to_string(), temp),
+        })
+        .collect(),
+    )
+    .unwrap();
+    let arguments = Arguments::new(1, Meteo::new("Lyon".to_string())).unwrap();
+    let check = |is: &str, gt: i8, window: Result<ForecastWindow, String>| {
+        vec![Restriction::meteo_forecast(is.to_string(), Temp { gt }, window).unwrap()].check_restriction_or(arguments.clone(), Some(forecast.clone()))
+    };
+
+    // Without a window, only the current weather is checked.
+    let current = vec![Restriction::meteo("rain".to_string(), Temp { gt: 0 }).unwrap()];
+    assert!(!current.check_restriction_or(arguments.clone(), Some(forecast.clone())));
+
+    // The next 2 hours only hold the current weather.
+    assert!(!check(
+        "rain",
+        0,
+        ForecastWindow::new(2, Aggregation::Any, Aggregation::Max)
+    ));
+    assert!(check(
+        "rain",
+        0,
+        ForecastWindow::new(7, Aggregation::Any, Aggregation::Max)
+    ));
+    assert!(check(
+        "clear",
+        0,
+        ForecastWindow::new(5, Aggregation::All, Aggregation::Max)
+    ));
+    assert!(!check(
+        "clear",
+        0,
+        ForecastWindow::new(7, Aggregation::All, Aggregation::Max)
+    ));
+
+    assert!(check(
+        "clear",
+        24,
+        ForecastWindow::new(5, Aggregation::Any, Aggregation::Max)
+    ));
+    assert!(!check(
+        "clear",
+        25,
+        ForecastWindow::new(5, Aggregation::Any, Aggregation::Max)
+    ));
+    assert!(check(
+        "clear",
+        20,
+        ForecastWindow::new(5, Aggregation::Any, Aggregation::Min)
+    ));
+    assert!(!check(
+        "clear",
+        20,
+        ForecastWindow::new(10, Aggregation::Any, Aggregation::Min)
+    ));
+    assert!(check(
+        "rain",
+        27,
+        ForecastWindow::new(120, Aggregation::Any, Aggregation::Max)
+    ));
+}
+
+#[test]
+fn check_request_meteo_metrics() {
+    let arguments = Arguments::new(1, Meteo::new("Lyon".to_string())).unwrap();
+    let dry_and_calm = || {
+        MeteoMetrics::new(
+            None,
+            Some(Comparison::new(Some(60.0), None, None).unwrap()),
+            Some(Comparison::new(Some(8.0), None, None).unwrap()),
+            Some(Comparison::new(None, Some(0.0), None).unwrap()),
+        )
+    };
+    let check = |restriction: Result<Restriction, String>, observation: Observation| {
+        vec![restriction.unwrap()].check_restriction_or(arguments.clone(), Some(observation.into()))
+    };
+    let restriction = || Restriction::meteo_metrics("clear".to_string(), Temp { gt: 15 }, dry_and_calm());
+    let observation = || {
+        Observation::new("clear".to_string(), 20f64)
+            .with_humidity(40.0)
+            .with_wind(3.5)
+            .with_rain(0.0)
+    };
+
+    assert!(check(restriction(), observation()));
+    assert!(!check(restriction(), observation().with_humidity(75.0)));
+    assert!(check(restriction(), observation().with_wind(8.0)));
+    assert!(!check(restriction(), observation().with_wind(12.0)));
+    assert!(!check(restriction(), observation().with_rain(0.4)));
+    // A metric the weather source does not give is not satisfied.
+    assert!(!check(
+        restriction(),
+        Observation::new("clear".to_string(), 20f64)
+    ));
+
+    let feels_like = MeteoMetrics::new(
+        Some(Comparison::new(None, None, Some(25.0)).unwrap()),
+        None,
+        None,
+        None,
+    );
+    let restriction = Restriction::meteo_metrics("clear".to_string(), Temp { gt: 15 }, feels_like.clone());
+    assert!(check(restriction, observation().with_feels_like(27.0)));
+    let restriction = Restriction::meteo_metrics("clear".to_string(), Temp { gt: 15 }, feels_like);
+    assert!(!check(restriction, observation().with_feels_like(21.0)));
+
+    // On a window, the metrics are aggregated as the weather.
+    let now = Utc::now();
+    let forecast = Forecast::new(
+        [observation(), observation().with_wind(15.0)]
+            .into_iter()
+            .enumerate()
+            .map(|(index, observation)| ForecastStep {
+                at: now + TimeDelta::hours(3 * index as i64),
+                observation,
+            })
+            .collect(),
+    )
+    .unwrap();
+    let windy = |is: Aggregation| {
+        vec![Restriction::meteo_with(
+            "clear".to_string(),
+            Temp { gt: 15 },
+            Some(ForecastWindow::new(5, is, Aggregation::Max)),
+            dry_and_calm(),
+        )
+        .unwrap()]
+        .check_restriction_or(arguments.clone(), Some(forecast.clone()))
+    };
+    assert!(windy(Aggregation::Any));
+    assert!(!windy(Aggregation::All));
+
+    // The condition and the metrics must match on the same step.
+    let forecast = Forecast::new(
+        [
+            Observation::new("rain".to_string(), 20f64).with_humidity(90.0),
+            Observation::new("clear".to_string(), 20f64).with_humidity(40.0),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(index, observation)| ForecastStep {
+            at: now + TimeDelta::hours(3 * index as i64),
+            observation,
+        })
+        .collect(),
+    )
+    .unwrap();
+    let dry_rain = vec![Restriction::meteo_with(
+        "rain".to_string(),
+        Temp { gt: 15 },
+        Some(ForecastWindow::new(5, Aggregation::Any, Aggregation::Max)),
+        MeteoMetrics::new(
+            None,
+            Some(Comparison::new(Some(60.0), None, None).unwrap()),
+            None,
+            None,
+        ),
+    )
+    .unwrap()];
+    assert!(!dry_rain.check_restriction_or(arguments.clone(), Some(forecast)));
+}
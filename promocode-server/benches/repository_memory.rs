@@ -0,0 +1,47 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use promocode_models::promocode::{avantage::Avantage, Promocode};
+use promocode_server::repository::{memory::InMemoryRepository, PromocodeRepository};
+
+/// Returns a repository holding `size` promocodes.
+fn repository(size: usize) -> InMemoryRepository {
+    let repository = InMemoryRepository::new();
+    repository
+        .insert_batch(
+            (0..size)
+                .map(|index| {
+                    Promocode::new(
+                        format!("id {}", index),
+                        format!("NAME {}", index),
+                        Avantage::new(10),
+                        vec![],
+                    )
+                    .unwrap()
+                })
+                .collect(),
+        )
+        .unwrap();
+    repository
+}
+
+/// The cost of a lookup should not depend on the size of the repository.
+fn lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lookup");
+
+    for size in [100, 10_000, 100_000] {
+        let repository = repository(size);
+        let name = format!("NAME {}", size / 2);
+        let id = format!("id {}", size / 2);
+
+        group.bench_with_input(BenchmarkId::new("get_by_name", size), &name, |b, name| {
+            b.iter(|| repository.get_by_name(black_box(name)).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("get_by_id", size), &id, |b, id| {
+            b.iter(|| repository.get_by_id(black_box(id)).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, lookup);
+criterion_main!(benches);
@@ -0,0 +1,298 @@
+//! Authentication of the requests with API keys.
+//!
+//! Every route requires a [Scope], checked by its [Authorized] extractor
+//! against the [ApiKeys] of the application state. The key is sent as
+//! `Authorization: Bearer <key>`.
+
+use std::{
+    fmt::{Display, Formatter},
+    fs,
+    marker::PhantomData,
+    path::Path,
+};
+
+use ntex::{
+    http::{header, Payload, StatusCode},
+    web::{DefaultError, ErrorRenderer, FromRequest, HttpRequest, HttpResponse, WebResponseError},
+};
+use promocode_util::validate_type::string::NonBlankString;
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256};
+
+/// What an API key is allowed to do.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// Read the promocodes.
+    Read,
+    /// Create, update and delete the promocodes, sign promocodes.
+    Write,
+    /// Validate promocodes.
+    Validate,
+}
+
+impl Display for Scope {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Scope::Read => write!(f, "read"),
+            Scope::Write => write!(f, "write"),
+            Scope::Validate => write!(f, "validate"),
+        }
+    }
+}
+
+/// An API key, of which only the SHA-256 hash is known (e.g.
+/// `printf %s "$KEY" | sha256sum`).
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct ApiKey {
+    name: NonBlankString,
+    sha256: String,
+    scopes: Vec<Scope>,
+}
+
+impl ApiKey {
+    /// Create a new [`ApiKey`](Self)
+    ///
+    /// # Errors
+    ///
+    /// This function fails if `name` is blank or if `sha256` is not a
+    /// hexadecimal SHA-256 hash.
+    pub fn new(name: String, sha256: String, scopes: Vec<Scope>) -> Result<Self, String> {
+        let name = match NonBlankString::new(name) {
+            Err(err_name) => return Err(format!("`name` {}", err_name)),
+            Ok(value) => value,
+        };
+
+        let sha256 = sha256.to_lowercase();
+        if sha256.len() != 64 || !sha256.chars().all(|it| it.is_ascii_hexdigit()) {
+            return Err(format!(
+                "`sha256` of `{}` is not a hexadecimal SHA-256 hash",
+                name
+            ));
+        }
+
+        Ok(Self {
+            name,
+            sha256,
+            scopes,
+        })
+    }
+
+    /// Returns the name as [String] type
+    pub fn name(&self) -> String {
+        self.name.clone().get()
+    }
+}
+
+impl<'de> Deserialize<'de> for ApiKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct ApiKeyUnsafe {
+            name: String,
+            sha256: String,
+            scopes: Vec<Scope>,
+        }
+
+        match ApiKeyUnsafe::deserialize(deserializer) {
+            Ok(data) => ApiKey::new(data.name, data.sha256, data.scopes).map_err(Error::custom),
+            Err(err) => Err(Error::custom(err)),
+        }
+    }
+}
+
+/// Returns the hexadecimal SHA-256 hash of `key`.
+pub fn hash_api_key(key: &str) -> String {
+    Sha256::digest(key.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// The [ApiKey]s accepted by the application, registered in its state.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ApiKeys {
+    keys: Vec<ApiKey>,
+    public_scopes: Vec<Scope>,
+}
+
+impl ApiKeys {
+    /// Create a new [`ApiKeys`](Self).
+    ///
+    /// The `public_scopes` are granted to every request, with or without a
+    /// key.
+    pub fn new(keys: Vec<ApiKey>, public_scopes: Vec<Scope>) -> Self {
+        Self {
+            keys,
+            public_scopes,
+        }
+    }
+
+    /// Parses a JSON array of [ApiKey]s.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the JSON is not valid.
+    pub fn parse(json: &str) -> Result<Vec<ApiKey>, String> {
+        serde_json::from_str(json).map_err(|err| format!("Cannot parse the API keys: {}", err))
+    }
+
+    /// Loads the JSON array of [ApiKey]s stored at `path`.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the file cannot be read or is not valid.
+    pub fn load(path: &Path) -> Result<Vec<ApiKey>, String> {
+        match fs::read_to_string(path) {
+            Err(err) => Err(format!("Cannot read `{}`: {}", path.display(), err)),
+            Ok(json) => Self::parse(&json).map_err(|err| format!("`{}`: {}", path.display(), err)),
+        }
+    }
+
+    /// Returns the number of [ApiKey]s.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if there is no [ApiKey].
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Checks that `key` grants `scope`.
+    ///
+    /// # Errors
+    ///
+    /// This function fails with an [AuthError::Unauthenticated] if the scope
+    /// is not public and `key` is missing or unknown, or with an
+    /// [AuthError::Forbidden] if `key` does not grant `scope`.
+    pub fn authorize(&self, key: Option<&str>, scope: Scope) -> Result<(), AuthError> {
+        if self.public_scopes.contains(&scope) {
+            return Ok(());
+        }
+
+        let sha256 = match key {
+            None => return Err(AuthError::Unauthenticated),
+            Some(key) => hash_api_key(key),
+        };
+        match self
+            .keys
+            .iter()
+            .find(|it| constant_time_eq(&it.sha256, &sha256))
+        {
+            None => Err(AuthError::Unauthenticated),
+            Some(api_key) if api_key.scopes.contains(&scope) => Ok(()),
+            Some(api_key) => Err(AuthError::Forbidden {
+                name: api_key.name(),
+                scope,
+            }),
+        }
+    }
+}
+
+/// Compares two hashes in a time independent of their content.
+fn constant_time_eq(left: &str, right: &str) -> bool {
+    left.len() == right.len()
+        && left
+            .bytes()
+            .zip(right.bytes())
+            .fold(0u8, |diff, (left, right)| diff | (left ^ right))
+            == 0
+}
+
+/// Error of the [Authorized] extractor.
+#[derive(Clone, PartialEq, Debug)]
+pub enum AuthError {
+    /// The API key is missing or unknown.
+    Unauthenticated,
+    /// The API key does not grant the scope.
+    Forbidden { name: String, scope: Scope },
+    /// No [ApiKeys] are registered in the application state.
+    NotConfigured,
+}
+
+impl Display for AuthError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthError::Unauthenticated => write!(f, "A valid API key is required."),
+            AuthError::Forbidden { name, scope } => write!(
+                f,
+                "API key `{}` does not grant the `{}` scope.",
+                name, scope
+            ),
+            AuthError::NotConfigured => write!(f, "Authentication is not configured."),
+        }
+    }
+}
+
+impl WebResponseError<DefaultError> for AuthError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AuthError::Unauthenticated => StatusCode::UNAUTHORIZED,
+            AuthError::Forbidden { .. } => StatusCode::FORBIDDEN,
+            AuthError::NotConfigured => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self, _: &HttpRequest) -> HttpResponse {
+        let mut response = HttpResponse::build(WebResponseError::<DefaultError>::status_code(self));
+        if *self == AuthError::Unauthenticated {
+            response.header(header::WWW_AUTHENTICATE, "Bearer");
+        }
+        response.json(&self.to_string())
+    }
+}
+
+/// Scope required by an [Authorized] extractor.
+pub trait RequiredScope {
+    const SCOPE: Scope;
+}
+
+/// Marker types of the [Scope]s, for [Authorized].
+pub mod scope {
+    use super::{RequiredScope, Scope};
+
+    /// [Scope::Read]
+    pub struct Read;
+    /// [Scope::Write]
+    pub struct Write;
+    /// [Scope::Validate]
+    pub struct Validate;
+
+    impl RequiredScope for Read {
+        const SCOPE: Scope = Scope::Read;
+    }
+
+    impl RequiredScope for Write {
+        const SCOPE: Scope = Scope::Write;
+    }
+
+    impl RequiredScope for Validate {
+        const SCOPE: Scope = Scope::Validate;
+    }
+}
+
+/// Extractor rejecting the requests whose API key does not grant the scope
+/// `S` (e.g. `Authorized<scope::Write>`).
+pub struct Authorized<S: RequiredScope>(PhantomData<S>);
+
+impl<S: RequiredScope, E: ErrorRenderer> FromRequest<E> for Authorized<S> {
+    type Error = AuthError;
+
+    async fn from_request(req: &HttpRequest, _: &mut Payload) -> Result<Self, Self::Error> {
+        let api_keys = req.app_state::<ApiKeys>().ok_or(AuthError::NotConfigured)?;
+
+        let key = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim);
+
+        api_keys
+            .authorize(key, S::SCOPE)
+            .map(|_| Authorized(PhantomData))
+    }
+}
@@ -0,0 +1,54 @@
+//! Serves the geocoding and forecast endpoints of the OpenWeather API from a
+//! fixtures file, to run the server offline with
+//! `--open-weather-base-url http://127.0.0.1:8090`.
+
+use std::path::PathBuf;
+
+use clap::Parser;
+use log::{error, info};
+use ntex::web::{App, HttpServer};
+
+use promocode_server::weather::{fixtures::WeatherFixtures, mock::mock_services};
+
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+struct MockCli {
+    /// Hostname to operate on
+    #[arg(long, value_name = "HOSTNAME", default_value = "127.0.0.1")]
+    host: String,
+
+    /// Port to operate on
+    #[arg(long, value_name = "PORT", default_value_t = 8090)]
+    port: u16,
+
+    /// JSON file of the weather of the towns, as
+    /// `{"towns": [{"name": ..., "lat": ..., "lon": ..., "condition": ..., "temp": ...}]}`.
+    #[arg(long, value_name = "FILE", default_value = "etc/weather/fixtures.json")]
+    fixtures: PathBuf,
+}
+
+#[ntex::main]
+async fn main() -> std::io::Result<()> {
+    env_logger::init();
+
+    let cli = MockCli::parse();
+
+    let fixtures = match WeatherFixtures::load(&cli.fixtures) {
+        Ok(fixtures) => fixtures,
+        Err(err) => {
+            error!("{}", err);
+            return Ok(());
+        },
+    };
+    info!(
+        "Weather of {} towns served at {}:{}.",
+        fixtures.towns.len(),
+        cli.host,
+        cli.port
+    );
+
+    HttpServer::new(move || App::new().state(fixtures.clone()).configure(mock_services))
+        .bind((cli.host, cli.port))?
+        .run()
+        .await
+}
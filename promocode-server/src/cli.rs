@@ -1,6 +1,28 @@
-use clap::Parser;
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+
+use crate::{
+    auth::Scope,
+    repository::file::{Durability, FileRepository},
+    weather::{open_weather_sdk::DEFAULT_OPEN_WEATHER_BASE_URL, quota::QuotaPolicy, WeatherProviderKind},
+};
 
 pub const ENV_VAR_NAME_OPEN_WEATHER_MAP_API_KEY: &str = "OPEN_WEATHER_MAP_API_KEY";
+pub const ENV_VAR_NAME_OPEN_WEATHER_MAP_BASE_URL: &str = "OPEN_WEATHER_MAP_BASE_URL";
+pub const ENV_VAR_NAME_PROMOCODE_SIGNING_KEY: &str = "PROMOCODE_SIGNING_KEY";
+pub const ENV_VAR_NAME_PROMOCODE_API_KEYS: &str = "PROMOCODE_API_KEYS";
+
+/// Where the promocodes are stored.
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Storage {
+    /// In memory, everything is lost when the server stops.
+    Memory,
+    /// In `--data-dir`, as a snapshot and a journal of the writes.
+    File,
+    /// In `--data-dir`, as a SQLite database.
+    Sqlite,
+}
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -13,12 +35,154 @@ pub struct Cli {
     #[arg(long, value_name = "PORT", default_value_t = 8080)]
     pub port: u16,
 
+    /// Hostname of the listener of the administrative routes.
+    #[arg(long, value_name = "HOSTNAME", default_value = "127.0.0.1")]
+    pub admin_host: String,
+
+    /// Port of the listener of the administrative routes (creating, listing,
+    /// deleting the promocodes...).
+    ///
+    /// The `--host` and `--port` listener then only serves the validation of
+    /// the promocodes. Every route is served there when not set.
+    #[arg(long, value_name = "PORT")]
+    pub admin_port: Option<u16>,
+
+    /// JSON file describing the promocode naming policy (trimming, case
+    /// folding, confusable characters, allowed charset, length bounds and
+    /// check digit).
+    ///
+    /// Names are stored and looked up as given when not set: trimming and
+    /// case folding are opt-in.
+    #[arg(long, value_name = "FILE")]
+    pub name_policy: Option<PathBuf>,
+
+    /// Where the promocodes are stored.
+    #[arg(long, value_enum, default_value_t = Storage::Memory)]
+    pub storage: Storage,
+
+    /// Directory of the `file` and `sqlite` storages.
+    #[arg(long, value_name = "DIR", default_value = "data")]
+    pub data_dir: PathBuf,
+
+    /// When the journal of the `file` storage is written to the disk.
+    #[arg(long, value_enum, default_value_t = Durability::Fsync)]
+    pub durability: Durability,
+
+    /// Number of writes after which the journal of the `file` storage is
+    /// compacted into its snapshot.
+    #[arg(long, value_name = "WRITES", default_value_t = FileRepository::DEFAULT_COMPACT_EVERY)]
+    pub compact_every: usize,
+
+    /// Serves `GET /promocode` with a JSON body, the deprecated alias of
+    /// `POST /promocode/validate`.
+    #[arg(long, value_name = "BOOL", default_value_t = true, action = clap::ArgAction::Set)]
+    pub deprecated_get_validation: bool,
+
+    /// JSON file holding the API keys, as an array of
+    /// `{"name": ..., "sha256": ..., "scopes": ["read", "write", "validate"]}`.
+    ///
+    /// Only the SHA-256 hash of a key is stored (`printf %s "$KEY" | sha256sum`).
+    #[arg(long, value_name = "FILE")]
+    pub api_keys: Option<PathBuf>,
+
+    /// API keys, in the format of `--api-keys`, added to the ones of the
+    /// file.
+    #[arg(long = "api-keys-json", env = ENV_VAR_NAME_PROMOCODE_API_KEYS, value_name = "JSON", hide_env_values = true)]
+    pub api_keys_json: Option<String>,
+
+    /// Scopes granted to every request, with or without an API key. None when
+    /// given without a value.
+    #[arg(long, value_enum, value_delimiter = ',', num_args = 0.., default_value = "validate")]
+    pub public_scopes: Vec<Scope>,
+
+    /// Where the weather checked by the `@meteo` restrictions comes from.
+    #[arg(long, value_enum, default_value_t = WeatherProviderKind::OpenWeather)]
+    pub weather_provider: WeatherProviderKind,
+
+    /// JSON file of the `fixtures` weather provider, as
+    /// `{"towns": [{"name": ..., "lat": ..., "lon": ..., "condition": ..., "temp": ...}]}`.
+    #[arg(
+        long,
+        value_name = "FILE",
+        required_if_eq("weather_provider", "fixtures")
+    )]
+    pub weather_fixtures: Option<PathBuf>,
+
+    /// Number of towns, and of locations, whose weather is cached. The weather
+    /// is not cached when `0`.
+    #[arg(long, value_name = "ENTRIES", default_value_t = 1024)]
+    pub weather_cache_size: usize,
+
+    /// Seconds during which the location of a town is cached.
+    #[arg(long, value_name = "SECONDS", default_value_t = 7 * 24 * 60 * 60)]
+    pub geocoding_cache_ttl: u64,
+
+    /// Seconds during which the weather at a location is cached.
+    #[arg(long, value_name = "SECONDS", default_value_t = 10 * 60)]
+    pub weather_cache_ttl: u64,
+
+    /// Milliseconds after which a call to the weather provider is abandoned.
+    #[arg(long, value_name = "MILLISECONDS", default_value_t = 2_000)]
+    pub weather_timeout: u64,
+
+    /// Number of times a failed call to the weather provider is retried.
+    #[arg(long, value_name = "RETRIES", default_value_t = 2)]
+    pub weather_retries: u32,
+
+    /// Milliseconds before the first retry of a failed call to the weather
+    /// provider, doubled before each next one.
+    #[arg(long, value_name = "MILLISECONDS", default_value_t = 200)]
+    pub weather_retry_backoff: u64,
+
+    /// Number of consecutive failed calls after which the weather provider is
+    /// not called anymore for `--weather-circuit-open` seconds. Never when
+    /// `0`.
+    #[arg(long, value_name = "CALLS", default_value_t = 5)]
+    pub weather_circuit_failures: u32,
+
+    /// Seconds during which the weather provider is not called after
+    /// `--weather-circuit-failures` consecutive failed calls.
+    #[arg(long, value_name = "SECONDS", default_value_t = 30)]
+    pub weather_circuit_open: u64,
+
+    /// Calls per minute allowed to the Open Weather Map API, each retry
+    /// counting as a call. Not limited when `0`.
+    #[arg(long, value_name = "CALLS", default_value_t = 60)]
+    pub weather_quota_per_minute: u32,
+
+    /// Calls per month allowed to the Open Weather Map API, each retry
+    /// counting as a call. Not limited when `0`.
+    #[arg(long, value_name = "CALLS", default_value_t = 1_000_000)]
+    pub weather_quota_per_month: u64,
+
+    /// JSON file where the calls of the month are saved, to count them across
+    /// restarts.
+    #[arg(long, value_name = "FILE")]
+    pub weather_quota_file: Option<PathBuf>,
+
+    /// What to do with the calls to the Open Weather Map API once the quota is
+    /// exhausted.
+    #[arg(long, value_enum, default_value_t = QuotaPolicy::Stale)]
+    pub weather_quota_exhausted: QuotaPolicy,
+
     /// Open Weather Map API key.
     ///
-    /// Quota:
+    /// Quota, enforced with `--weather-quota-per-minute` and
+    /// `--weather-quota-per-month`:
     ///
     ///  - `60` calls/minute
     ///  - `1_000_000` calls/month
     #[arg(env = ENV_VAR_NAME_OPEN_WEATHER_MAP_API_KEY, default_value = "")]
     pub open_weather_map_api_key: String,
+
+    /// Base URL of the Open Weather Map API, e.g. the one of the
+    /// `weather-mock` binary to run offline.
+    #[arg(long, env = ENV_VAR_NAME_OPEN_WEATHER_MAP_BASE_URL, value_name = "URL", default_value = DEFAULT_OPEN_WEATHER_BASE_URL)]
+    pub open_weather_base_url: String,
+
+    /// Key used to sign and verify the self-contained promocodes.
+    ///
+    /// Signed promocodes are denied when it is empty.
+    #[arg(long, env = ENV_VAR_NAME_PROMOCODE_SIGNING_KEY, default_value = "", hide_env_values = true)]
+    pub signing_key: String,
 }
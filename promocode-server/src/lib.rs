@@ -1,4 +1,7 @@
+pub mod auth;
 pub mod cli;
-pub mod db;
-pub mod open_weather_sdk;
+pub mod name_policy;
+pub mod repository;
 pub mod server;
+pub mod signing_key;
+pub mod weather;
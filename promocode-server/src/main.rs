@@ -1,10 +1,24 @@
+use std::{sync::Arc, time::Duration};
+
 use clap::Parser;
 use log::{error, info, warn};
 
+use promocode_models::promocode::name_policy::NamePolicy;
 use promocode_server::{
-    cli::{Cli, ENV_VAR_NAME_OPEN_WEATHER_MAP_API_KEY},
-    open_weather_sdk::init_open_weather_sdk,
-    server,
+    auth::ApiKeys,
+    cli::{Cli, Storage, ENV_VAR_NAME_OPEN_WEATHER_MAP_API_KEY, ENV_VAR_NAME_PROMOCODE_SIGNING_KEY},
+    name_policy::load_name_policy,
+    repository::{file::FileRepository, memory::InMemoryRepository, sqlite::SqliteRepository, Repository},
+    server::{self, routes::RouteSettings},
+    signing_key::SigningKey,
+    weather::{
+        cache::CachedWeatherProvider,
+        fixtures::{FixturesWeatherProvider, WeatherFixtures},
+        open_weather_sdk::{OpenWeatherClient, OpenWeatherProvider},
+        quota::{QuotaPolicy, WeatherQuota},
+        resilience::{CircuitBreaker, ResilientWeatherProvider},
+        NoWeatherProvider, Weather, WeatherProvider, WeatherProviderKind,
+    },
 };
 
 fn main() {
@@ -12,22 +26,201 @@ fn main() {
 
     let cli = Cli::parse();
 
-    if cli.open_weather_map_api_key.is_empty() {
-        warn!(
-            "{} environment variable is empty or not exist. So all weather restrictions will return false.",
-            ENV_VAR_NAME_OPEN_WEATHER_MAP_API_KEY
-        );
+    let name_policy = match cli.name_policy {
+        None => NamePolicy::default(),
+        Some(path) => match load_name_policy(&path) {
+            Ok(name_policy) => {
+                info!("Name policy loaded from `{}`.", path.display());
+                name_policy
+            },
+            Err(err) => {
+                error!("{}", err);
+                return;
+            },
+        },
+    };
+
+    // The quota, of the OpenWeather API only, is applied to each attempt of
+    // the calls below.
+    let (weather_provider, weather_quota): (Box<dyn WeatherProvider>, Option<WeatherQuota>) = match cli.weather_provider {
+        WeatherProviderKind::OpenWeather => match OpenWeatherClient::new(cli.open_weather_map_api_key, cli.open_weather_base_url) {
+            Err(err) => {
+                warn!(
+                    "{} environment variable is empty or not exist. So all weather restrictions will return false. {}",
+                    ENV_VAR_NAME_OPEN_WEATHER_MAP_API_KEY, err
+                );
+                (Box::new(NoWeatherProvider), None)
+            },
+            Ok(client) => {
+                info!(
+                    "{} environment variable initialized.",
+                    ENV_VAR_NAME_OPEN_WEATHER_MAP_API_KEY
+                );
+                match WeatherQuota::open(
+                    cli.weather_quota_per_minute,
+                    cli.weather_quota_per_month,
+                    cli.weather_quota_exhausted,
+                    cli.weather_quota_file,
+                ) {
+                    Ok(quota) => {
+                        let usage = quota.usage();
+                        info!(
+                            "Weather quota of {} calls/minute and {} calls/month, {} calls made in {}.",
+                            cli.weather_quota_per_minute, cli.weather_quota_per_month, usage.calls, usage.month
+                        );
+                        (Box::new(OpenWeatherProvider::new(client)), Some(quota))
+                    },
+                    Err(err) => {
+                        error!("{}", err);
+                        return;
+                    },
+                }
+            },
+        },
+        WeatherProviderKind::Fixtures => {
+            // Required by the `Cli` with this provider.
+            let path = cli.weather_fixtures.unwrap_or_default();
+            match WeatherFixtures::load(&path) {
+                Ok(fixtures) => {
+                    info!("Weather fixtures loaded from `{}`.", path.display());
+                    (Box::new(FixturesWeatherProvider::new(fixtures)), None)
+                },
+                Err(err) => {
+                    error!("{}", err);
+                    return;
+                },
+            }
+        },
+    };
+    let weather_provider = ResilientWeatherProvider::new(
+        weather_provider,
+        Duration::from_millis(cli.weather_timeout),
+        cli.weather_retries,
+        Duration::from_millis(cli.weather_retry_backoff),
+        CircuitBreaker::new(
+            cli.weather_circuit_failures,
+            Duration::from_secs(cli.weather_circuit_open),
+        ),
+    );
+    let weather_provider: Box<dyn WeatherProvider> = match weather_quota {
+        None => Box::new(weather_provider),
+        Some(quota) => Box::new(weather_provider.with_quota(quota)),
+    };
+    let weather: Weather = if cli.weather_cache_size == 0 {
+        Arc::from(weather_provider)
     } else {
-        match init_open_weather_sdk(cli.open_weather_map_api_key) {
-            Ok(_) => info!(
+        info!(
+            "Weather of {} towns cached, locations for {}s, weather for {}s.",
+            cli.weather_cache_size, cli.geocoding_cache_ttl, cli.weather_cache_ttl
+        );
+        Arc::new(
+            CachedWeatherProvider::new(
+                weather_provider,
+                Duration::from_secs(cli.geocoding_cache_ttl),
+                Duration::from_secs(cli.weather_cache_ttl),
+                cli.weather_cache_size,
+            )
+            .with_stale_if_error(cli.weather_quota_exhausted == QuotaPolicy::Stale),
+        )
+    };
+    let signing_key = match SigningKey::new(cli.signing_key) {
+        Ok(signing_key) => {
+            info!(
                 "{} environment variable initialized.",
-                ENV_VAR_NAME_OPEN_WEATHER_MAP_API_KEY
-            ),
-            Err(err) => error!("{}", err),
-        };
+                ENV_VAR_NAME_PROMOCODE_SIGNING_KEY
+            );
+            signing_key
+        },
+        Err(_) => {
+            warn!(
+                "{} environment variable is empty or not exist. So all signed promocodes will be denied.",
+                ENV_VAR_NAME_PROMOCODE_SIGNING_KEY
+            );
+            SigningKey::default()
+        },
+    };
+
+    let repository: Repository = match cli.storage {
+        Storage::Memory => Arc::new(InMemoryRepository::new()),
+        Storage::File => match FileRepository::open(&cli.data_dir, cli.durability, cli.compact_every) {
+            Ok(repository) => {
+                info!("Promocodes stored in `{}`.", cli.data_dir.display());
+                Arc::new(repository)
+            },
+            Err(err) => {
+                error!("{}", err);
+                return;
+            },
+        },
+        Storage::Sqlite => {
+            let path = cli.data_dir.join("promocodes.sqlite");
+            match std::fs::create_dir_all(&cli.data_dir)
+                .map_err(|err| format!("Cannot create `{}`: {}", cli.data_dir.display(), err))
+                .and_then(|_| SqliteRepository::open(&path))
+            {
+                Ok(repository) => {
+                    info!("Promocodes stored in `{}`.", path.display());
+                    Arc::new(repository)
+                },
+                Err(err) => {
+                    error!("{}", err);
+                    return;
+                },
+            }
+        },
+    };
+
+    if cli.deprecated_get_validation {
+        warn!("`GET /promocode` is deprecated, use `POST /promocode/validate` instead.");
+    }
+    let settings = RouteSettings {
+        deprecated_get_validation: cli.deprecated_get_validation,
+    };
+
+    let mut keys = vec![];
+    if let Some(path) = &cli.api_keys {
+        match ApiKeys::load(path) {
+            Ok(loaded) => keys.extend(loaded),
+            Err(err) => {
+                error!("{}", err);
+                return;
+            },
+        }
+    }
+    if let Some(json) = &cli.api_keys_json {
+        match ApiKeys::parse(json) {
+            Ok(parsed) => keys.extend(parsed),
+            Err(err) => {
+                error!("{}", err);
+                return;
+            },
+        }
+    }
+    let api_keys = ApiKeys::new(keys, cli.public_scopes);
+    if api_keys.is_empty() {
+        warn!("No API key configured. So only the public scopes are granted.");
+    } else {
+        info!("{} API keys loaded.", api_keys.len());
+    }
+
+    let admin = cli
+        .admin_port
+        .map(|admin_port| (cli.admin_host, admin_port));
+    if admin.is_none() {
+        warn!("No `--admin-port` given. So the administrative routes are served with the public ones.");
     }
 
-    match server::serve(cli.host, cli.port) {
+    match server::serve(
+        cli.host,
+        cli.port,
+        admin,
+        repository,
+        weather,
+        name_policy,
+        signing_key,
+        settings,
+        api_keys,
+    ) {
         Ok(_) => {},
         Err(err) => {
             error!("{}", err)
@@ -0,0 +1,17 @@
+use std::{fs, path::Path};
+
+use promocode_models::promocode::name_policy::NamePolicy;
+
+/// Reads a [NamePolicy] from a JSON file.
+///
+/// The [NamePolicy] is then shared through the ntex application state, like
+/// the [Repository](crate::repository::Repository).
+///
+/// # Returns
+///
+/// Returns a [Result] with the [NamePolicy] if the file can be read and
+/// parsed, or an error message otherwise.
+pub fn load_name_policy(path: &Path) -> Result<NamePolicy, String /* Error */> {
+    let content = fs::read_to_string(path).map_err(|err| format!("Cannot read `{}`: {}", path.display(), err))?;
+    serde_json::from_str(&content).map_err(|err| format!("Cannot parse `{}`: {}", path.display(), err))
+}
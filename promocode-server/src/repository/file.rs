@@ -0,0 +1,397 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use clap::ValueEnum;
+use log::warn;
+use promocode_models::promocode::Promocode;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    check_conflicts, check_expected_version, check_version, memory::InMemoryRepository, PromocodeFilter, PromocodeRepository, RepositoryError, UniqueField,
+};
+
+const SNAPSHOT_FILE_NAME: &str = "snapshot.json";
+const JOURNAL_FILE_NAME: &str = "journal.jsonl";
+
+/// When the journal of a [FileRepository] is written to the disk.
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Durability {
+    /// Entries are buffered, and written once the buffer (8 KiB) is full, on
+    /// compaction or when the repository is dropped. A crash loses the
+    /// buffered entries.
+    Buffered,
+    /// Entries are handed to the OS after every write. A crash of the process
+    /// loses nothing, a crash of the OS may lose the last entries.
+    Flush,
+    /// Entries are synced to the disk after every write.
+    #[default]
+    Fsync,
+}
+
+/// An entry of the journal of a [FileRepository].
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum JournalEntry {
+    Insert { promocodes: Vec<Promocode> },
+    Update { promocode: Promocode },
+    Delete { id: String },
+}
+
+/// A line of the journal, numbered so that the entries already in the
+/// snapshot are skipped on replay.
+#[derive(Serialize, Deserialize, Debug)]
+struct JournalLine {
+    seq: u64,
+    #[serde(flatten)]
+    entry: JournalEntry,
+}
+
+/// Content of the snapshot: every [Promocode], up to the journal entry `seq`.
+#[derive(Serialize, Deserialize, Debug)]
+struct Snapshot {
+    seq: u64,
+    promocodes: Vec<Promocode>,
+}
+
+/// Append-only journal of the writes since the last snapshot.
+#[derive(Debug)]
+struct Journal {
+    writer: BufWriter<File>,
+    len: usize,
+    /// Number of the last entry written.
+    seq: u64,
+}
+
+/// [PromocodeRepository] persisted in a directory.
+///
+/// The directory holds a snapshot of every [Promocode] (`snapshot.json`) and
+/// a journal of the writes done since (`journal.jsonl`, one JSON entry per
+/// line). The journal is written before the in-memory state, replayed on
+/// [open](Self::open), and compacted into the snapshot once it holds
+/// `compact_every` entries.
+///
+/// The entries are numbered, and the snapshot records the last one it holds,
+/// so that a crash between the snapshot and the truncation of the journal
+/// does not replay them twice.
+#[derive(Debug)]
+pub struct FileRepository {
+    dir: PathBuf,
+    durability: Durability,
+    compact_every: usize,
+    memory: InMemoryRepository,
+    journal: Mutex<Journal>,
+}
+
+impl FileRepository {
+    /// Number of journal entries after which it is compacted by default.
+    pub const DEFAULT_COMPACT_EVERY: usize = 1000;
+
+    /// Opens the [`FileRepository`](Self) stored in `dir`, creating it if
+    /// needed.
+    ///
+    /// The journal is replayed then compacted. A truncated last entry, left by
+    /// a crash during a write, is dropped.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the files cannot be read or written, or if they
+    /// are corrupted.
+    pub fn open(dir: &Path, durability: Durability, compact_every: usize) -> Result<Self, String> {
+        if let Err(err) = fs::create_dir_all(dir) {
+            return Err(format!("Cannot create `{}`: {}", dir.display(), err));
+        }
+
+        let memory = InMemoryRepository::new();
+
+        let snapshot_path = dir.join(SNAPSHOT_FILE_NAME);
+        let mut seq = 0;
+        if snapshot_path.exists() {
+            let snapshot: Snapshot = fs::read(&snapshot_path)
+                .map_err(|err| err.to_string())
+                .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|err| err.to_string()))
+                .map_err(|err| format!("Cannot read `{}`: {}", snapshot_path.display(), err))?;
+            seq = snapshot.seq;
+            memory
+                .insert_batch(snapshot.promocodes)
+                .map_err(|err| err.to_string())?;
+        }
+
+        let journal_path = dir.join(JOURNAL_FILE_NAME);
+        if journal_path.exists() {
+            seq = replay(&journal_path, &memory, seq)?;
+        }
+
+        // Kept as is until the compaction below wrote the new snapshot.
+        let journal = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&journal_path)
+            .map_err(|err| format!("Cannot write `{}`: {}", journal_path.display(), err))?;
+        let journal = Journal {
+            writer: BufWriter::new(journal),
+            len: 0,
+            seq,
+        };
+
+        let repository = Self {
+            dir: dir.to_path_buf(),
+            durability,
+            compact_every: compact_every.max(1),
+            memory,
+            journal: Mutex::new(journal),
+        };
+        repository.compact(&mut repository.journal.lock().unwrap())?;
+
+        Ok(repository)
+    }
+
+    /// Writes a new snapshot then empties the journal.
+    fn compact(&self, journal: &mut Journal) -> Result<(), String> {
+        // Otherwise, the buffered entries would end up in the emptied journal.
+        if let Err(err) = journal.writer.flush() {
+            return Err(format!("Cannot write the journal: {}", err));
+        }
+
+        let snapshot_path = self.dir.join(SNAPSHOT_FILE_NAME);
+        let tmp_path = self.dir.join(format!("{}.tmp", SNAPSHOT_FILE_NAME));
+
+        let promocodes = self
+            .memory
+            .list(&PromocodeFilter::default())
+            .map_err(|err| err.to_string())?;
+        let snapshot = Snapshot {
+            seq: journal.seq,
+            promocodes,
+        };
+        let snapshot = serde_json::to_vec(&snapshot).map_err(|err| err.to_string())?;
+
+        create_file(&tmp_path)
+            .and_then(|mut file| {
+                file.write_all(&snapshot)
+                    .and_then(|_| file.sync_all())
+                    .map_err(|err| format!("Cannot write `{}`: {}", tmp_path.display(), err))
+            })
+            .and_then(|_| fs::rename(&tmp_path, &snapshot_path).map_err(|err| format!("Cannot write `{}`: {}", snapshot_path.display(), err)))?;
+
+        // Makes the rename durable before the journal is emptied.
+        if let Ok(dir) = File::open(&self.dir) {
+            let _ = dir.sync_all();
+        }
+
+        journal.writer = BufWriter::new(create_file(&self.dir.join(JOURNAL_FILE_NAME))?);
+        journal.len = 0;
+        Ok(())
+    }
+
+    /// Appends `entry` to the journal, according to the [Durability].
+    fn append(&self, journal: &mut Journal, entry: JournalEntry) -> Result<JournalEntry, String> {
+        let line = JournalLine {
+            seq: journal.seq + 1,
+            entry,
+        };
+        let mut bytes = serde_json::to_vec(&line).map_err(|err| err.to_string())?;
+        bytes.push(b'\n');
+        let line = line.entry;
+
+        let written = match self.durability {
+            Durability::Buffered => journal.writer.write_all(&bytes),
+            Durability::Flush => journal
+                .writer
+                .write_all(&bytes)
+                .and_then(|_| journal.writer.flush()),
+            Durability::Fsync => journal
+                .writer
+                .write_all(&bytes)
+                .and_then(|_| journal.writer.flush())
+                .and_then(|_| journal.writer.get_ref().sync_data()),
+        };
+        if let Err(err) = written {
+            return Err(format!("Cannot write the journal: {}", err));
+        }
+
+        journal.len += 1;
+        journal.seq += 1;
+        Ok(line)
+    }
+
+    /// Deletes `promocode`, which must be in the repository.
+    fn delete(&self, journal: &mut Journal, promocode: Promocode) -> Result<Option<Promocode>, RepositoryError> {
+        self.append(
+            journal,
+            JournalEntry::Delete {
+                id: promocode._id(),
+            },
+        )?;
+        let deleted = self.memory.delete_by_id(&promocode._id(), None)?;
+
+        self.compact_if_needed(journal);
+        Ok(deleted)
+    }
+
+    /// Compacts the journal if it holds too many entries.
+    ///
+    /// The write is already durable, so a failed compaction is only logged.
+    fn compact_if_needed(&self, journal: &mut Journal) {
+        if journal.len >= self.compact_every {
+            if let Err(err) = self.compact(journal) {
+                warn!("{}", err);
+            }
+        }
+    }
+}
+
+impl PromocodeRepository for FileRepository {
+    fn get_by_id(&self, id: &str) -> Result<Option<Promocode>, RepositoryError> {
+        self.memory.get_by_id(id)
+    }
+
+    fn get_by_name(&self, name: &str) -> Result<Option<Promocode>, RepositoryError> {
+        self.memory.get_by_name(name)
+    }
+
+    fn list(&self, filter: &PromocodeFilter) -> Result<Vec<Promocode>, RepositoryError> {
+        self.memory.list(filter)
+    }
+
+    fn list_page(&self, filter: &PromocodeFilter, after: Option<&str>, limit: usize) -> Result<Vec<Promocode>, RepositoryError> {
+        self.memory.list_page(filter, after, limit)
+    }
+
+    fn insert_batch(&self, promocodes: Vec<Promocode>) -> Result<(), RepositoryError> {
+        let mut journal = self.journal.lock().unwrap();
+
+        // Writers are serialized by the journal lock, so the checks still hold
+        // when the in-memory state is updated.
+        check_conflicts(&promocodes, |field, value| match field {
+            UniqueField::Id => Ok(self.memory.get_by_id(value)?.is_some()),
+            UniqueField::Name => Ok(self.memory.get_by_name(value)?.is_some()),
+        })?;
+
+        let entry = self.append(&mut journal, JournalEntry::Insert { promocodes })?;
+        if let JournalEntry::Insert { promocodes } = entry {
+            self.memory.insert_batch(promocodes)?;
+        }
+
+        self.compact_if_needed(&mut journal);
+        Ok(())
+    }
+
+    fn update(&self, promocode: Promocode) -> Result<(), RepositoryError> {
+        let mut journal = self.journal.lock().unwrap();
+
+        match self.memory.get_by_id(&promocode._id())? {
+            None => {
+                return Err(RepositoryError::NotFound {
+                    id: promocode._id(),
+                })
+            },
+            Some(stored) => check_version(&stored, &promocode)?,
+        }
+        if let Some(it) = self.memory.get_by_name(&promocode.name())? {
+            if it._id() != promocode._id() {
+                return Err(RepositoryError::Conflict {
+                    field: UniqueField::Name,
+                    value: promocode.name(),
+                });
+            }
+        }
+
+        let entry = self.append(&mut journal, JournalEntry::Update { promocode })?;
+        if let JournalEntry::Update { promocode } = entry {
+            self.memory.update(promocode)?;
+        }
+
+        self.compact_if_needed(&mut journal);
+        Ok(())
+    }
+
+    fn delete_by_id(&self, id: &str, expected_version: Option<u64>) -> Result<Option<Promocode>, RepositoryError> {
+        let mut journal = self.journal.lock().unwrap();
+
+        match self.memory.get_by_id(id)? {
+            None => Ok(None),
+            Some(promocode) => {
+                check_expected_version(&promocode, expected_version)?;
+                self.delete(&mut journal, promocode)
+            },
+        }
+    }
+
+    fn delete_by_name(&self, name: &str) -> Result<Option<Promocode>, RepositoryError> {
+        let mut journal = self.journal.lock().unwrap();
+
+        match self.memory.get_by_name(name)? {
+            None => Ok(None),
+            Some(promocode) => self.delete(&mut journal, promocode),
+        }
+    }
+}
+
+impl Drop for FileRepository {
+    fn drop(&mut self) {
+        if let Ok(journal) = self.journal.get_mut() {
+            if let Err(err) = journal
+                .writer
+                .flush()
+                .and_then(|_| journal.writer.get_ref().sync_data())
+            {
+                warn!("Cannot write the journal: {}", err);
+            }
+        }
+    }
+}
+
+/// Applies the entries of the journal at `path` after the entry `seq`, the
+/// last one in the snapshot, to `memory`.
+///
+/// Returns the number of the last entry.
+fn replay(path: &Path, memory: &InMemoryRepository, mut seq: u64) -> Result<u64, String> {
+    let file = File::open(path).map_err(|err| format!("Cannot read `{}`: {}", path.display(), err))?;
+    let lines = BufReader::new(file)
+        .lines()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("Cannot read `{}`: {}", path.display(), err))?;
+
+    for (index, line) in lines.iter().enumerate() {
+        let entry = match serde_json::from_str::<JournalLine>(line) {
+            Ok(JournalLine { seq: line_seq, .. }) if line_seq <= seq => continue,
+            Ok(JournalLine {
+                seq: line_seq,
+                entry,
+            }) => {
+                seq = line_seq;
+                entry
+            },
+            Err(err) if index + 1 == lines.len() => {
+                warn!("Last entry of `{}` dropped: {}", path.display(), err);
+                break;
+            },
+            Err(err) => return Err(format!("`{}` line {}: {}", path.display(), index + 1, err)),
+        };
+
+        let applied = match entry {
+            JournalEntry::Insert { promocodes } => memory.insert_batch(promocodes),
+            JournalEntry::Update { promocode } => memory.update(promocode),
+            JournalEntry::Delete { id } => memory.delete_by_id(&id, None).map(|_| ()),
+        };
+        if let Err(err) = applied {
+            return Err(format!("`{}` line {}: {}", path.display(), index + 1, err));
+        }
+    }
+
+    Ok(seq)
+}
+
+/// Creates or truncates the file at `path`.
+fn create_file(path: &Path) -> Result<File, String> {
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+        .map_err(|err| format!("Cannot write `{}`: {}", path.display(), err))
+}
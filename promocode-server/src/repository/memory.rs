@@ -0,0 +1,182 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    ops::Bound,
+    sync::{Arc, Mutex},
+};
+
+use arc_swap::ArcSwap;
+use indexmap::IndexMap;
+use promocode_models::promocode::Promocode;
+
+use super::{check_conflicts, check_expected_version, check_version, PromocodeFilter, PromocodeRepository, RepositoryError, UniqueField};
+
+/// Immutable state of an [InMemoryRepository].
+#[derive(Clone, Default, Debug)]
+struct Snapshot {
+    /// [Promocode]s by id, in insertion order.
+    by_id: IndexMap<String, Arc<Promocode>>,
+    /// Ids by name.
+    by_name: HashMap<String, String>,
+    /// Ids, in order.
+    ids: BTreeSet<String>,
+}
+
+impl Snapshot {
+    fn insert(&mut self, promocode: Promocode) {
+        self.by_name.insert(promocode.name(), promocode._id());
+        self.ids.insert(promocode._id());
+        self.by_id.insert(promocode._id(), Arc::new(promocode));
+    }
+
+    fn remove(&mut self, id: &str) -> Option<Promocode> {
+        let promocode = self.by_id.shift_remove(id)?;
+        self.by_name.remove(&promocode.name());
+        self.ids.remove(id);
+        Some(Arc::unwrap_or_clone(promocode))
+    }
+}
+
+/// [PromocodeRepository] keeping the [Promocode]s in memory.
+///
+/// The [Promocode]s are indexed by id and by name. Readers work on an
+/// immutable snapshot and never wait for writers: a write copies the indexes
+/// (not the [Promocode]s), updates the copy, then publishes it.
+///
+/// # WARN: for dev only, everything is lost when the server stops !
+#[derive(Default, Debug)]
+pub struct InMemoryRepository {
+    snapshot: ArcSwap<Snapshot>,
+    /// Serializes the writers, so that none of them is lost.
+    writer: Mutex<()>,
+}
+
+impl InMemoryRepository {
+    /// Create a new empty [`InMemoryRepository`](Self)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies `write` to a copy of the current snapshot, and publishes it if
+    /// `write` succeeds.
+    fn write<T>(&self, write: impl FnOnce(&mut Snapshot) -> Result<T, RepositoryError>) -> Result<T, RepositoryError> {
+        let _writer = self.writer.lock().unwrap();
+
+        let mut snapshot = Snapshot::clone(&self.snapshot.load());
+        let result = write(&mut snapshot)?;
+        self.snapshot.store(Arc::new(snapshot));
+
+        Ok(result)
+    }
+}
+
+impl PromocodeRepository for InMemoryRepository {
+    fn get_by_id(&self, id: &str) -> Result<Option<Promocode>, RepositoryError> {
+        Ok(self
+            .snapshot
+            .load()
+            .by_id
+            .get(id)
+            .map(|promocode| Promocode::clone(promocode)))
+    }
+
+    fn get_by_name(&self, name: &str) -> Result<Option<Promocode>, RepositoryError> {
+        let snapshot = self.snapshot.load();
+
+        Ok(snapshot
+            .by_name
+            .get(name)
+            .and_then(|id| snapshot.by_id.get(id))
+            .map(|promocode| Promocode::clone(promocode)))
+    }
+
+    fn list(&self, filter: &PromocodeFilter) -> Result<Vec<Promocode>, RepositoryError> {
+        Ok(self
+            .snapshot
+            .load()
+            .by_id
+            .values()
+            .filter(|promocode| filter.matches(promocode))
+            .map(|promocode| Promocode::clone(promocode))
+            .collect())
+    }
+
+    fn list_page(&self, filter: &PromocodeFilter, after: Option<&str>, limit: usize) -> Result<Vec<Promocode>, RepositoryError> {
+        let snapshot = self.snapshot.load();
+        let start = after.map_or(Bound::Unbounded, Bound::Excluded);
+
+        Ok(snapshot
+            .ids
+            .range::<str, _>((start, Bound::Unbounded))
+            .filter_map(|id| snapshot.by_id.get(id))
+            .filter(|promocode| filter.matches(promocode))
+            .take(limit)
+            .map(|promocode| Promocode::clone(promocode))
+            .collect())
+    }
+
+    fn insert_batch(&self, promocodes: Vec<Promocode>) -> Result<(), RepositoryError> {
+        self.write(|snapshot| {
+            check_conflicts(&promocodes, |field, value| match field {
+                UniqueField::Id => Ok(snapshot.by_id.contains_key(value)),
+                UniqueField::Name => Ok(snapshot.by_name.contains_key(value)),
+            })?;
+
+            for promocode in promocodes {
+                snapshot.insert(promocode);
+            }
+            Ok(())
+        })
+    }
+
+    fn update(&self, promocode: Promocode) -> Result<(), RepositoryError> {
+        self.write(|snapshot| {
+            let (id, name) = (promocode._id(), promocode.name());
+            match snapshot.by_id.get(&id) {
+                None => return Err(RepositoryError::NotFound { id }),
+                Some(stored) => check_version(stored, &promocode)?,
+            }
+
+            if snapshot.by_name.get(&name).is_some_and(|it| *it != id) {
+                return Err(RepositoryError::Conflict {
+                    field: UniqueField::Name,
+                    value: name,
+                });
+            }
+
+            // Replaced in place, to keep the insertion order.
+            if let Some(it) = snapshot.by_id.get_mut(&id) {
+                let previous = std::mem::replace(it, Arc::new(promocode));
+                snapshot.by_name.remove(&previous.name());
+                snapshot.by_name.insert(name, id);
+            }
+            Ok(())
+        })
+    }
+
+    fn delete_by_id(&self, id: &str, expected_version: Option<u64>) -> Result<Option<Promocode>, RepositoryError> {
+        if !self.snapshot.load().by_id.contains_key(id) {
+            return Ok(None);
+        }
+
+        self.write(|snapshot| {
+            match snapshot.by_id.get(id) {
+                None => return Ok(None),
+                Some(stored) => check_expected_version(stored, expected_version)?,
+            }
+            Ok(snapshot.remove(id))
+        })
+    }
+
+    fn delete_by_name(&self, name: &str) -> Result<Option<Promocode>, RepositoryError> {
+        if !self.snapshot.load().by_name.contains_key(name) {
+            return Ok(None);
+        }
+
+        self.write(|snapshot| {
+            Ok(match snapshot.by_name.get(name).cloned() {
+                None => None,
+                Some(id) => snapshot.remove(&id),
+            })
+        })
+    }
+}
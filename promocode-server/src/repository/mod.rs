@@ -0,0 +1,258 @@
+//! Storage of the [Promocode]s.
+//!
+//! The routes only depend on the [PromocodeRepository] trait, shared through
+//! the ntex application state as a [Repository].
+
+use std::{
+    collections::HashSet,
+    fmt::{Display, Formatter},
+    sync::Arc,
+};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use promocode_models::promocode::{restriction::RestrictionKind, status::PromocodeStatus, Promocode};
+use serde::Serialize;
+
+pub mod file;
+pub mod memory;
+pub mod sqlite;
+
+/// [PromocodeRepository] shared by the application.
+pub type Repository = Arc<dyn PromocodeRepository>;
+
+/// Field of a [Promocode] that must be unique in a [PromocodeRepository].
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UniqueField {
+    #[serde(rename = "_id")]
+    Id,
+    #[serde(rename = "name")]
+    Name,
+}
+
+impl Display for UniqueField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UniqueField::Id => write!(f, "id"),
+            UniqueField::Name => write!(f, "name"),
+        }
+    }
+}
+
+/// Error returned by a [PromocodeRepository].
+#[derive(Clone, PartialEq, Debug)]
+pub enum RepositoryError {
+    /// Another [Promocode] already has this `value` for this `field`.
+    Conflict { field: UniqueField, value: String },
+    /// There is no [Promocode] with this id.
+    NotFound { id: String },
+    /// The stored [Promocode] with this id is not the one the update is based
+    /// on: it is at `version`.
+    VersionMismatch { id: String, version: u64 },
+    /// The underlying storage failed.
+    Storage(String),
+}
+
+impl Display for RepositoryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RepositoryError::Conflict { field, value } => write!(f, "Promocode with {} `{}` already exist.", field, value),
+            RepositoryError::NotFound { id } => write!(f, "Promocode with id `{}` does not exist.", id),
+            RepositoryError::VersionMismatch { id, version } => write!(
+                f,
+                "Promocode with id `{}` was modified meanwhile, it is at version {}.",
+                id, version
+            ),
+            RepositoryError::Storage(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for RepositoryError {}
+
+impl From<String> for RepositoryError {
+    fn from(err: String) -> Self {
+        RepositoryError::Storage(err)
+    }
+}
+
+/// Filters applied by [PromocodeRepository::list] and
+/// [PromocodeRepository::list_page].
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct PromocodeFilter {
+    /// Only the promocodes having all of these tags are returned.
+    pub tags: Vec<String>,
+    /// Only the promocodes generated for this campaign are returned.
+    pub campaign: Option<String>,
+    /// Only the promocodes whose name starts with this prefix, ignoring case,
+    /// are returned.
+    pub name_prefix: Option<String>,
+    /// Only the promocodes having a restriction of this kind, at any depth,
+    /// are returned.
+    pub restriction_kind: Option<RestrictionKind>,
+    /// Only the promocodes having this status today are returned.
+    pub status: Option<PromocodeStatus>,
+    /// Only the promocodes whose date restrictions accept this day are
+    /// returned.
+    pub valid_on: Option<NaiveDate>,
+}
+
+impl PromocodeFilter {
+    /// Returns `true` if `promocode` passes this filter.
+    pub fn matches(&self, promocode: &Promocode) -> bool {
+        promocode.metadata.has_tags(&self.tags)
+            && (self.campaign.is_none() || promocode.metadata.campaign() == self.campaign)
+            && self.name_prefix.as_ref().is_none_or(|prefix| {
+                promocode
+                    .name()
+                    .to_lowercase()
+                    .starts_with(&prefix.to_lowercase())
+            })
+            && self
+                .restriction_kind
+                .is_none_or(|kind| promocode.has_restriction_kind(kind))
+            && self
+                .status
+                .is_none_or(|status| promocode.status(Utc::now().date_naive()) == status)
+            && self.valid_on.is_none_or(|day| promocode.accepts_date(day))
+    }
+}
+
+/// Store of [Promocode]s, unique by id and by name.
+///
+/// Names are stored as given: normalizing them with the
+/// [NamePolicy](promocode_models::promocode::name_policy::NamePolicy) is up
+/// to the caller.
+///
+/// Every method returns a [RepositoryError::Storage] if the underlying storage
+/// fails.
+pub trait PromocodeRepository: Send + Sync {
+    /// Retrieves a [Promocode] by its id.
+    fn get_by_id(&self, id: &str) -> Result<Option<Promocode>, RepositoryError>;
+
+    /// Retrieves a [Promocode] by its name.
+    fn get_by_name(&self, name: &str) -> Result<Option<Promocode>, RepositoryError>;
+
+    /// Retrieves the [Promocode]s passing `filter`, in insertion order.
+    fn list(&self, filter: &PromocodeFilter) -> Result<Vec<Promocode>, RepositoryError>;
+
+    /// Retrieves at most `limit` [Promocode]s passing `filter`, in id order,
+    /// after the id `after`, if any.
+    ///
+    /// Passing the id of the last [Promocode] of a page as `after` fetches
+    /// the next one, so that the whole store is gone through without loading
+    /// it at once.
+    fn list_page(&self, filter: &PromocodeFilter, after: Option<&str>, limit: usize) -> Result<Vec<Promocode>, RepositoryError>;
+
+    /// Inserts a new [Promocode].
+    ///
+    /// # Errors
+    ///
+    /// This function fails with a [RepositoryError::Conflict] if a [Promocode]
+    /// with the same id or name already exists. The check and the insertion
+    /// are atomic.
+    fn insert(&self, promocode: Promocode) -> Result<(), RepositoryError> {
+        self.insert_batch(vec![promocode])
+    }
+
+    /// Inserts a batch of new [Promocode]s.
+    ///
+    /// # Errors
+    ///
+    /// Either every [Promocode] of the batch is inserted, or none of them if
+    /// one of them has the same id or name as another one, in the batch or in
+    /// the repository ([RepositoryError::Conflict]).
+    fn insert_batch(&self, promocodes: Vec<Promocode>) -> Result<(), RepositoryError>;
+
+    /// Replaces the [Promocode] having the same id as `promocode`.
+    ///
+    /// The version of `promocode` must follow the stored one, as set by
+    /// [Metadata::mark_updated](promocode_models::promocode::metadata::Metadata::mark_updated):
+    /// an update based on an outdated [Promocode] is rejected instead of
+    /// overwriting a concurrent one.
+    ///
+    /// # Errors
+    ///
+    /// This function fails with a [RepositoryError::NotFound] if there is no
+    /// [Promocode] with this id, with a [RepositoryError::VersionMismatch] if
+    /// the version of `promocode` does not follow the stored one, or with a
+    /// [RepositoryError::Conflict] if another [Promocode] already has its
+    /// name. The checks and the update are atomic.
+    fn update(&self, promocode: Promocode) -> Result<(), RepositoryError>;
+
+    /// Deletes the [Promocode] with the given id, and returns it.
+    ///
+    /// If `expected_version` is set, the [Promocode] is only deleted if it is
+    /// still at this version, so that a delete based on an outdated
+    /// [Promocode] does not remove a concurrent update.
+    ///
+    /// # Errors
+    ///
+    /// This function fails with a [RepositoryError::VersionMismatch] if the
+    /// stored [Promocode] is not at `expected_version`. The check and the
+    /// deletion are atomic.
+    fn delete_by_id(&self, id: &str, expected_version: Option<u64>) -> Result<Option<Promocode>, RepositoryError>;
+
+    /// Deletes the [Promocode] with the given name, and returns it.
+    fn delete_by_name(&self, name: &str) -> Result<Option<Promocode>, RepositoryError>;
+
+    /// Records a redemption, at `at`, of the [Promocode] with the given id:
+    /// a validation it accepted.
+    ///
+    /// Only the [SqliteRepository](sqlite::SqliteRepository) keeps the
+    /// redemptions, the other repositories ignore them.
+    fn record_redemption(&self, _id: &str, _at: DateTime<Utc>) -> Result<(), RepositoryError> {
+        Ok(())
+    }
+}
+
+/// Returns a [RepositoryError::Conflict] if one of `promocodes` has the same
+/// id or name as another one in `promocodes`, or if `is_taken` returns `true`
+/// for its id or name.
+pub(crate) fn check_conflicts(promocodes: &[Promocode], is_taken: impl Fn(UniqueField, &str) -> Result<bool, RepositoryError>) -> Result<(), RepositoryError> {
+    let mut ids = HashSet::with_capacity(promocodes.len());
+    let mut names = HashSet::with_capacity(promocodes.len());
+
+    for promocode in promocodes {
+        let (id, name) = (promocode._id(), promocode.name());
+
+        if is_taken(UniqueField::Id, &id)? || !ids.insert(id.clone()) {
+            return Err(RepositoryError::Conflict {
+                field: UniqueField::Id,
+                value: id,
+            });
+        }
+        if is_taken(UniqueField::Name, &name)? || !names.insert(name.clone()) {
+            return Err(RepositoryError::Conflict {
+                field: UniqueField::Name,
+                value: name,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns a [RepositoryError::VersionMismatch] if the version of `promocode`
+/// does not follow the one of `stored`.
+pub(crate) fn check_version(stored: &Promocode, promocode: &Promocode) -> Result<(), RepositoryError> {
+    if promocode.metadata.version() != stored.metadata.version() + 1 {
+        return Err(RepositoryError::VersionMismatch {
+            id: stored._id(),
+            version: stored.metadata.version(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Returns a [RepositoryError::VersionMismatch] if `stored` is not at
+/// `expected_version`, when there is one.
+pub(crate) fn check_expected_version(stored: &Promocode, expected_version: Option<u64>) -> Result<(), RepositoryError> {
+    match expected_version {
+        Some(version) if version != stored.metadata.version() => Err(RepositoryError::VersionMismatch {
+            id: stored._id(),
+            version: stored.metadata.version(),
+        }),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,27 @@
+CREATE TABLE promocodes (
+    seq          INTEGER PRIMARY KEY AUTOINCREMENT,
+    id           TEXT    NOT NULL UNIQUE,
+    name         TEXT    NOT NULL UNIQUE,
+    percent      INTEGER NOT NULL,
+    -- `Restrictions` tree, as JSON.
+    restrictions TEXT    NOT NULL,
+    -- `Metadata`, as JSON.
+    metadata     TEXT    NOT NULL
+);
+
+CREATE TABLE redemptions (
+    seq          INTEGER PRIMARY KEY AUTOINCREMENT,
+    promocode_id TEXT    NOT NULL,
+    redeemed_at  TEXT    NOT NULL
+);
+
+CREATE INDEX redemptions_promocode_id ON redemptions (promocode_id);
+
+CREATE TABLE audit (
+    seq          INTEGER PRIMARY KEY AUTOINCREMENT,
+    at           TEXT    NOT NULL,
+    op           TEXT    NOT NULL,
+    promocode_id TEXT    NOT NULL,
+    -- The written `Promocode`, as JSON, if any.
+    payload      TEXT
+);
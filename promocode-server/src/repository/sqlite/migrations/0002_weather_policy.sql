@@ -0,0 +1,2 @@
+-- `WeatherPolicy` of the promocodes, as serialized.
+ALTER TABLE promocodes ADD COLUMN weather_policy TEXT NOT NULL DEFAULT 'fail_closed';
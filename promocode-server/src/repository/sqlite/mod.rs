@@ -0,0 +1,420 @@
+use std::{path::Path, sync::Mutex};
+
+use chrono::{DateTime, Utc};
+use promocode_models::promocode::{avantage::Avantage, metadata::Metadata, restrictions::Restrictions, weather_policy::WeatherPolicy, Promocode};
+use rusqlite::{params, Connection, ErrorCode, OptionalExtension, Transaction};
+use serde_json::Value;
+
+use super::{check_expected_version, check_version, PromocodeFilter, PromocodeRepository, RepositoryError, UniqueField};
+
+/// Schema migrations, applied in order on [open](SqliteRepository::open).
+///
+/// The number of applied migrations is stored in the `user_version` of the
+/// database. Never edit a released migration, add a new one instead.
+const MIGRATIONS: &[&str] = &[
+    include_str!("migrations/0001_init.sql"),
+    include_str!("migrations/0002_weather_policy.sql"),
+];
+
+const SELECT_PROMOCODES: &str = "SELECT id, name, percent, restrictions, metadata, weather_policy FROM promocodes";
+
+/// An entry of the audit trail of a [SqliteRepository].
+#[derive(Clone, PartialEq, Debug)]
+pub struct AuditEntry {
+    pub at: DateTime<Utc>,
+    /// `insert`, `update` or `delete`.
+    pub op: String,
+    pub promocode_id: String,
+}
+
+/// [PromocodeRepository] stored in a SQLite database.
+///
+/// Besides the [Promocode]s, whose [Restrictions] and [Metadata] are stored
+/// as JSON, the database holds their redemptions and an audit trail of every
+/// write.
+#[derive(Debug)]
+pub struct SqliteRepository {
+    connection: Mutex<Connection>,
+}
+
+impl SqliteRepository {
+    /// Opens the [`SqliteRepository`](Self) stored at `path`, creating it if
+    /// needed, and migrates its schema.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the database cannot be opened or migrated.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        match Connection::open(path) {
+            Err(err) => Err(format!("Cannot open `{}`: {}", path.display(), err)),
+            Ok(connection) => Self::from_connection(connection),
+        }
+    }
+
+    /// Opens a new [`SqliteRepository`](Self) in memory.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the database cannot be created.
+    pub fn open_in_memory() -> Result<Self, String> {
+        Connection::open_in_memory()
+            .map_err(sql_error)
+            .and_then(Self::from_connection)
+    }
+
+    fn from_connection(mut connection: Connection) -> Result<Self, String> {
+        migrate(&mut connection)?;
+
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    /// Returns the number of migrations applied to the database.
+    pub fn schema_version(&self) -> Result<usize, String> {
+        schema_version(&self.connection.lock().unwrap())
+    }
+
+    /// Returns the number of redemptions of the [Promocode] with the given id,
+    /// as recorded by [PromocodeRepository::record_redemption].
+    pub fn redemption_count(&self, promocode_id: &str) -> Result<usize, String> {
+        self.connection
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT COUNT(*) FROM redemptions WHERE promocode_id = ?1",
+                params![promocode_id],
+                |row| row.get(0),
+            )
+            .map_err(sql_error)
+    }
+
+    /// Returns the audit trail, oldest first.
+    pub fn audit(&self) -> Result<Vec<AuditEntry>, String> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection
+            .prepare("SELECT at, op, promocode_id FROM audit ORDER BY seq")
+            .map_err(sql_error)?;
+
+        let rows = statement
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get(1)?, row.get(2)?))
+            })
+            .map_err(sql_error)?;
+
+        rows.map(|row| {
+            let (at, op, promocode_id) = row.map_err(sql_error)?;
+            match DateTime::parse_from_rfc3339(&at) {
+                Err(err) => Err(format!("Cannot parse audit date `{}`: {}", at, err)),
+                Ok(at) => Ok(AuditEntry {
+                    at: at.with_timezone(&Utc),
+                    op,
+                    promocode_id,
+                }),
+            }
+        })
+        .collect()
+    }
+
+    /// Deletes the [Promocode] matching `clause`, if it is at `expected_version`
+    /// when there is one, and returns it.
+    fn delete(&self, clause: &str, value: &str, expected_version: Option<u64>) -> Result<Option<Promocode>, RepositoryError> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction().map_err(sql_error)?;
+
+        let promocode = match select_one(&transaction, clause, value)? {
+            None => return Ok(None),
+            Some(promocode) => promocode,
+        };
+        check_expected_version(&promocode, expected_version)?;
+
+        transaction
+            .execute(
+                "DELETE FROM promocodes WHERE id = ?1",
+                params![promocode._id()],
+            )
+            .map_err(sql_error)?;
+        audit(&transaction, Utc::now(), "delete", &promocode)?;
+        transaction.commit().map_err(sql_error)?;
+
+        Ok(Some(promocode))
+    }
+}
+
+impl PromocodeRepository for SqliteRepository {
+    fn get_by_id(&self, id: &str) -> Result<Option<Promocode>, RepositoryError> {
+        Ok(select_one(
+            &self.connection.lock().unwrap(),
+            "WHERE id = ?1",
+            id,
+        )?)
+    }
+
+    fn get_by_name(&self, name: &str) -> Result<Option<Promocode>, RepositoryError> {
+        Ok(select_one(
+            &self.connection.lock().unwrap(),
+            "WHERE name = ?1",
+            name,
+        )?)
+    }
+
+    fn list(&self, filter: &PromocodeFilter) -> Result<Vec<Promocode>, RepositoryError> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection
+            .prepare(&format!("{} ORDER BY seq", SELECT_PROMOCODES))
+            .map_err(sql_error)?;
+
+        let rows = statement.query_map([], read_row).map_err(sql_error)?;
+
+        let mut promocodes = vec![];
+        for row in rows {
+            let promocode = to_promocode(row.map_err(sql_error)?)?;
+            if filter.matches(&promocode) {
+                promocodes.push(promocode);
+            }
+        }
+
+        Ok(promocodes)
+    }
+
+    fn list_page(&self, filter: &PromocodeFilter, after: Option<&str>, limit: usize) -> Result<Vec<Promocode>, RepositoryError> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection
+            .prepare(&format!(
+                "{} WHERE ?1 IS NULL OR id > ?1 ORDER BY id",
+                SELECT_PROMOCODES
+            ))
+            .map_err(sql_error)?;
+
+        // The rows are read as needed, until the page is full.
+        let rows = statement
+            .query_map(params![after], read_row)
+            .map_err(sql_error)?;
+
+        let mut promocodes = vec![];
+        for row in rows {
+            if promocodes.len() == limit {
+                break;
+            }
+            let promocode = to_promocode(row.map_err(sql_error)?)?;
+            if filter.matches(&promocode) {
+                promocodes.push(promocode);
+            }
+        }
+
+        Ok(promocodes)
+    }
+
+    fn insert_batch(&self, promocodes: Vec<Promocode>) -> Result<(), RepositoryError> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction().map_err(sql_error)?;
+        let now = Utc::now();
+
+        for promocode in &promocodes {
+            let (restrictions, metadata, weather_policy) = to_json(promocode)?;
+            let inserted = transaction.execute(
+                "INSERT INTO promocodes (id, name, percent, restrictions, metadata, weather_policy) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    promocode._id(),
+                    promocode.name(),
+                    promocode.avantage.percent.get(),
+                    restrictions,
+                    metadata,
+                    weather_policy
+                ],
+            );
+
+            match inserted {
+                Err(err) if is_constraint_violation(&err) => {
+                    return Err(
+                        match select_one(&transaction, "WHERE id = ?1", &promocode._id())? {
+                            Some(_) => RepositoryError::Conflict {
+                                field: UniqueField::Id,
+                                value: promocode._id(),
+                            },
+                            None => RepositoryError::Conflict {
+                                field: UniqueField::Name,
+                                value: promocode.name(),
+                            },
+                        },
+                    );
+                },
+                Err(err) => return Err(sql_error(err).into()),
+                Ok(_) => audit(&transaction, now, "insert", promocode)?,
+            }
+        }
+
+        Ok(transaction.commit().map_err(sql_error)?)
+    }
+
+    fn update(&self, promocode: Promocode) -> Result<(), RepositoryError> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction().map_err(sql_error)?;
+
+        match select_one(&transaction, "WHERE id = ?1", &promocode._id())? {
+            None => {
+                return Err(RepositoryError::NotFound {
+                    id: promocode._id(),
+                })
+            },
+            Some(stored) => check_version(&stored, &promocode)?,
+        }
+
+        let (restrictions, metadata, weather_policy) = to_json(&promocode)?;
+        let updated = transaction.execute(
+            "UPDATE promocodes SET name = ?2, percent = ?3, restrictions = ?4, metadata = ?5, weather_policy = ?6 WHERE id = ?1",
+            params![
+                promocode._id(),
+                promocode.name(),
+                promocode.avantage.percent.get(),
+                restrictions,
+                metadata,
+                weather_policy
+            ],
+        );
+
+        match updated {
+            Err(err) if is_constraint_violation(&err) => Err(RepositoryError::Conflict {
+                field: UniqueField::Name,
+                value: promocode.name(),
+            }),
+            Err(err) => Err(sql_error(err).into()),
+            Ok(0) => Err(RepositoryError::NotFound {
+                id: promocode._id(),
+            }),
+            Ok(_) => {
+                audit(&transaction, Utc::now(), "update", &promocode)?;
+                Ok(transaction.commit().map_err(sql_error)?)
+            },
+        }
+    }
+
+    fn delete_by_id(&self, id: &str, expected_version: Option<u64>) -> Result<Option<Promocode>, RepositoryError> {
+        self.delete("WHERE id = ?1", id, expected_version)
+    }
+
+    fn delete_by_name(&self, name: &str) -> Result<Option<Promocode>, RepositoryError> {
+        self.delete("WHERE name = ?1", name, None)
+    }
+
+    fn record_redemption(&self, id: &str, at: DateTime<Utc>) -> Result<(), RepositoryError> {
+        Ok(self
+            .connection
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO redemptions (promocode_id, redeemed_at) VALUES (?1, ?2)",
+                params![id, at.to_rfc3339()],
+            )
+            .map(|_| ())
+            .map_err(sql_error)?)
+    }
+}
+
+/// Applies the [MIGRATIONS] not applied yet, each one in its own transaction.
+fn migrate(connection: &mut Connection) -> Result<(), String> {
+    let version = schema_version(connection)?;
+    if version > MIGRATIONS.len() {
+        return Err(format!(
+            "Database schema version {} is newer than the supported one ({}).",
+            version,
+            MIGRATIONS.len()
+        ));
+    }
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let transaction = connection.transaction().map_err(sql_error)?;
+        transaction
+            .execute_batch(migration)
+            .and_then(|_| transaction.pragma_update(None, "user_version", (index + 1) as i64))
+            .map_err(|err| format!("Migration {} failed: {}", index + 1, err))?;
+        transaction.commit().map_err(sql_error)?;
+    }
+
+    Ok(())
+}
+
+fn schema_version(connection: &Connection) -> Result<usize, String> {
+    connection
+        .pragma_query_value(None, "user_version", |row| row.get::<_, i64>(0))
+        .map(|version| version as usize)
+        .map_err(sql_error)
+}
+
+/// Selects the [Promocode] matching `clause`, e.g. `WHERE id = ?1`.
+fn select_one(connection: &Connection, clause: &str, value: &str) -> Result<Option<Promocode>, String> {
+    connection
+        .query_row(
+            &format!("{} {}", SELECT_PROMOCODES, clause),
+            params![value],
+            read_row,
+        )
+        .optional()
+        .map_err(sql_error)?
+        .map(to_promocode)
+        .transpose()
+}
+
+/// Appends an entry to the audit trail.
+fn audit(transaction: &Transaction, at: DateTime<Utc>, op: &str, promocode: &Promocode) -> Result<(), String> {
+    let payload = serde_json::to_string(promocode).map_err(|err| err.to_string())?;
+
+    transaction
+        .execute(
+            "INSERT INTO audit (at, op, promocode_id, payload) VALUES (?1, ?2, ?3, ?4)",
+            params![at.to_rfc3339(), op, promocode._id(), payload],
+        )
+        .map(|_| ())
+        .map_err(sql_error)
+}
+
+type PromocodeRow = (String, String, u8, String, String, String);
+
+fn read_row(row: &rusqlite::Row) -> rusqlite::Result<PromocodeRow> {
+    Ok((
+        row.get(0)?,
+        row.get(1)?,
+        row.get(2)?,
+        row.get(3)?,
+        row.get(4)?,
+        row.get(5)?,
+    ))
+}
+
+fn to_promocode((id, name, percent, restrictions, metadata, weather_policy): PromocodeRow) -> Result<Promocode, String> {
+    let restrictions: Restrictions = serde_json::from_str(&restrictions).map_err(|err| format!("Promocode `{}` > `restrictions` {}", id, err))?;
+    let metadata: Metadata = serde_json::from_str(&metadata).map_err(|err| format!("Promocode `{}` > `metadata` {}", id, err))?;
+    let weather_policy: WeatherPolicy =
+        serde_json::from_value(Value::String(weather_policy)).map_err(|err| format!("Promocode `{}` > `weather_policy` {}", id, err))?;
+
+    Promocode::new(
+        id,
+        name,
+        Avantage::new(percent),
+        restrictions.into_iter().map(Ok).collect(),
+    )
+    .map(|promocode| promocode.with_weather_policy(weather_policy))
+    .and_then(|promocode| promocode.with_metadata(Ok(metadata)))
+}
+
+/// Returns the [Restrictions] and the [Metadata] of `promocode` as JSON, and
+/// its [WeatherPolicy] as serialized.
+fn to_json(promocode: &Promocode) -> Result<(String, String, String), String> {
+    match (
+        serde_json::to_string(&promocode.restrictions),
+        serde_json::to_string(&promocode.metadata),
+        serde_json::to_value(promocode.weather_policy),
+    ) {
+        (Ok(restrictions), Ok(metadata), Ok(Value::String(weather_policy))) => Ok((restrictions, metadata, weather_policy)),
+        (Err(err), _, _) | (_, Err(err), _) | (_, _, Err(err)) => Err(err.to_string()),
+        (_, _, Ok(weather_policy)) => Err(format!("Unexpected weather policy `{}`.", weather_policy)),
+    }
+}
+
+fn is_constraint_violation(err: &rusqlite::Error) -> bool {
+    err.sqlite_error_code() == Some(ErrorCode::ConstraintViolation)
+}
+
+fn sql_error(err: rusqlite::Error) -> String {
+    format!("SQLite: {}", err)
+}
@@ -1,21 +1,115 @@
-use ntex::web::{App, HttpServer};
+use futures_util::future;
+use log::info;
+use ntex::{
+    http::header,
+    web::{
+        middleware::{DefaultHeaders, Logger},
+        App, HttpServer,
+    },
+};
 
-mod routes;
+use crate::{auth::ApiKeys, repository::Repository, signing_key::SigningKey, weather::Weather};
+use promocode_models::promocode::name_policy::NamePolicy;
+use routes::RouteSettings;
+
+pub mod routes;
+
+/// Format of the access log of the public listener.
+const PUBLIC_LOG_FORMAT: &str = "public \"%r\" %s %b %Dms";
+/// Format of the access log of the admin listener, which also tells who
+/// called it.
+const ADMIN_LOG_FORMAT: &str = "admin %a \"%r\" %s %b %Dms \"%{User-Agent}i\"";
 
 /// Starts the HTTP server at the specified host and port.
 ///
+/// When an `admin` address is given, only the
+/// [public_services](routes::public_services) are served at `host` and
+/// `port`, and the [admin_services](routes::admin_services) are served at
+/// `admin`, each listener with its own middlewares. Otherwise, every service
+/// is served at `host` and `port`.
+///
 /// # Arguments
 ///
 /// - `host` - The IP address or hostname to bind the server to.
 /// - `port` - The port number to bind the server to.
+/// - `admin` - The IP address or hostname and port of the admin listener.
+/// - `repository` - The [Repository] shared by all the workers and listeners.
+/// - `weather` - The [Weather] shared by all the workers and listeners.
+/// - `name_policy` - The [NamePolicy] of the promocode names.
+/// - `signing_key` - The [SigningKey] of the signed promocodes.
+/// - `settings` - The [RouteSettings] of the routes.
+/// - `api_keys` - The [ApiKeys] granting access to the routes.
 ///
 /// # Returns
 ///
 /// A [Result] indicating the success or failure of the server startup.
-#[allow(unused_variables)]
+#[allow(unused_variables, clippy::too_many_arguments)]
 #[ntex::main]
-pub async fn serve(host: String, port: u16) -> std::io::Result<()> {
-    let server = HttpServer::new(move || App::new().configure(routes::services));
+pub async fn serve(
+    host: String,
+    port: u16,
+    admin: Option<(String, u16)>,
+    repository: Repository,
+    weather: Weather,
+    name_policy: NamePolicy,
+    signing_key: SigningKey,
+    settings: RouteSettings,
+    api_keys: ApiKeys,
+) -> std::io::Result<()> {
+    let Some(admin) = admin else {
+        let server = HttpServer::new(move || {
+            let settings = settings.clone();
+            App::new()
+                .wrap(Logger::default())
+                .state(repository.clone())
+                .state(weather.clone())
+                .state(name_policy.clone())
+                .state(signing_key.clone())
+                .state(api_keys.clone())
+                .configure(move |cfg| routes::services_with(cfg, &settings))
+        });
+
+        return server.bind((host, port))?.run().await;
+    };
+
+    let public_server = {
+        let repository = repository.clone();
+        let weather = weather.clone();
+        let name_policy = name_policy.clone();
+        let signing_key = signing_key.clone();
+        let api_keys = api_keys.clone();
+        HttpServer::new(move || {
+            let settings = settings.clone();
+            App::new()
+                .wrap(Logger::new(PUBLIC_LOG_FORMAT))
+                .state(repository.clone())
+                .state(weather.clone())
+                .state(name_policy.clone())
+                .state(signing_key.clone())
+                .state(api_keys.clone())
+                .configure(move |cfg| routes::public_services(cfg, &settings))
+        })
+    };
+    let admin_server = HttpServer::new(move || {
+        App::new()
+            .wrap(Logger::new(ADMIN_LOG_FORMAT))
+            .wrap(DefaultHeaders::new().header(header::CACHE_CONTROL, "no-store"))
+            .state(repository.clone())
+            .state(weather.clone())
+            .state(name_policy.clone())
+            .state(signing_key.clone())
+            .state(api_keys.clone())
+            .configure(routes::admin_services)
+    });
 
-    server.bind((host, port))?.run().await
+    info!(
+        "Public routes served at {}:{}, admin routes at {}:{}.",
+        host, port, admin.0, admin.1
+    );
+    future::try_join(
+        public_server.bind((host, port))?.run(),
+        admin_server.bind(admin)?.run(),
+    )
+    .await
+    .map(|_| ())
 }
@@ -1,13 +1,90 @@
-use ntex::web;
+use ntex::web::{self, HttpResponse};
+use serde::Serialize;
+
+use crate::repository::{RepositoryError, UniqueField};
 
 pub mod promocode;
 pub mod promocode_list;
+pub mod promocode_resource;
+pub mod signed_promocode;
+pub mod voucher_batch;
+pub mod weather_stats;
+
+/// Optional behaviours of the routes.
+#[derive(Clone, Debug)]
+pub struct RouteSettings {
+    /// Serves `GET /promocode`, the deprecated alias of
+    /// `POST /promocode/validate`.
+    pub deprecated_get_validation: bool,
+}
+
+impl Default for RouteSettings {
+    fn default() -> Self {
+        Self {
+            deprecated_get_validation: true,
+        }
+    }
+}
 
-/// Registers the services for the application.
+/// Registers the services for the application, with the default
+/// [RouteSettings].
+///
+/// Every route requires a [Scope](crate::auth::Scope), so the
+/// [ApiKeys](crate::auth::ApiKeys) must be registered in the application
+/// state, along with the [Repository](crate::repository::Repository) and the
+/// [Weather](crate::weather::Weather).
 pub fn services(cfg: &mut web::ServiceConfig) {
-    promocode::promocode_services(cfg);
+    services_with(cfg, &RouteSettings::default());
+}
+
+/// Registers both the [public_services] and the [admin_services] for the
+/// application, when they share a listener.
+pub fn services_with(cfg: &mut web::ServiceConfig, settings: &RouteSettings) {
+    public_services(cfg, settings);
+    admin_services(cfg);
+}
+
+/// Registers the services meant to be reachable from the internet: the
+/// validation of the promocodes.
+pub fn public_services(cfg: &mut web::ServiceConfig, settings: &RouteSettings) {
+    promocode::promocode_validation_services(cfg, settings);
+}
+
+/// Registers the services managing the promocodes, to serve on a private
+/// listener.
+pub fn admin_services(cfg: &mut web::ServiceConfig) {
+    promocode::promocode_admin_services(cfg);
+    promocode_resource::promocode_resource_services(cfg);
+    signed_promocode::signed_promocode_services(cfg);
+    voucher_batch::voucher_batch_services(cfg);
+    promocode_list::promocode_list_services(cfg);
+    weather_stats::weather_stats_services(cfg);
+}
+
+/// Body of an HTTP 409 response.
+#[derive(Serialize, Debug)]
+struct ConflictResponse {
+    error: String,
+    field: UniqueField,
+    value: String,
+}
 
-    if cfg!(debug_assertions) {
-        promocode_list::promocode_list_services(cfg);
+/// Maps a [RepositoryError] to an [HttpResponse].
+///
+/// - [RepositoryError::Conflict] gives an HTTP 409 error with the conflicting
+///   field and value.
+/// - [RepositoryError::NotFound] gives an HTTP 404 error.
+/// - [RepositoryError::VersionMismatch] gives an HTTP 412 error.
+/// - [RepositoryError::Storage] gives an HTTP 500 error.
+pub(crate) fn repository_error_response(err: RepositoryError) -> HttpResponse {
+    match err {
+        RepositoryError::Conflict { field, ref value } => HttpResponse::Conflict().json(&ConflictResponse {
+            error: err.to_string(),
+            field,
+            value: value.clone(),
+        }),
+        RepositoryError::NotFound { .. } => HttpResponse::NotFound().json(&err.to_string()),
+        RepositoryError::VersionMismatch { .. } => HttpResponse::PreconditionFailed().json(&err.to_string()),
+        RepositoryError::Storage(err) => HttpResponse::InternalServerError().json(&err),
     }
 }
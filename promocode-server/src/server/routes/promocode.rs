@@ -1,92 +1,411 @@
-use ntex::web::{delete, get, put, types::Json, HttpResponse, ServiceConfig};
+use chrono::Utc;
+use log::warn;
+use ntex::{
+    http::header::{self, HeaderName, HeaderValue},
+    util::Bytes,
+    web::{
+        delete, get, post, put,
+        types::{Json, Path, State},
+        HttpResponse, ServiceConfig,
+    },
+};
+use serde::de::DeserializeSeed;
 
+use super::{repository_error_response, RouteSettings};
 use crate::{
-    db::{db_delete_by_name, db_get_by_name, db_list, db_push},
-    open_weather_sdk,
+    auth::{scope, Authorized},
+    repository::{Repository, RepositoryError, UniqueField},
+    signing_key::SigningKey,
+    weather::{self, Weather},
 };
 use promocode_models::{
-    promocode::{restrictions::RestrictionsExt, Promocode},
-    promocode_request::PromocodeRequest,
+    forecast::Forecast,
+    promocode::{name_policy::NamePolicy, restrictions::RestrictionsExt, Promocode},
+    promocode_request::{arguments::Arguments, MalformedName, PromocodeRequest, PromocodeRequestSeed},
     promocode_response::PromocodeResponse,
+    signed_promocode::SignedPromocode,
 };
 
-/// Configure the promo code services.
+/// Configure the promo code validation services.
 ///
-/// This function should be called to configure the promo code-related services
-/// in a [ServiceConfig]. It adds the necessary routes to handle the `GET` and
-/// `PUT` requests for promo codes. If the code is compiled with debug
-/// assertions enabled, it also adds the route for deleting promo codes.
+/// This function should be called to configure the public promo code-related
+/// services in a [ServiceConfig]. It adds the necessary routes to validate
+/// promo codes.
 ///
 /// # Arguments
 ///
 /// * `cfg` - A mutable reference to the [ServiceConfig] where the routes will be added.
-pub fn promocode_services(cfg: &mut ServiceConfig) {
-    cfg.service(get_promocode);
-    cfg.service(put_promocode);
-    if cfg!(debug_assertions) {
-        cfg.service(delete_promocode);
+/// * `settings` - The [RouteSettings], telling whether the deprecated
+///   `GET /promocode` is served.
+pub fn promocode_validation_services(cfg: &mut ServiceConfig, settings: &RouteSettings) {
+    cfg.service(validate_promocode);
+    cfg.service(validate_promocode_by_name);
+    if settings.deprecated_get_validation {
+        cfg.service(get_promocode);
     }
 }
 
-/// Handler for validate a [PromocodeRequest].
+/// Configure the promo code administration services.
 ///
-/// This async function takes a [PromocodeRequest] in JSON format as input and
-/// retrieves the corresponding [Promocode] from the database by calling
-/// [db_get_by_name]. It then checks if the [Promocode] has any restrictions and
-/// whether the request satisfies those restrictions.
+/// It adds the necessary routes to create and delete promo codes in a
+/// [ServiceConfig].
+///
+/// # Arguments
 ///
-/// If the promocode exists and the request satisfies any restrictions, it
-/// returns an HTTP 200 response with the accepted [Promocode] in the body. If
-/// the request does not satisfy the restrictions or the promocode does not
-/// exist, it returns an HTTP 400 error with a [BadRequest] response.
+/// * `cfg` - A mutable reference to the [ServiceConfig] where the routes will be added.
+pub fn promocode_admin_services(cfg: &mut ServiceConfig) {
+    cfg.service(put_promocode);
+    cfg.service(delete_promocode);
+}
+
+/// Handler for validate a [PromocodeRequest].
 ///
 /// # Arguments
 ///
-/// - `promocode_req_json`: JSON payload containing the [PromocodeRequest]
-///   details.
+/// - `repository`: The [Repository] of the application.
+/// - `weather`: The [Weather] of the application.
+/// - `name_policy`: The [NamePolicy] of the application.
+/// - `signing_key`: The [SigningKey] of the application.
+/// - `body`: JSON payload containing the [PromocodeRequest] details, whose
+///   name is checked by the [NamePolicy] while it is read.
 ///
 /// # Returns
 ///
-/// An [HttpResponse] object that may contain a [PromocodeAccepted] or a
-/// [BadRequest] with [PromocodeDenied].
+/// See [validate] for the status codes.
+#[post("/promocode/validate")]
+pub async fn validate_promocode(
+    _: Authorized<scope::Validate>,
+    repository: State<Repository>,
+    weather: State<Weather>,
+    name_policy: State<NamePolicy>,
+    signing_key: State<SigningKey>,
+    body: Bytes,
+) -> HttpResponse {
+    match read_promocode_request(&body, &name_policy) {
+        Ok(promocode_request) => {
+            validate(
+                &repository,
+                &weather,
+                &name_policy,
+                &signing_key,
+                promocode_request,
+            )
+            .await
+        },
+        Err(response) => response,
+    }
+}
+
+/// Handler for validate the promocode named in the path.
 ///
-/// # Errors
+/// # Arguments
 ///
-/// This function might return an error if there's a problem with:
+/// - `repository`: The [Repository] of the application.
+/// - `weather`: The [Weather] of the application.
+/// - `name_policy`: The [NamePolicy] of the application.
+/// - `signing_key`: The [SigningKey] of the application.
+/// - `name`: The name of the promocode, as it was typed.
+/// - `arguments_json`: JSON payload containing the [Arguments] of the
+///   request.
+///
+/// # Returns
+///
+/// See [validate] for the status codes.
+#[post("/promocode/{name}/validate")]
+pub async fn validate_promocode_by_name(
+    _: Authorized<scope::Validate>,
+    repository: State<Repository>,
+    weather: State<Weather>,
+    name_policy: State<NamePolicy>,
+    signing_key: State<SigningKey>,
+    name: Path<String>,
+    arguments_json: Json<Arguments>,
+) -> HttpResponse {
+    match PromocodeRequest::new(name.into_inner(), Ok(arguments_json.into_inner())) {
+        Ok(promocode_request) => {
+            validate(
+                &repository,
+                &weather,
+                &name_policy,
+                &signing_key,
+                promocode_request.with_name_policy(&name_policy),
+            )
+            .await
+        },
+        Err(err) => HttpResponse::BadRequest().json(&err),
+    }
+}
+
+/// Deprecated alias of [validate_promocode].
 ///
-/// - Retrieving the requested [Promocode] from the database.
-/// - Checking the restrictions of the [Promocode].
-/// - Generating a response with the provided [Promocode].
+/// A `GET` request with a body is dropped or refused by many proxies, caches
+/// and HTTP clients. The responses carry a `Deprecation` header and a `Link`
+/// to the successor route. It is only served if
+/// [RouteSettings::deprecated_get_validation] is set.
 #[get("/promocode")]
-pub async fn get_promocode(promocode_req_json: Json<PromocodeRequest>) -> HttpResponse {
+pub async fn get_promocode(
+    _: Authorized<scope::Validate>,
+    repository: State<Repository>,
+    weather: State<Weather>,
+    name_policy: State<NamePolicy>,
+    signing_key: State<SigningKey>,
+    body: Bytes,
+) -> HttpResponse {
+    let mut response = match read_promocode_request(&body, &name_policy) {
+        Ok(promocode_request) => {
+            validate(
+                &repository,
+                &weather,
+                &name_policy,
+                &signing_key,
+                promocode_request,
+            )
+            .await
+        },
+        Err(response) => response,
+    };
+
+    let headers = response.headers_mut();
+    headers.insert(
+        HeaderName::from_static("deprecation"),
+        HeaderValue::from_static("true"),
+    );
+    headers.insert(
+        header::LINK,
+        HeaderValue::from_static("</promocode/validate>; rel=\"successor-version\""),
+    );
+    response
+}
+
+/// Reads a [PromocodeRequest] from a JSON `body`, checking its name with
+/// `name_policy`, see [PromocodeRequestSeed].
+///
+/// It returns an HTTP 400 error if `body` is not a [PromocodeRequest].
+fn read_promocode_request(body: &[u8], name_policy: &NamePolicy) -> Result<Result<PromocodeRequest, MalformedName>, HttpResponse> {
+    let mut deserializer = serde_json::Deserializer::from_slice(body);
+
+    PromocodeRequestSeed::new(name_policy)
+        .deserialize(&mut deserializer)
+        .and_then(|promocode_request| deserializer.end().map(|_| promocode_request))
+        .map_err(|err| HttpResponse::BadRequest().json(&format!("Json deserialize error: {}", err)))
+}
+
+/// Validates a [PromocodeRequest].
+///
+/// It retrieves the corresponding [Promocode] from the [Repository] by its
+/// name normalized by the [NamePolicy]. It then checks if the [Promocode] has
+/// any restrictions and whether the request satisfies those restrictions.
+///
+/// A [SignedPromocode] is not looked up in the database, see
+/// [check_signed_promocode].
+///
+/// # Returns
+///
+/// - HTTP 200 with a [PromocodeResponse::Accepted] if the promocode exists
+///   and the request satisfies one of its restrictions. The redemption is
+///   recorded by the [Repository].
+/// - HTTP 400 with a [PromocodeResponse::Denied] if the promocode does not
+///   exist or the request does not satisfy its restrictions. A stored
+///   [Promocode] without restrictions denies every request, unlike a
+///   [SignedPromocode].
+/// - HTTP 422 with a [PromocodeResponse::Malformed] if the name does not
+///   comply with the [NamePolicy] (e.g. a wrong check digit), a
+///   [MalformedName] found while reading the request, without querying the
+///   database. It suggests an existing name when the policy allows it.
+/// - HTTP 500 if the [Repository] fails.
+async fn validate(
+    repository: &Repository,
+    weather: &Weather,
+    name_policy: &NamePolicy,
+    signing_key: &SigningKey,
+    promocode_request: Result<PromocodeRequest, MalformedName>,
+) -> HttpResponse {
+    let promocode_request = match promocode_request {
+        Ok(promocode_request) => promocode_request,
+        Err(malformed) => {
+            let suggestion = suggest_promocode_name(repository, name_policy, &malformed.promocode_name);
+            return promocode_http_response(PromocodeResponse::malformed(
+                malformed.promocode_name,
+                suggestion,
+            ));
+        },
+    };
+
+    if SignedPromocode::is_signed_code(promocode_request.normalized_name().as_str()) {
+        return check_signed_promocode(weather, signing_key, &promocode_request).await;
+    }
+
     let mut percent = 0u8;
 
-    let predicate = match db_get_by_name(promocode_req_json.promocode_name()) {
-        Some(promocode) => {
+    let predicate = match repository.get_by_name(&promocode_request.normalized_name()) {
+        Err(err) => return repository_error_response(err),
+        Ok(Some(promocode)) => {
             percent = promocode.avantage.percent.get();
 
-            let weather_and_temp = open_weather_sdk::get_current_meteo_and_temp(&promocode_req_json).await;
+            let arguments = &promocode_request.arguments;
+            let accepted = check_lazily(
+                weather,
+                promocode
+                    .restrictions
+                    .check_restriction_or_without_weather(arguments),
+                &promocode_request,
+                |forecast| {
+                    promocode
+                        .restrictions
+                        .check_restriction_or(arguments.clone(), forecast)
+                },
+                || promocode.check_weather_unavailable(arguments),
+            )
+            .await;
 
-            promocode
-                .restrictions
-                .check_restriction_or(promocode_req_json.arguments.clone(), weather_and_temp)
+            if accepted {
+                if let Err(err) = repository.record_redemption(&promocode._id(), Utc::now()) {
+                    return repository_error_response(err);
+                }
+            }
+            accepted
         },
-        None => false,
+        Ok(None) => false,
     };
 
-    match Promocode::generate_response(promocode_req_json.promocode_name(), percent, predicate) {
+    promocode_http_response(Promocode::generate_response(
+        promocode_request.promocode_name(),
+        percent,
+        predicate,
+    ))
+}
+
+/// Validates a [PromocodeRequest] for a [SignedPromocode], without querying the
+/// database.
+///
+/// If the signature of the code is wrong (tampered code, or signed with
+/// another key), it returns an HTTP 422 error with a
+/// [PromocodeResponse::Malformed]. Otherwise, the code is accepted if it is not
+/// expired and the request satisfies one of its restrictions, if any: its
+/// expiry is enough to restrict a [SignedPromocode], which is accepted without
+/// restrictions unlike a stored [Promocode].
+///
+/// All signed promocodes are denied when the [SigningKey] is not set.
+async fn check_signed_promocode(weather: &Weather, signing_key: &SigningKey, promocode_request: &PromocodeRequest) -> HttpResponse {
+    let signed_promocode = match signing_key.get() {
+        None => {
+            warn!("Signed promocode denied because the signing key is not set.");
+            None
+        },
+        Some(key) => match SignedPromocode::decode(promocode_request.normalized_name().as_str(), key) {
+            Ok(signed_promocode) => Some(signed_promocode),
+            Err(err) => {
+                warn!("{}", err);
+                return promocode_http_response(PromocodeResponse::malformed(
+                    promocode_request.promocode_name(),
+                    None,
+                ));
+            },
+        },
+    };
+
+    let (percent, predicate) = match signed_promocode {
+        None => (0u8, false),
+        Some(signed_promocode) => {
+            let today = Utc::now().date_naive();
+            (
+                signed_promocode.avantage.percent(),
+                check_lazily(
+                    weather,
+                    signed_promocode.check_without_weather(&promocode_request.arguments, today),
+                    promocode_request,
+                    |forecast| signed_promocode.check(promocode_request.arguments.clone(), forecast, today),
+                    || signed_promocode.check_weather_unavailable(&promocode_request.arguments, today),
+                )
+                .await,
+            )
+        },
+    };
+
+    promocode_http_response(Promocode::generate_response(
+        promocode_request.promocode_name(),
+        percent,
+        predicate,
+    ))
+}
+
+/// Returns the outcome of a check, `without_weather` if it is decided,
+/// otherwise the one of `with_weather` given the forecast of the town of the
+/// request, or the one of `weather_unavailable` if the weather cannot be
+/// fetched.
+///
+/// The weather is therefore only fetched, once, when a
+/// [Restriction::Meteo](promocode_models::promocode::restriction::Restriction::Meteo)
+/// decides the outcome.
+async fn check_lazily(
+    weather: &Weather,
+    without_weather: Option<bool>,
+    promocode_request: &PromocodeRequest,
+    with_weather: impl FnOnce(Option<Forecast>) -> bool,
+    weather_unavailable: impl FnOnce() -> bool,
+) -> bool {
+    match without_weather {
+        Some(outcome) => outcome,
+        None => match weather::fetch_forecast(
+            weather.as_ref(),
+            promocode_request.arguments.meteo.town().as_str(),
+        )
+        .await
+        {
+            Ok(forecast) => with_weather(forecast),
+            Err(err) => {
+                warn!(
+                    "Weather unavailable, checked with the weather policy of the promocode: {}",
+                    err
+                );
+                weather_unavailable()
+            },
+        },
+    }
+}
+
+/// Maps a [PromocodeResponse] to an [HttpResponse] with the matching status
+/// code.
+fn promocode_http_response(promocode_response: Result<PromocodeResponse, String>) -> HttpResponse {
+    match promocode_response {
         Ok(promocode_response) => match promocode_response {
             PromocodeResponse::Accepted { .. } => HttpResponse::Ok().json(&promocode_response),
             PromocodeResponse::Denied { .. } => HttpResponse::BadRequest().json(&promocode_response),
+            PromocodeResponse::Malformed { .. } => HttpResponse::UnprocessableEntity().json(&promocode_response),
         },
         Err(err) => HttpResponse::InternalServerError().json(&err),
     }
 }
 
+/// Maximum number of [Repository] lookups made to suggest a promocode name.
+const MAX_SUGGESTION_LOOKUPS: usize = 32;
+
+/// Returns an existing promocode name at one typo of `promocode_name`, if the
+/// [CheckDigit](promocode_models::promocode::check_digit::CheckDigit) of the
+/// [NamePolicy] allows suggestions.
+///
+/// At most [MAX_SUGGESTION_LOOKUPS] candidates are looked up, so that a
+/// malformed name costs a bounded number of queries whatever the alphabet.
+fn suggest_promocode_name(repository: &Repository, name_policy: &NamePolicy, promocode_name: &str) -> Option<String> {
+    let check_digit = name_policy.check_digit().filter(|it| it.suggest())?;
+    let promocode_name = name_policy.normalize(promocode_name).ok()?;
+
+    check_digit
+        .suggestions(&promocode_name)
+        .into_iter()
+        .take(MAX_SUGGESTION_LOOKUPS)
+        .find(|it| matches!(repository.get_by_name(it), Ok(Some(_))))
+}
+
 /// Handler for creating a new [Promocode].
 ///
+/// The name of the [Promocode] is normalized by the [NamePolicy] before
+/// being stored.
+///
 /// # Arguments
 ///
+/// - `repository`: The [Repository] of the application.
+/// - `name_policy`: The [NamePolicy] of the application.
 /// - `promocode_json`: JSON payload containing the [Promocode] details.
 ///
 /// # Returns
@@ -94,28 +413,42 @@ pub async fn get_promocode(promocode_req_json: Json<PromocodeRequest>) -> HttpRe
 /// This function returns an [HttpResponse] indicating the status of the
 /// operation.
 ///
-/// - If a [Promocode] with the same id or name already exists in the database,
-///   it returns a [HttpResponse::BadRequest()] response with an error message.
-/// - If the [Promocode] was successfully added to the database, it returns an
-///   [Ok] response with an empty JSON payload.
-/// - If an error occurred while adding the [Promocode] to the database, it
-///   returns a [HttpResponse::BadRequest()] response with the error message.
+/// - If the name does not comply with the [NamePolicy], it returns a
+///   [HttpResponse::BadRequest()] response with an error message.
+/// - If a [Promocode] with the same id or normalized name already exists in
+///   the [Repository], it returns a [HttpResponse::Conflict()] response with
+///   the conflicting field. The check and the insertion are atomic.
+/// - If the very same [Promocode] is already stored, e.g. when a request is
+///   retried, it returns an [Ok] response without writing anything.
+/// - If the [Promocode] was successfully added to the [Repository], it returns
+///   an [Ok] response with an empty JSON payload. Its `created_at` and
+///   `updated_at` metadata are set to the current date, whatever was sent.
 #[put("/promocode")]
-pub async fn put_promocode(promocode_json: Json<Promocode>) -> HttpResponse {
-    if db_list()
-        .iter()
-        .any(|it| it._id() == promocode_json._id() || it.name() == promocode_json.name())
-    {
-        return HttpResponse::BadRequest().json(&format!(
-            "Promocode with id `{}` or name `{}` already exist.",
-            promocode_json._id(),
-            promocode_json.name()
-        ));
-    }
+pub async fn put_promocode(
+    _: Authorized<scope::Write>,
+    repository: State<Repository>,
+    name_policy: State<NamePolicy>,
+    promocode_json: Json<Promocode>,
+) -> HttpResponse {
+    let mut promocode = match promocode_json.into_inner().normalize_name(&name_policy) {
+        Ok(promocode) => promocode,
+        Err(err) => return HttpResponse::BadRequest().json(&err),
+    };
+
+    promocode.metadata.mark_created(Utc::now());
 
-    match db_push(promocode_json.to_owned()) {
+    match repository.insert(promocode.clone()) {
         Ok(_) => HttpResponse::Ok().json(&""),
-        Err(err) => HttpResponse::BadRequest().json(&err),
+        Err(RepositoryError::Conflict {
+            field: UniqueField::Id,
+            ..
+        }) if repository
+            .get_by_id(&promocode._id())
+            .is_ok_and(|stored| stored.is_some_and(|it| it.same_content(&promocode))) =>
+        {
+            HttpResponse::Ok().json(&"")
+        },
+        Err(err) => repository_error_response(err),
     }
 }
 
@@ -123,14 +456,33 @@ pub async fn put_promocode(promocode_json: Json<Promocode>) -> HttpResponse {
 ///
 /// # Parameters
 ///
-/// - `name`: The name of the [Promocode] to be deleted.
+/// - `repository`: The [Repository] of the application.
+/// - `name_policy`: The [NamePolicy] of the application.
+/// - `name`: The name of the [Promocode] to be deleted, normalized by
+///   `name_policy`.
 ///
 /// # Returns
 ///
 /// An [HttpResponse] with a status code of 200 if the [Promocode] was
-/// successfully deleted from the database. The response body is an empty JSON object.
+/// successfully deleted from the [Repository]. The response body is an empty JSON object.
+/// If there is no [Promocode] with this name, it returns a
+/// [HttpResponse::NotFound()].
+///
+/// Prefer `DELETE /promocodes/{id}`.
 #[delete("/promocode")]
-pub async fn delete_promocode(name: Json<String>) -> HttpResponse {
-    db_delete_by_name(name.to_owned());
-    HttpResponse::Ok().json(&"")
+pub async fn delete_promocode(_: Authorized<scope::Write>, repository: State<Repository>, name_policy: State<NamePolicy>, name: Json<String>) -> HttpResponse {
+    // A name not complying with the policy cannot be stored.
+    let deleted = match name_policy.normalize(name.as_str()) {
+        Ok(normalized) => repository.delete_by_name(&normalized),
+        Err(_) => Ok(None),
+    };
+
+    match deleted {
+        Ok(Some(_)) => HttpResponse::Ok().json(&""),
+        Ok(None) => HttpResponse::NotFound().json(&format!(
+            "Promocode with name `{}` does not exist.",
+            name.as_str()
+        )),
+        Err(err) => repository_error_response(err),
+    }
 }
@@ -1,6 +1,31 @@
-use ntex::web::{get, HttpResponse, ServiceConfig};
+use std::{cmp::Ordering, collections::BinaryHeap};
 
-use crate::db::db_list;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, NaiveDate, Utc};
+use futures_util::{stream, Stream};
+use ntex::{
+    util::Bytes,
+    web::{
+        get,
+        types::{Query, State},
+        HttpResponse, ServiceConfig,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+use super::repository_error_response;
+use crate::{
+    auth::{scope, Authorized},
+    repository::{PromocodeFilter, Repository, RepositoryError},
+};
+use promocode_models::promocode::{restriction::RestrictionKind, status::PromocodeStatus, Promocode};
+
+/// Number of promocodes of a page, when the query does not tell.
+pub const DEFAULT_PAGE_LIMIT: usize = 100;
+/// Maximum number of promocodes of a page.
+pub const MAX_PAGE_LIMIT: usize = 1000;
+/// Number of promocodes read at once from the [Repository] to go through it.
+const SCAN_PAGE_SIZE: usize = 500;
 
 /// Register the `get_promocode_list` service to the given `ServiceConfig`.
 ///
@@ -13,8 +38,339 @@ pub fn promocode_list_services(cfg: &mut ServiceConfig) {
     cfg.service(get_promocode_list);
 }
 
-/// Fetches the list of [Promocode].
+/// Order of the promocodes of the list, `-` meaning descending. Ties are
+/// broken by id.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum PromocodeSort {
+    #[serde(rename = "name")]
+    Name,
+    #[serde(rename = "-name")]
+    NameDesc,
+    #[default]
+    #[serde(rename = "created")]
+    Created,
+    #[serde(rename = "-created")]
+    CreatedDesc,
+    #[serde(rename = "percent")]
+    Percent,
+    #[serde(rename = "-percent")]
+    PercentDesc,
+}
+
+/// Value a [Promocode] is sorted by.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum SortKey {
+    Percent(u8),
+    Name(String),
+    Created(Option<DateTime<Utc>>),
+}
+
+impl PromocodeSort {
+    fn key(&self, promocode: &Promocode) -> SortKey {
+        match self {
+            PromocodeSort::Name | PromocodeSort::NameDesc => SortKey::Name(promocode.name()),
+            PromocodeSort::Created | PromocodeSort::CreatedDesc => SortKey::Created(promocode.metadata.created_at()),
+            PromocodeSort::Percent | PromocodeSort::PercentDesc => SortKey::Percent(promocode.avantage.percent.get()),
+        }
+    }
+
+    /// Compares two (key, id) positions in the order of this sort.
+    fn compare(&self, left: (&SortKey, &str), right: (&SortKey, &str)) -> Ordering {
+        match self {
+            PromocodeSort::Name | PromocodeSort::Created | PromocodeSort::Percent => left.cmp(&right),
+            PromocodeSort::NameDesc | PromocodeSort::CreatedDesc | PromocodeSort::PercentDesc => right.cmp(&left),
+        }
+    }
+}
+
+/// A [Promocode] with its [SortKey], ordered by its [PromocodeSort].
+struct Ranked {
+    sort: PromocodeSort,
+    key: SortKey,
+    id: String,
+    promocode: Promocode,
+}
+
+impl Ranked {
+    fn new(sort: PromocodeSort, promocode: Promocode) -> Self {
+        Self {
+            sort,
+            key: sort.key(&promocode),
+            id: promocode._id(),
+            promocode,
+        }
+    }
+
+    /// Returns `true` if this promocode comes after the `cursor` one.
+    fn is_after(&self, cursor: &Cursor) -> bool {
+        self.sort
+            .compare((&self.key, &self.id), (&cursor.key, &cursor.id))
+            == Ordering::Greater
+    }
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sort
+            .compare((&self.key, &self.id), (&other.key, &other.id))
+    }
+}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ranked {}
+
+/// Position of the last promocode of a page, handed to the client as an
+/// opaque string to fetch the next one.
+///
+/// As it holds the sort key and the id rather than an offset, a page is
+/// neither skipped nor repeated when promocodes are inserted or deleted
+/// meanwhile.
+#[derive(Serialize, Deserialize, Debug)]
+struct Cursor {
+    sort: PromocodeSort,
+    key: SortKey,
+    id: String,
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    fn decode(cursor: &str, sort: PromocodeSort) -> Result<Self, String> {
+        let cursor: Cursor = URL_SAFE_NO_PAD
+            .decode(cursor)
+            .map_err(|err| err.to_string())
+            .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|err| err.to_string()))
+            .map_err(|err| format!("`cursor` is not valid: {}", err))?;
+
+        if cursor.sort != sort {
+            return Err("`cursor` was issued for another `sort`".to_string());
+        }
+        Ok(cursor)
+    }
+}
+
+/// Format of the list.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ListFormat {
+    /// A [PromocodePage] of JSON.
+    #[default]
+    Json,
+    /// Every promocode passing the filters, in id order and without
+    /// pagination, one JSON object per line.
+    Ndjson,
+}
+
+/// Query parameters accepted by [get_promocode_list].
+#[derive(Deserialize, Debug)]
+pub struct PromocodeListQuery {
+    /// Comma separated list of tags. Only the promocodes having all of them
+    /// are returned.
+    tags: Option<String>,
+    /// Only the promocodes generated for this campaign are returned.
+    campaign: Option<String>,
+    /// Only the promocodes whose name starts with this prefix, ignoring case,
+    /// are returned.
+    name_prefix: Option<String>,
+    /// Only the promocodes having a restriction of this kind are returned.
+    restriction: Option<RestrictionKind>,
+    /// Only the promocodes having this status today are returned.
+    status: Option<PromocodeStatus>,
+    /// Only the promocodes whose date restrictions accept this day
+    /// (`YYYY-MM-DD`) are returned.
+    valid_on: Option<NaiveDate>,
+    #[serde(default)]
+    sort: PromocodeSort,
+    /// Maximum number of promocodes of the page. Without `limit` nor
+    /// `cursor`, the list is not paginated.
+    limit: Option<usize>,
+    /// `next_cursor` of the previous page.
+    cursor: Option<String>,
+    #[serde(default)]
+    format: ListFormat,
+}
+
+impl PromocodeListQuery {
+    /// Returns the [PromocodeFilter] of this query, without blank tags.
+    fn filter(&self) -> PromocodeFilter {
+        let tags = match &self.tags {
+            None => vec![],
+            Some(tags) => tags
+                .split(',')
+                .map(|tag| tag.trim().to_string())
+                .filter(|tag| !tag.is_empty())
+                .collect(),
+        };
+
+        PromocodeFilter {
+            tags,
+            campaign: self.campaign.clone(),
+            name_prefix: self.name_prefix.clone(),
+            restriction_kind: self.restriction,
+            status: self.status,
+            valid_on: self.valid_on,
+        }
+    }
+}
+
+/// A page of the list.
+#[derive(Serialize, Debug)]
+pub struct PromocodePage {
+    promocodes: Vec<Promocode>,
+    /// Cursor of the next page, absent on the last one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_cursor: Option<String>,
+}
+
+/// Fetches the list of [Promocode], optionally filtered and sorted
+/// (e.g. `/promocodes?tags=summer,newsletter&status=active&sort=-percent`).
+///
+/// The [Repository] is read page by page: a page of the list only holds its
+/// own promocodes in memory, and the NDJSON export streams the store.
+///
+/// # Returns
+///
+/// - With `format=ndjson`, it streams every matching [Promocode], one per
+///   line and in id order, as `application/x-ndjson`.
+/// - With `limit` or `cursor`, it returns a [PromocodePage] of at most
+///   `limit` promocodes (default [DEFAULT_PAGE_LIMIT], at most
+///   [MAX_PAGE_LIMIT]). The next page is fetched by passing its
+///   `next_cursor` back as `cursor`, with the same filters and sort.
+/// - Otherwise, it returns every matching [Promocode] as a JSON array, as
+///   before the pagination.
+/// - If the query is not valid, it returns a [HttpResponse::BadRequest()].
 #[get("/promocodes")]
-async fn get_promocode_list() -> HttpResponse {
-    HttpResponse::Ok().json(&db_list())
+async fn get_promocode_list(_: Authorized<scope::Read>, repository: State<Repository>, query: Query<PromocodeListQuery>) -> HttpResponse {
+    let filter = query.filter();
+    let sort = query.sort;
+
+    if query.format == ListFormat::Ndjson {
+        return HttpResponse::Ok()
+            .content_type("application/x-ndjson")
+            .streaming(Box::pin(export(Repository::clone(&repository), filter)));
+    }
+
+    if query.limit.is_none() && query.cursor.is_none() {
+        let mut ranked = vec![];
+        if let Err(err) = scan(&repository, &filter, |promocode| {
+            ranked.push(Ranked::new(sort, promocode))
+        }) {
+            return repository_error_response(err);
+        }
+        ranked.sort();
+        return HttpResponse::Ok().json(
+            &ranked
+                .into_iter()
+                .map(|it| it.promocode)
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    let cursor = match query
+        .cursor
+        .as_deref()
+        .map(|cursor| Cursor::decode(cursor, sort))
+        .transpose()
+    {
+        Err(err) => return HttpResponse::BadRequest().json(&err),
+        Ok(cursor) => cursor,
+    };
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_LIMIT)
+        .clamp(1, MAX_PAGE_LIMIT);
+
+    // Keeps the `limit + 1` first promocodes after the cursor, the extra one
+    // telling whether there is a next page.
+    let mut first = BinaryHeap::with_capacity(limit + 2);
+    let scanned = scan(&repository, &filter, |promocode| {
+        let ranked = Ranked::new(sort, promocode);
+        if cursor.as_ref().is_none_or(|cursor| ranked.is_after(cursor)) {
+            first.push(ranked);
+            if first.len() > limit + 1 {
+                first.pop();
+            }
+        }
+    });
+    if let Err(err) = scanned {
+        return repository_error_response(err);
+    }
+
+    let mut page = first.into_sorted_vec();
+    let next_cursor = if page.len() > limit {
+        page.truncate(limit);
+        page.last().map(|last| {
+            Cursor {
+                sort,
+                key: last.key.clone(),
+                id: last.id.clone(),
+            }
+            .encode()
+        })
+    } else {
+        None
+    };
+
+    HttpResponse::Ok().json(&PromocodePage {
+        promocodes: page.into_iter().map(|it| it.promocode).collect(),
+        next_cursor,
+    })
+}
+
+/// Calls `visit` with every [Promocode] passing `filter`, reading the
+/// [Repository] page by page.
+fn scan(repository: &Repository, filter: &PromocodeFilter, mut visit: impl FnMut(Promocode)) -> Result<(), RepositoryError> {
+    let mut after = None;
+    loop {
+        let page = repository.list_page(filter, after.as_deref(), SCAN_PAGE_SIZE)?;
+        let is_last = page.len() < SCAN_PAGE_SIZE;
+        after = page.last().map(|it| it._id());
+
+        page.into_iter().for_each(&mut visit);
+        if is_last {
+            return Ok(());
+        }
+    }
+}
+
+/// Streams every [Promocode] passing `filter` as NDJSON, reading the
+/// [Repository] page by page as the lines are sent.
+fn export(repository: Repository, filter: PromocodeFilter) -> impl Stream<Item = Result<Bytes, RepositoryError>> {
+    // The id after which the next page starts, [None] once the last page is
+    // sent.
+    stream::unfold(Some(None), move |after: Option<Option<String>>| {
+        let repository = repository.clone();
+        let filter = filter.clone();
+        async move {
+            let after = after?;
+            let page = match repository.list_page(&filter, after.as_deref(), SCAN_PAGE_SIZE) {
+                Err(err) => return Some((Err(err), None)),
+                Ok(page) if page.is_empty() => return None,
+                Ok(page) => page,
+            };
+            let next = (page.len() == SCAN_PAGE_SIZE).then(|| page.last().map(|it| it._id()));
+
+            let mut lines = vec![];
+            for promocode in &page {
+                if let Err(err) = serde_json::to_writer(&mut lines, promocode) {
+                    return Some((Err(RepositoryError::Storage(err.to_string())), None));
+                }
+                lines.push(b'\n');
+            }
+            Some((Ok(Bytes::from(lines)), next))
+        }
+    })
 }
@@ -0,0 +1,313 @@
+use chrono::Utc;
+use ntex::{
+    http::{header, StatusCode},
+    web::{
+        delete, get, patch, put,
+        types::{Json, Path, State},
+        HttpRequest, HttpResponse, ServiceConfig,
+    },
+};
+use serde_json::{Map, Value};
+
+use super::repository_error_response;
+use crate::{
+    auth::{scope, Authorized},
+    repository::Repository,
+};
+use promocode_models::promocode::{name_policy::NamePolicy, Promocode};
+
+/// Registers the services handling a [Promocode] as a resource identified by
+/// its id, under `/promocodes/{id}`, or by its name under
+/// `/promocodes/by-name/{name}`.
+///
+/// Every response carrying a [Promocode] has an `ETag` header holding its
+/// version. Sending it back in an `If-Match` header makes a write fail with an
+/// HTTP 412 error if the [Promocode] was modified meanwhile.
+pub fn promocode_resource_services(cfg: &mut ServiceConfig) {
+    cfg.service(get_promocode_by_name);
+    cfg.service(get_promocode_resource);
+    cfg.service(delete_promocode_resource);
+    cfg.service(put_promocode_resource);
+    cfg.service(patch_promocode_resource);
+}
+
+/// Handler for fetch the [Promocode] with the given id.
+///
+/// It returns an [Ok] response with the [Promocode], or a
+/// [HttpResponse::NotFound()] if there is none.
+#[get("/promocodes/{id}")]
+pub async fn get_promocode_resource(_: Authorized<scope::Read>, repository: State<Repository>, id: Path<String>) -> HttpResponse {
+    match repository.get_by_id(&id) {
+        Ok(Some(promocode)) => promocode_response(StatusCode::OK, &promocode),
+        Ok(None) => not_found(&id),
+        Err(err) => repository_error_response(err),
+    }
+}
+
+/// Handler for fetch the [Promocode] with the given name, normalized by the
+/// [NamePolicy].
+///
+/// It returns an [Ok] response with the [Promocode], or a
+/// [HttpResponse::NotFound()] if there is none.
+#[get("/promocodes/by-name/{name}")]
+pub async fn get_promocode_by_name(
+    _: Authorized<scope::Read>,
+    repository: State<Repository>,
+    name_policy: State<NamePolicy>,
+    name: Path<String>,
+) -> HttpResponse {
+    // A name not complying with the policy cannot be stored.
+    let promocode = match name_policy.normalize(&name) {
+        Ok(name) => repository.get_by_name(&name),
+        Err(_) => Ok(None),
+    };
+
+    match promocode {
+        Ok(Some(promocode)) => promocode_response(StatusCode::OK, &promocode),
+        Ok(None) => HttpResponse::NotFound().json(&format!("Promocode with name `{}` does not exist.", *name)),
+        Err(err) => repository_error_response(err),
+    }
+}
+
+/// Handler for delete the [Promocode] with the given id.
+///
+/// # Returns
+///
+/// - If there is no [Promocode] with this id, it returns a
+///   [HttpResponse::NotFound()].
+/// - If the `If-Match` header does not match the stored [Promocode], or if
+///   the [Promocode] is modified between the check and the deletion, it
+///   returns a [HttpResponse::PreconditionFailed()].
+/// - Otherwise, it returns an [Ok] response with the deleted [Promocode].
+#[delete("/promocodes/{id}")]
+pub async fn delete_promocode_resource(_: Authorized<scope::Write>, repository: State<Repository>, request: HttpRequest, id: Path<String>) -> HttpResponse {
+    let stored = match repository.get_by_id(&id) {
+        Ok(Some(stored)) => stored,
+        Ok(None) => return not_found(&id),
+        Err(err) => return repository_error_response(err),
+    };
+    if let Some(response) = check_if_match(&request, Some(&stored)) {
+        return response;
+    }
+
+    // The repository checks the version again, atomically with the deletion.
+    match repository.delete_by_id(&id, if_match_version(&request, &stored)) {
+        Ok(Some(promocode)) => promocode_response(StatusCode::OK, &promocode),
+        // Deleted meanwhile.
+        Ok(None) => not_found(&id),
+        Err(err) => repository_error_response(err),
+    }
+}
+
+/// Handler for create or replace the [Promocode] with the given id.
+///
+/// # Arguments
+///
+/// - `repository`: The [Repository] of the application.
+/// - `name_policy`: The [NamePolicy] of the application.
+/// - `id`: The id of the [Promocode], which must be the `_id` of the body.
+/// - `promocode_json`: JSON payload containing the whole [Promocode].
+///
+/// # Returns
+///
+/// - If the body is not valid, it returns a [HttpResponse::BadRequest()].
+/// - If the `If-Match` header does not match the stored [Promocode], it
+///   returns a [HttpResponse::PreconditionFailed()].
+/// - If there is no [Promocode] with this id, it is created and it returns a
+///   [HttpResponse::Created()] response with the stored [Promocode].
+/// - If the stored [Promocode] has the same content, nothing is written and it
+///   returns an [Ok] response with the stored [Promocode]. Retrying a request
+///   is therefore harmless.
+/// - Otherwise, the [Promocode] is replaced, its version incremented, and it
+///   returns an [Ok] response with the stored [Promocode].
+///
+/// The `created_at`, `updated_at` and `version` metadata are maintained by
+/// the server, whatever was sent.
+#[put("/promocodes/{id}")]
+pub async fn put_promocode_resource(
+    _: Authorized<scope::Write>,
+    repository: State<Repository>,
+    name_policy: State<NamePolicy>,
+    request: HttpRequest,
+    id: Path<String>,
+    promocode_json: Json<Promocode>,
+) -> HttpResponse {
+    let promocode = match promocode_json.into_inner().normalize_name(&name_policy) {
+        Ok(promocode) => promocode,
+        Err(err) => return HttpResponse::BadRequest().json(&err),
+    };
+    if promocode._id() != *id {
+        return HttpResponse::BadRequest().json(&format!(
+            "`_id` `{}` does not match the path id `{}`",
+            promocode._id(),
+            *id
+        ));
+    }
+
+    let stored = match repository.get_by_id(&id) {
+        Ok(stored) => stored,
+        Err(err) => return repository_error_response(err),
+    };
+    if let Some(response) = check_if_match(&request, stored.as_ref()) {
+        return response;
+    }
+
+    match stored {
+        None => {
+            let mut promocode = promocode;
+            promocode.metadata.mark_created(Utc::now());
+            match repository.insert(promocode.clone()) {
+                Ok(_) => promocode_response(StatusCode::CREATED, &promocode),
+                Err(err) => repository_error_response(err),
+            }
+        },
+        Some(stored) => replace(&repository, stored, promocode),
+    }
+}
+
+/// Handler for partially update the [Promocode] with the given id.
+///
+/// The body is a JSON merge patch ([RFC 7396](https://www.rfc-editor.org/rfc/rfc7396)):
+/// its fields replace the ones of the stored [Promocode], a `null` field
+/// removes it.
+///
+/// # Arguments
+///
+/// - `repository`: The [Repository] of the application.
+/// - `name_policy`: The [NamePolicy] of the application.
+/// - `id`: The id of the [Promocode].
+/// - `patch`: JSON merge patch applied to the [Promocode].
+///
+/// # Returns
+///
+/// - If there is no [Promocode] with this id, it returns a
+///   [HttpResponse::NotFound()].
+/// - If the `If-Match` header does not match the stored [Promocode], it
+///   returns a [HttpResponse::PreconditionFailed()].
+/// - If the patched [Promocode] is not valid, or if the patch modifies its
+///   `_id`, it returns a [HttpResponse::BadRequest()].
+/// - Otherwise, it returns an [Ok] response with the stored [Promocode], as
+///   [put_promocode_resource] does.
+#[patch("/promocodes/{id}")]
+pub async fn patch_promocode_resource(
+    _: Authorized<scope::Write>,
+    repository: State<Repository>,
+    name_policy: State<NamePolicy>,
+    request: HttpRequest,
+    id: Path<String>,
+    patch: Json<Value>,
+) -> HttpResponse {
+    let stored = match repository.get_by_id(&id) {
+        Ok(Some(stored)) => stored,
+        Ok(None) => return not_found(&id),
+        Err(err) => return repository_error_response(err),
+    };
+    if let Some(response) = check_if_match(&request, Some(&stored)) {
+        return response;
+    }
+
+    let mut value = match serde_json::to_value(&stored) {
+        Ok(value) => value,
+        Err(err) => return HttpResponse::InternalServerError().json(&err.to_string()),
+    };
+    merge_patch(&mut value, &patch);
+
+    let promocode = match serde_json::from_value::<Promocode>(value).map_err(|err| err.to_string()) {
+        Ok(promocode) if promocode._id() != *id => return HttpResponse::BadRequest().json(&"`_id` cannot be modified"),
+        Ok(promocode) => promocode.normalize_name(&name_policy),
+        Err(err) => Err(err),
+    };
+
+    match promocode {
+        Ok(promocode) => replace(&repository, stored, promocode),
+        Err(err) => HttpResponse::BadRequest().json(&err),
+    }
+}
+
+/// Replaces `stored` by `promocode`, unless they have the same content.
+fn replace(repository: &Repository, stored: Promocode, mut promocode: Promocode) -> HttpResponse {
+    if promocode.same_content(&stored) {
+        return promocode_response(StatusCode::OK, &stored);
+    }
+
+    promocode.metadata.inherit(&stored.metadata);
+    promocode.metadata.mark_updated(Utc::now());
+
+    match repository.update(promocode.clone()) {
+        Ok(_) => promocode_response(StatusCode::OK, &promocode),
+        Err(err) => repository_error_response(err),
+    }
+}
+
+/// Returns the entity tag of `promocode`, its quoted version.
+fn etag(promocode: &Promocode) -> String {
+    format!("\"{}\"", promocode.metadata.version())
+}
+
+/// Returns an HTTP 412 error if the `If-Match` header of `request` does not
+/// match `stored`, the [Promocode] the request would modify.
+///
+/// `If-Match: *` only matches an existing [Promocode], as per
+/// [RFC 9110](https://www.rfc-editor.org/rfc/rfc9110#field.if-match).
+fn check_if_match(request: &HttpRequest, stored: Option<&Promocode>) -> Option<HttpResponse> {
+    let if_match = request.headers().get(header::IF_MATCH)?;
+
+    let matches = match (if_match.to_str(), stored) {
+        (Ok(if_match), Some(stored)) => {
+            let etag = etag(stored);
+            if_match
+                .split(',')
+                .map(str::trim)
+                .any(|tag| tag == "*" || tag == etag)
+        },
+        _ => false,
+    };
+
+    if matches {
+        None
+    } else {
+        Some(HttpResponse::PreconditionFailed().json(&"The promocode was modified meanwhile, or does not exist."))
+    }
+}
+
+/// Returns the version of `stored` the write must be based on: the one
+/// matched by the `If-Match` header of `request`, if it names an `ETag` and
+/// not `*`.
+fn if_match_version(request: &HttpRequest, stored: &Promocode) -> Option<u64> {
+    let if_match = request.headers().get(header::IF_MATCH)?.to_str().ok()?;
+
+    (!if_match.split(',').any(|tag| tag.trim() == "*")).then(|| stored.metadata.version())
+}
+
+/// Returns an HTTP 404 error for the missing [Promocode] with the given id.
+fn not_found(id: &str) -> HttpResponse {
+    HttpResponse::NotFound().json(&format!("Promocode with id `{}` does not exist.", id))
+}
+
+/// Returns a response with `promocode` as body and its `ETag`.
+fn promocode_response(status: StatusCode, promocode: &Promocode) -> HttpResponse {
+    HttpResponse::build(status)
+        .header(header::ETAG, etag(promocode))
+        .json(promocode)
+}
+
+/// Applies the JSON merge `patch` to `target`.
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use ntex::web::{
+    post,
+    types::{Json, State},
+    HttpResponse, ServiceConfig,
+};
+
+use crate::{
+    auth::{scope, Authorized},
+    signing_key::SigningKey,
+};
+use promocode_models::signed_promocode::SignedPromocode;
+
+/// Register the `post_signed_promocode` service to the given `ServiceConfig`.
+///
+/// # Arguments
+///
+/// - `cfg` - A mutable reference to the `ServiceConfig` to register the service
+///   with.
+///
+pub fn signed_promocode_services(cfg: &mut ServiceConfig) {
+    cfg.service(post_signed_promocode);
+}
+
+/// Handler for issuing a [SignedPromocode].
+///
+/// Nothing is stored: the returned code carries its own avantage, expiry and
+/// restrictions, and is verified with the signing key on validation.
+///
+/// # Arguments
+///
+/// - `signing_key`: The [SigningKey] of the application.
+/// - `signed_promocode_json`: JSON payload containing the [SignedPromocode]
+///   details.
+///
+/// # Returns
+///
+/// - If the signing key is set, it returns an [Ok] response with the
+///   encoded code as a JSON string.
+/// - Otherwise, it returns a [HttpResponse::InternalServerError()] response
+///   with an error message.
+#[post("/promocode/sign")]
+pub async fn post_signed_promocode(_: Authorized<scope::Write>, signing_key: State<SigningKey>, signed_promocode_json: Json<SignedPromocode>) -> HttpResponse {
+    match signing_key.get() {
+        None => HttpResponse::InternalServerError().json(&"Signing key is not set."),
+        Some(key) => HttpResponse::Ok().json(&signed_promocode_json.encode(key)),
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::HashSet;
+
+use chrono::Utc;
+use ntex::web::{
+    post,
+    types::{Json, State},
+    HttpResponse, ServiceConfig,
+};
+
+use super::repository_error_response;
+use crate::{
+    auth::{scope, Authorized},
+    repository::Repository,
+};
+use promocode_models::{
+    promocode::name_policy::NamePolicy,
+    voucher_batch::{GeneratedVoucherBatch, VoucherBatch},
+};
+
+/// Register the `post_voucher_batch` service to the given `ServiceConfig`.
+///
+/// # Arguments
+///
+/// - `cfg` - A mutable reference to the `ServiceConfig` to register the service
+///   with.
+///
+pub fn voucher_batch_services(cfg: &mut ServiceConfig) {
+    cfg.service(post_voucher_batch);
+}
+
+/// Handler for generating and storing a [VoucherBatch].
+///
+/// The generated names are normalized by the [NamePolicy], and never collide
+/// with the names already in the [Repository]. When the check digit of the
+/// [NamePolicy] applies to the generated names, the generator must embed one
+/// with the same alphabet, so that the names can be redeemed.
+///
+/// # Arguments
+///
+/// - `repository`: The [Repository] of the application.
+/// - `name_policy`: The [NamePolicy] of the application.
+/// - `voucher_batch_json`: JSON payload containing the [VoucherBatch] details.
+///
+/// # Returns
+///
+/// - If the batch was generated and stored, it returns an [Ok] response with
+///   a [GeneratedVoucherBatch] listing the stored names.
+/// - If the codes cannot be generated, it returns a
+///   [HttpResponse::BadRequest()] response with the error message.
+/// - If a code was stored meanwhile by another request, it returns a
+///   [HttpResponse::Conflict()] response with the conflicting field.
+///
+/// Nothing is stored on error.
+#[post("/promocode/batch")]
+pub async fn post_voucher_batch(
+    _: Authorized<scope::Write>,
+    repository: State<Repository>,
+    name_policy: State<NamePolicy>,
+    voucher_batch_json: Json<VoucherBatch>,
+) -> HttpResponse {
+    let mut rng = rand::thread_rng();
+
+    if let Some(check_digit) = name_policy.check_digit() {
+        let applies = name_policy
+            .normalize(&voucher_batch_json.generator.generate_one(&mut rng))
+            .is_ok_and(|name| check_digit.applies_to(&name));
+        match voucher_batch_json.generator.check_digit() {
+            None if applies => {
+                return HttpResponse::BadRequest().json(&"`generator` > `check_digit` must be set, the name policy expects one.");
+            },
+            Some(generator_check_digit) if generator_check_digit.alphabet() != check_digit.alphabet() => {
+                return HttpResponse::BadRequest().json(&format!(
+                    "`generator` > `alphabet` must be `{}` to embed a check digit.",
+                    check_digit.alphabet()
+                ));
+            },
+            _ => {},
+        }
+    }
+
+    let mut generated_names = HashSet::new();
+    let mut repository_error = None;
+
+    let generated = voucher_batch_json.generate(&mut rng, |code| {
+        if repository_error.is_some() {
+            return false;
+        }
+        let name = match name_policy.normalize(code) {
+            Ok(name) => name,
+            Err(_) => return false,
+        };
+        match repository.get_by_name(&name) {
+            Ok(None) => generated_names.insert(name),
+            Ok(Some(_)) => false,
+            Err(err) => {
+                repository_error = Some(err);
+                false
+            },
+        }
+    });
+    if let Some(err) = repository_error {
+        return repository_error_response(err);
+    }
+
+    let now = Utc::now();
+    let promocodes = match generated.and_then(|promocodes| {
+        promocodes
+            .into_iter()
+            .map(|promocode| {
+                promocode.normalize_name(&name_policy).map(|mut promocode| {
+                    promocode.metadata.mark_created(now);
+                    promocode
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()
+    }) {
+        Ok(promocodes) => promocodes,
+        Err(err) => return HttpResponse::BadRequest().json(&err),
+    };
+
+    let generated_voucher_batch = GeneratedVoucherBatch {
+        campaign: voucher_batch_json.campaign(),
+        codes: promocodes.iter().map(|it| it.name()).collect(),
+    };
+
+    match repository.insert_batch(promocodes) {
+        Ok(_) => HttpResponse::Ok().json(&generated_voucher_batch),
+        Err(err) => repository_error_response(err),
+    }
+}
@@ -0,0 +1,38 @@
+use ntex::web::{get, types::State, HttpResponse, ServiceConfig};
+
+use crate::{
+    auth::{scope, Authorized},
+    weather::Weather,
+};
+
+/// Register the `get_weather_cache_stats` service to the given
+/// `ServiceConfig`.
+///
+/// # Arguments
+///
+/// - `cfg` - A mutable reference to the `ServiceConfig` to register the service
+///   with.
+///
+pub fn weather_stats_services(cfg: &mut ServiceConfig) {
+    cfg.service(get_weather_cache_stats);
+}
+
+/// Handler for fetch the hit, miss and coalesced lookup counters of the
+/// weather cache.
+///
+/// # Arguments
+///
+/// - `weather`: The [Weather] of the application.
+///
+/// # Returns
+///
+/// - If the weather provider has a cache, it returns an [Ok] response with
+///   its [WeatherCacheStats](crate::weather::cache::WeatherCacheStats).
+/// - Otherwise, it returns a [HttpResponse::NotFound()].
+#[get("/weather/cache")]
+pub async fn get_weather_cache_stats(_: Authorized<scope::Read>, weather: State<Weather>) -> HttpResponse {
+    match weather.cache_stats() {
+        Some(stats) => HttpResponse::Ok().json(&stats),
+        None => HttpResponse::NotFound().json(&"The weather is not cached."),
+    }
+}
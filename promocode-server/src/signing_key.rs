@@ -0,0 +1,30 @@
+use std::sync::Arc;
+
+/// Key used to sign and verify the
+/// [SignedPromocode](promocode_models::signed_promocode::SignedPromocode)s,
+/// shared through the ntex application state.
+///
+/// All signed promocodes are denied with the [default](Self::default) key,
+/// which is not set.
+#[derive(Clone, Default)]
+pub struct SigningKey(Option<Arc<[u8]>>);
+
+impl SigningKey {
+    /// Create a new [`SigningKey`](Self)
+    ///
+    /// # Errors
+    ///
+    /// This function fails if `key` is empty.
+    pub fn new(key: String) -> Result<Self, String /* Error */> {
+        if key.is_empty() {
+            return Err("Signing key cannot be empty.".to_string());
+        }
+
+        Ok(Self(Some(Arc::from(key.into_bytes()))))
+    }
+
+    /// Returns the key, or [None] if it is not set.
+    pub fn get(&self) -> Option<&[u8]> {
+        self.0.as_deref()
+    }
+}
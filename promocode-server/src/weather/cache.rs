@@ -0,0 +1,297 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use promocode_models::forecast::Forecast;
+use serde::Serialize;
+use tokio::sync::OnceCell;
+
+use super::{Location, WeatherFuture, WeatherProvider};
+
+/// Counters of a [TtlCache].
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct CacheStats {
+    /// Lookups answered by a fresh value.
+    pub hits: u64,
+    /// Lookups which fetched the value.
+    pub misses: u64,
+    /// Lookups which waited for the fetch of another one.
+    pub coalesced: u64,
+    /// Lookups answered by an expired value, as fetching it again failed.
+    pub stale: u64,
+    /// Values removed to respect the size bound.
+    pub evictions: u64,
+    /// Number of values, fresh, expired or being fetched.
+    pub entries: usize,
+}
+
+/// Value of a [TtlCache], set once fetched with the instant of the fetch.
+type Slot<V> = Arc<OnceCell<(Result<V, String>, Instant)>>;
+
+/// Entry of a [TtlCache].
+#[derive(Debug)]
+struct Entry<V> {
+    slot: Slot<V>,
+    /// Previous value, and the instant of its fetch, while `slot` is fetched
+    /// again.
+    stale: Option<(V, Instant)>,
+}
+
+/// Cache of values expiring `ttl` after their fetch, holding at most
+/// `max_entries` values.
+///
+/// Concurrent lookups of a missing key share a single fetch. Errors are not
+/// cached, but may be answered by the expired value, see
+/// [with_stale_if_error](Self::with_stale_if_error).
+#[derive(Debug)]
+pub struct TtlCache<K, V> {
+    ttl: Duration,
+    max_entries: usize,
+    stale_if_error: bool,
+    entries: Mutex<HashMap<K, Entry<V>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    coalesced: AtomicU64,
+    stale: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> TtlCache<K, V> {
+    /// Create a new [`TtlCache`](Self). A `max_entries` of `0` is read as `1`.
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            ttl,
+            max_entries: max_entries.max(1),
+            stale_if_error: false,
+            entries: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            coalesced: AtomicU64::new(0),
+            stale: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns this [TtlCache], answering with the expired value of a key when
+    /// fetching it again fails, if `stale_if_error`.
+    pub fn with_stale_if_error(self, stale_if_error: bool) -> Self {
+        Self {
+            stale_if_error,
+            ..self
+        }
+    }
+
+    /// Returns the value of `key`, fetched with `fetch` if it is missing or
+    /// expired.
+    pub async fn get_or_fetch<'a>(&self, key: K, fetch: impl FnOnce() -> WeatherFuture<'a, V>) -> Result<V, String> {
+        let (slot, stale) = self.slot(&key);
+
+        let (value, _) = slot
+            .get_or_init(|| async { (fetch().await, Instant::now()) })
+            .await;
+        match value {
+            Ok(value) => Ok(value.clone()),
+            Err(err) => {
+                self.remove(&key, &slot);
+                match stale {
+                    Some((value, _)) if self.stale_if_error => {
+                        self.stale.fetch_add(1, Ordering::Relaxed);
+                        Ok(value)
+                    },
+                    _ => Err(err.clone()),
+                }
+            },
+        }
+    }
+
+    /// Returns the current counters.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            coalesced: self.coalesced.load(Ordering::Relaxed),
+            stale: self.stale.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            entries: self
+                .entries
+                .lock()
+                .map(|entries| entries.len())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns the slot of `key` and its previous value, replacing the slot if
+    /// it is expired or failed.
+    fn slot(&self, key: &K) -> (Slot<V>, Option<(V, Instant)>) {
+        let mut entries = self.entries.lock().unwrap_or_else(|err| err.into_inner());
+
+        let mut stale = None;
+        if let Some(entry) = entries.get(key) {
+            match entry.slot.get() {
+                None => {
+                    self.coalesced.fetch_add(1, Ordering::Relaxed);
+                    return (entry.slot.clone(), entry.stale.clone());
+                },
+                Some((Ok(_), fetched_at)) if fetched_at.elapsed() < self.ttl => {
+                    self.hits.fetch_add(1, Ordering::Relaxed);
+                    return (entry.slot.clone(), None);
+                },
+                Some((Ok(value), fetched_at)) => stale = Some((value.clone(), *fetched_at)),
+                Some((Err(_), _)) => stale = entry.stale.clone(),
+            }
+            entries.remove(key);
+        }
+
+        if entries.len() >= self.max_entries {
+            self.evict(&mut entries);
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let slot: Slot<V> = Arc::new(OnceCell::new());
+        entries.insert(
+            key.clone(),
+            Entry {
+                slot: slot.clone(),
+                stale: stale.clone(),
+            },
+        );
+        (slot, stale)
+    }
+
+    /// Removes the expired values, or else the oldest one.
+    fn evict(&self, entries: &mut HashMap<K, Entry<V>>) {
+        let before = entries.len();
+        entries.retain(|_, entry| {
+            entry
+                .slot
+                .get()
+                .is_none_or(|(_, fetched_at)| fetched_at.elapsed() < self.ttl)
+        });
+
+        if entries.len() == before {
+            let oldest = entries
+                .iter()
+                .filter_map(|(key, entry)| entry.slot.get().map(|(_, fetched_at)| (key, *fetched_at)))
+                .min_by_key(|(_, fetched_at)| *fetched_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+
+        self.evictions
+            .fetch_add((before - entries.len()) as u64, Ordering::Relaxed);
+    }
+
+    /// Removes the failed `slot` from the cache, unless `key` was fetched again
+    /// meanwhile. Its previous value is kept, expired, if
+    /// [stale_if_error](Self::with_stale_if_error).
+    fn remove(&self, key: &K, slot: &Slot<V>) {
+        let mut entries = self.entries.lock().unwrap_or_else(|err| err.into_inner());
+        let Some(entry) = entries
+            .get_mut(key)
+            .filter(|it| Arc::ptr_eq(&it.slot, slot))
+        else {
+            return;
+        };
+
+        match entry.stale.take() {
+            Some((value, fetched_at)) if self.stale_if_error => {
+                entry.slot = Arc::new(OnceCell::new_with(Some((Ok(value), fetched_at))));
+            },
+            _ => {
+                entries.remove(key);
+            },
+        }
+    }
+}
+
+/// Counters of a [CachedWeatherProvider].
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct WeatherCacheStats {
+    pub geocoding: CacheStats,
+    pub weather: CacheStats,
+}
+
+/// [WeatherProvider] caching the [Location]s of the towns and the
+/// [Forecast] at the locations of another one.
+pub struct CachedWeatherProvider {
+    inner: Box<dyn WeatherProvider>,
+    locations: TtlCache<String, Option<Location>>,
+    weathers: TtlCache<(i64, i64), Forecast>,
+}
+
+impl CachedWeatherProvider {
+    /// Create a new [`CachedWeatherProvider`](Self) in front of `inner`.
+    ///
+    /// # Arguments
+    ///
+    /// - `geocoding_ttl` - How long a town keeps its [Location] (unknown
+    ///   towns included).
+    /// - `weather_ttl` - How long a [Location] keeps its [Forecast].
+    /// - `max_entries` - Maximum number of towns, and of locations.
+    pub fn new(inner: Box<dyn WeatherProvider>, geocoding_ttl: Duration, weather_ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            inner,
+            locations: TtlCache::new(geocoding_ttl, max_entries),
+            weathers: TtlCache::new(weather_ttl, max_entries),
+        }
+    }
+
+    /// Returns this [CachedWeatherProvider], answering with the expired
+    /// values when `inner` fails (e.g. when its quota is exhausted), if
+    /// `stale_if_error`.
+    pub fn with_stale_if_error(self, stale_if_error: bool) -> Self {
+        Self {
+            locations: self.locations.with_stale_if_error(stale_if_error),
+            weathers: self.weathers.with_stale_if_error(stale_if_error),
+            ..self
+        }
+    }
+
+    /// Returns `town` trimmed, lowercased, with single spaces.
+    fn town_key(town: &str) -> String {
+        town.split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+            .to_lowercase()
+    }
+
+    /// Returns the coordinates of `location`, rounded to about 10 meters.
+    fn location_key(location: &Location) -> (i64, i64) {
+        (
+            (location.lat * 10_000.0).round() as i64,
+            (location.lon * 10_000.0).round() as i64,
+        )
+    }
+}
+
+impl WeatherProvider for CachedWeatherProvider {
+    fn geocode<'a>(&'a self, town: &'a str) -> WeatherFuture<'a, Option<Location>> {
+        Box::pin(
+            self.locations
+                .get_or_fetch(Self::town_key(town), || self.inner.geocode(town)),
+        )
+    }
+
+    fn forecast<'a>(&'a self, location: &'a Location) -> WeatherFuture<'a, Forecast> {
+        Box::pin(
+            self.weathers
+                .get_or_fetch(Self::location_key(location), || {
+                    self.inner.forecast(location)
+                }),
+        )
+    }
+
+    fn cache_stats(&self) -> Option<WeatherCacheStats> {
+        Some(WeatherCacheStats {
+            geocoding: self.locations.stats(),
+            weather: self.weathers.stats(),
+        })
+    }
+}
@@ -0,0 +1,117 @@
+use std::{fs, path::Path};
+
+use chrono::{DateTime, Duration, Utc};
+use promocode_models::forecast::{Forecast, ForecastStep, Observation};
+use serde::{Deserialize, Serialize};
+
+use super::{Location, WeatherFuture, WeatherProvider};
+
+/// Weather of a town, in a [WeatherFixtures] file.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct TownFixture {
+    pub name: String,
+    pub lat: f64,
+    pub lon: f64,
+    /// Current weather, its main condition in any case (e.g. `Clear`,
+    /// `Rain`).
+    #[serde(flatten)]
+    pub current: Observation,
+    /// Weather of the next 3-hour steps, after the current one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub forecast: Vec<Observation>,
+}
+
+impl TownFixture {
+    /// Returns `true` if `town` is the name of this fixture, ignoring case and
+    /// surrounding whitespaces.
+    pub fn is_named(&self, town: &str) -> bool {
+        self.name.trim().to_lowercase() == town.trim().to_lowercase()
+    }
+
+    /// Returns `true` if `location` is at the coordinates of this fixture.
+    pub fn is_at(&self, location: &Location) -> bool {
+        self.lat == location.lat && self.lon == location.lon
+    }
+
+    /// Returns the [ForecastStep]s of this fixture, the current one at `now`
+    /// and the next ones every 3 hours.
+    pub fn steps(&self, now: DateTime<Utc>) -> Vec<ForecastStep> {
+        std::iter::once(&self.current)
+            .chain(&self.forecast)
+            .enumerate()
+            .map(|(index, observation)| ForecastStep {
+                at: now + Duration::hours(3 * index as i64),
+                observation: Observation {
+                    condition: observation.condition.to_lowercase(),
+                    ..observation.clone()
+                },
+            })
+            .collect()
+    }
+}
+
+/// Content of a fixtures file, e.g.
+/// `{"towns": [{"name": "Lyon", "lat": 45.75, "lon": 4.85, "condition": "Clear", "temp": 25.0}]}`.
+///
+/// The `forecast` of a town, e.g. `[{"condition": "Rain", "temp": 18.0}]`,
+/// holds its next 3-hour steps. The `feels_like`, `humidity`, `wind` and
+/// `rain` metrics are optional.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct WeatherFixtures {
+    pub towns: Vec<TownFixture>,
+}
+
+impl WeatherFixtures {
+    /// Reads [WeatherFixtures] from a JSON file.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the file cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|err| format!("Cannot read `{}`: {}", path.display(), err))?;
+        serde_json::from_str(&content).map_err(|err| format!("Cannot parse `{}`: {}", path.display(), err))
+    }
+
+    /// Returns the fixture of `town`, if any.
+    pub fn town(&self, town: &str) -> Option<&TownFixture> {
+        self.towns.iter().find(|it| it.is_named(town))
+    }
+}
+
+/// [WeatherProvider] answering from [WeatherFixtures], without network, for
+/// tests and offline demos.
+#[derive(Clone, Debug, Default)]
+pub struct FixturesWeatherProvider {
+    fixtures: WeatherFixtures,
+}
+
+impl FixturesWeatherProvider {
+    /// Create a new [`FixturesWeatherProvider`](Self).
+    pub fn new(fixtures: WeatherFixtures) -> Self {
+        Self { fixtures }
+    }
+}
+
+impl WeatherProvider for FixturesWeatherProvider {
+    fn geocode<'a>(&'a self, town: &'a str) -> WeatherFuture<'a, Option<Location>> {
+        let location = self.fixtures.town(town).map(|fixture| Location {
+            name: fixture.name.clone(),
+            lat: fixture.lat,
+            lon: fixture.lon,
+        });
+
+        Box::pin(async move { Ok(location) })
+    }
+
+    fn forecast<'a>(&'a self, location: &'a Location) -> WeatherFuture<'a, Forecast> {
+        let forecast = match self.fixtures.towns.iter().find(|it| it.is_at(location)) {
+            Some(fixture) => Forecast::new(fixture.steps(Utc::now())),
+            None => Err(format!(
+                "No weather fixture at ({}, {}).",
+                location.lat, location.lon
+            )),
+        };
+
+        Box::pin(async move { forecast })
+    }
+}
@@ -0,0 +1,149 @@
+//! Services answering as the geocoding and forecast endpoints of the
+//! OpenWeather API do, from [WeatherFixtures], to test the
+//! [OpenWeatherProvider](super::open_weather_sdk::OpenWeatherProvider)
+//! offline. They are served by the `weather-mock` binary.
+
+use chrono::Utc;
+use ntex::web::{
+    get,
+    types::{Query, State},
+    HttpResponse, ServiceConfig,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::fixtures::{TownFixture, WeatherFixtures};
+
+/// Registers the mock services to the given `ServiceConfig`.
+///
+/// The [WeatherFixtures] must be registered in the application state.
+pub fn mock_services(cfg: &mut ServiceConfig) {
+    cfg.service(get_geocoding);
+    cfg.service(get_forecast);
+}
+
+/// Query parameters of [get_geocoding].
+#[derive(Deserialize, Debug)]
+pub struct GeocodingQuery {
+    q: String,
+    limit: Option<usize>,
+    appid: Option<String>,
+}
+
+/// Query parameters of [get_forecast].
+#[derive(Deserialize, Debug)]
+pub struct ForecastQuery {
+    lat: f64,
+    lon: f64,
+    cnt: Option<usize>,
+    appid: Option<String>,
+}
+
+/// Mock of `GET /geo/1.0/direct`, returning the fixture of the town `q`, if
+/// any.
+#[get("/geo/1.0/direct")]
+async fn get_geocoding(fixtures: State<WeatherFixtures>, query: Query<GeocodingQuery>) -> HttpResponse {
+    if let Some(response) = check_api_key(&query.appid) {
+        return response;
+    }
+
+    // The town is the first part of `city,state,country`.
+    let town = query.q.split(',').next().unwrap_or_default();
+    let locations: Vec<Value> = fixtures
+        .town(town)
+        .map(geocoding_json)
+        .into_iter()
+        .take(query.limit.unwrap_or(5))
+        .collect();
+
+    HttpResponse::Ok().json(&locations)
+}
+
+/// Mock of `GET /data/2.5/forecast`, returning the weather of the fixture at
+/// `lat` and `lon`.
+#[get("/data/2.5/forecast")]
+async fn get_forecast(fixtures: State<WeatherFixtures>, query: Query<ForecastQuery>) -> HttpResponse {
+    if let Some(response) = check_api_key(&query.appid) {
+        return response;
+    }
+
+    let fixture = fixtures
+        .towns
+        .iter()
+        .find(|it| it.lat == query.lat && it.lon == query.lon);
+    match fixture {
+        None => HttpResponse::NotFound().json(&json!({"cod": 404, "message": "city not found"})),
+        Some(fixture) => HttpResponse::Ok().json(&forecast_json(fixture, query.cnt.unwrap_or(40))),
+    }
+}
+
+/// Returns an HTTP 401 error, as the API does, when no API key is given.
+fn check_api_key(appid: &Option<String>) -> Option<HttpResponse> {
+    match appid {
+        Some(appid) if !appid.trim().is_empty() => None,
+        _ => Some(HttpResponse::Unauthorized().json(&json!({"cod": 401, "message": "Invalid API key."}))),
+    }
+}
+
+fn geocoding_json(fixture: &TownFixture) -> Value {
+    json!({
+        "name": fixture.name,
+        "lat": fixture.lat,
+        "lon": fixture.lon,
+        "country": "",
+    })
+}
+
+fn forecast_json(fixture: &TownFixture, count: usize) -> Value {
+    let list: Vec<Value> = fixture
+        .steps(Utc::now())
+        .into_iter()
+        .take(count)
+        .map(|step| {
+            let observation = step.observation;
+            let mut json = json!({
+                "dt": step.at.timestamp(),
+                "main": {
+                    "temp": observation.temp,
+                    "feels_like": observation.feels_like.unwrap_or(observation.temp),
+                    "temp_min": observation.temp,
+                    "temp_max": observation.temp,
+                    "pressure": 1013,
+                    "sea_level": 1013,
+                    "grnd_level": 1013,
+                    "humidity": observation.humidity.unwrap_or(50.0).round() as u64,
+                    "temp_kf": 0.0,
+                },
+                "weather": [{"id": 800, "main": observation.condition, "description": observation.condition, "icon": "01d"}],
+                "clouds": {"all": 0},
+                "wind": {"speed": observation.wind.unwrap_or_default(), "deg": 0},
+                "visibility": 10000,
+                "pop": 0.0,
+                "sys": {"pod": "d"},
+                "dt_txt": step.at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            });
+            // As the API, no rain volume when it does not rain.
+            if let Some(rain) = observation.rain.filter(|rain| *rain > 0.0) {
+                json["rain"] = json!({"3h": rain});
+            }
+            json
+        })
+        .collect();
+
+    json!({
+        "cod": "200",
+        "message": 0,
+        "cnt": list.len(),
+        "list": list,
+        "city": {
+            "id": 0,
+            "name": fixture.name,
+            "coord": {"lat": fixture.lat, "lon": fixture.lon},
+            "country": "",
+            "population": 0,
+            "timezone": 0,
+            "sunrise": 0,
+            "sunset": 0,
+        },
+    })
+}
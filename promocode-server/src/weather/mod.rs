@@ -0,0 +1,95 @@
+//! Weather of the towns, checked by the
+//! [Restriction::Meteo](promocode_models::promocode::restriction::Restriction::Meteo)s.
+//!
+//! The weather comes from a [WeatherProvider], built at startup and shared by
+//! the application as [Weather].
+
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use cache::WeatherCacheStats;
+use clap::ValueEnum;
+use log::error;
+use promocode_models::forecast::Forecast;
+use serde::{Deserialize, Serialize};
+
+pub mod cache;
+pub mod fixtures;
+pub mod mock;
+pub mod open_weather_sdk;
+pub mod quota;
+pub mod resilience;
+
+/// Future returned by the [WeatherProvider] methods, failing with an error
+/// message.
+pub type WeatherFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, String>> + 'a>>;
+
+/// A geocoded town.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Location {
+    pub name: String,
+    pub lat: f64,
+    pub lon: f64,
+}
+
+/// Source of the weather.
+pub trait WeatherProvider: Send + Sync {
+    /// Returns the [Location] of `town`, or [None] if it is unknown.
+    fn geocode<'a>(&'a self, town: &'a str) -> WeatherFuture<'a, Option<Location>>;
+
+    /// Returns the [Forecast] at `location`, its first step being the
+    /// current weather.
+    fn forecast<'a>(&'a self, location: &'a Location) -> WeatherFuture<'a, Forecast>;
+
+    /// Returns the counters of the cache, if this provider has one.
+    fn cache_stats(&self) -> Option<WeatherCacheStats> {
+        None
+    }
+}
+
+/// Kind of [WeatherProvider].
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WeatherProviderKind {
+    /// The OpenWeather API, with `OPEN_WEATHER_MAP_API_KEY`.
+    OpenWeather,
+    /// The `--weather-fixtures` file, for tests and offline demos.
+    Fixtures,
+}
+
+/// [WeatherProvider] shared by the application.
+pub type Weather = Arc<dyn WeatherProvider>;
+
+/// [WeatherProvider] of a server without weather (e.g. without API key), so
+/// that the weather is always unavailable.
+#[derive(Default, Debug)]
+pub struct NoWeatherProvider;
+
+impl WeatherProvider for NoWeatherProvider {
+    fn geocode<'a>(&'a self, _: &'a str) -> WeatherFuture<'a, Option<Location>> {
+        Box::pin(async { Err("No weather provider configured.".to_string()) })
+    }
+
+    fn forecast<'a>(&'a self, _: &'a Location) -> WeatherFuture<'a, Forecast> {
+        Box::pin(async { Err("No weather provider configured.".to_string()) })
+    }
+}
+
+/// Retrieves the weather forecast in `town` from `provider`.
+///
+/// # Returns
+///
+/// A [Result] with the [Forecast], or [None] if `town` is unknown.
+///
+/// # Errors
+///
+/// This function fails if the weather is unavailable.
+pub async fn fetch_forecast(provider: &dyn WeatherProvider, town: &str) -> Result<Option<Forecast>, String> {
+    let location = match provider.geocode(town).await? {
+        Some(location) => location,
+        None => {
+            error!("No location found for `{}`!", town);
+            return Ok(None);
+        },
+    };
+
+    provider.forecast(&location).await.map(Some)
+}
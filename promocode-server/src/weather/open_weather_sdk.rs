@@ -0,0 +1,161 @@
+use chrono::DateTime;
+use openweather_sdk::responses::{response_handler, ForecastResponse, GeocodingResponse};
+use promocode_models::forecast::{Forecast, ForecastStep, Observation};
+
+use super::{Location, WeatherFuture, WeatherProvider};
+
+/// Base URL of the OpenWeather API.
+pub const DEFAULT_OPEN_WEATHER_BASE_URL: &str = "https://api.openweathermap.org";
+
+/// Number of 3-hour steps of the forecast fetched, the 5 days of the free
+/// plan.
+pub const FORECAST_STEPS: u8 = 40;
+
+/// Client of the geocoding and forecast endpoints of the OpenWeather API, or
+/// of any server answering as it does at `base_url` (e.g. the `weather-mock`
+/// binary).
+#[derive(Clone, Debug)]
+pub struct OpenWeatherClient {
+    api_key: String,
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl OpenWeatherClient {
+    /// Create a new [`OpenWeatherClient`](Self), querying `base_url` (e.g.
+    /// [DEFAULT_OPEN_WEATHER_BASE_URL]) with `api_key`.
+    ///
+    /// It can be called for each server instance, the returned client being
+    /// shared by its workers through an [OpenWeatherProvider].
+    ///
+    /// # Returns
+    ///
+    /// Returns a [Result] with an [Error] message in case the API key is
+    /// empty, or [Ok] with the new [OpenWeatherClient].
+    pub fn new(api_key: String, base_url: String) -> Result<Self, String /* Error */> {
+        if api_key.trim().is_empty() {
+            return Err("OpenWeather client needs an API key.".to_string());
+        }
+        Ok(Self::new_unchecked(api_key, base_url))
+    }
+
+    /// Create a new [`OpenWeatherClient`](Self) without checking `api_key`.
+    pub fn new_unchecked(api_key: String, base_url: String) -> Self {
+        Self {
+            api_key,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Returns the base URL of the API.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Fetches at most `limit` locations named `town`.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the request fails or the API returns an error.
+    pub async fn geocoding(&self, town: &str, limit: u8) -> Result<Vec<GeocodingResponse>, String> {
+        let limit = limit.to_string();
+        let response = self
+            .http
+            .get(format!("{}/geo/1.0/direct", self.base_url))
+            .query(&[("q", town), ("limit", &limit), ("appid", &self.api_key)])
+            .send()
+            .await
+            .map_err(|err| err.to_string())?;
+
+        response_handler(response)
+            .await
+            .map_err(|err| err.to_string())
+    }
+
+    /// Fetches the next `count` 3-hour steps of the forecast at the given
+    /// coordinates, in metric units.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the request fails or the API returns an error.
+    pub async fn forecast(&self, lat: f64, lon: f64, count: u8) -> Result<ForecastResponse, String> {
+        let response = self
+            .http
+            .get(format!("{}/data/2.5/forecast", self.base_url))
+            .query(&[
+                ("lat", lat.to_string()),
+                ("lon", lon.to_string()),
+                ("cnt", count.to_string()),
+                ("units", "metric".to_string()),
+                ("appid", self.api_key.clone()),
+            ])
+            .send()
+            .await
+            .map_err(|err| err.to_string())?;
+
+        response_handler(response)
+            .await
+            .map_err(|err| err.to_string())
+    }
+}
+
+/// [WeatherProvider] querying the OpenWeather API with an [OpenWeatherClient].
+#[derive(Clone, Debug)]
+pub struct OpenWeatherProvider {
+    client: OpenWeatherClient,
+}
+
+impl OpenWeatherProvider {
+    /// Create a new [`OpenWeatherProvider`](Self), see
+    /// [OpenWeatherClient::new].
+    pub fn new(client: OpenWeatherClient) -> Self {
+        Self { client }
+    }
+}
+
+impl WeatherProvider for OpenWeatherProvider {
+    fn geocode<'a>(&'a self, town: &'a str) -> WeatherFuture<'a, Option<Location>> {
+        Box::pin(async move {
+            let geocoding_vec = self.client.geocoding(town, 1).await?;
+
+            Ok(geocoding_vec.first().map(|geocoding| Location {
+                name: geocoding.name.clone(),
+                lat: geocoding.lat,
+                lon: geocoding.lon,
+            }))
+        })
+    }
+
+    fn forecast<'a>(&'a self, location: &'a Location) -> WeatherFuture<'a, Forecast> {
+        Box::pin(async move {
+            let forecast = self
+                .client
+                .forecast(location.lat, location.lon, FORECAST_STEPS)
+                .await?;
+
+            let steps = forecast
+                .list
+                .iter()
+                .filter_map(|data| {
+                    let weather = data.weather.first()?;
+                    // No rain volume when it does not rain.
+                    let rain = data
+                        .rain
+                        .as_ref()
+                        .and_then(|rain| rain.volume_over_three_hours)
+                        .unwrap_or_default();
+                    Some(ForecastStep {
+                        at: DateTime::from_timestamp(data.datetime as i64, 0)?,
+                        observation: Observation::new(weather.main.to_lowercase(), data.main.temp)
+                            .with_feels_like(data.main.feels_like)
+                            .with_humidity(data.main.humidity as f64)
+                            .with_wind(data.wind.speed)
+                            .with_rain(rain),
+                    })
+                })
+                .collect();
+            Forecast::new(steps).map_err(|_| format!("No weather found!: {}", forecast))
+        })
+    }
+}
@@ -0,0 +1,210 @@
+//! Client-side accounting of the calls to a
+//! [WeatherProvider](super::WeatherProvider), to stay within the quota of its
+//! API.
+//!
+//! The quota is applied to each attempt of a
+//! [ResilientWeatherProvider](super::resilience::ResilientWeatherProvider).
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+/// Percentages of the monthly quota logged when reached.
+const THRESHOLDS: [u64; 4] = [50, 80, 90, 100];
+
+/// What to do with a call once the quota is exhausted.
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum QuotaPolicy {
+    /// Fail the call, so the weather restrictions are not satisfied.
+    Deny,
+    /// Make the call anyway, with a warning.
+    Allow,
+    /// Fail the call, but answer with the expired cached weather, if any.
+    Stale,
+}
+
+/// Calls made during a month, as persisted between restarts.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct MonthlyUsage {
+    /// Month of the calls, as `YYYY-MM`.
+    pub month: String,
+    pub calls: u64,
+}
+
+impl MonthlyUsage {
+    fn of(now: DateTime<Utc>) -> Self {
+        Self {
+            month: now.format("%Y-%m").to_string(),
+            calls: 0,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct QuotaState {
+    /// Calls which can be made right now.
+    tokens: f64,
+    refilled_at: DateTime<Utc>,
+    usage: MonthlyUsage,
+    /// Highest of the [THRESHOLDS] logged this month.
+    warned: u64,
+}
+
+/// Quota of `per_minute` calls, as a token bucket refilled continuously, and
+/// of `per_month` calls, counted per calendar month (UTC).
+///
+/// The monthly usage is saved to `path`, if any, after each call.
+#[derive(Debug)]
+pub struct WeatherQuota {
+    per_minute: u32,
+    per_month: u64,
+    policy: QuotaPolicy,
+    path: Option<PathBuf>,
+    state: Mutex<QuotaState>,
+}
+
+impl WeatherQuota {
+    /// Create a new [`WeatherQuota`](Self), resuming the usage saved at
+    /// `path`, if it exists. A limit of `0` disables it.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the file at `path` cannot be read or parsed.
+    pub fn open(per_minute: u32, per_month: u64, policy: QuotaPolicy, path: Option<PathBuf>) -> Result<Self, String> {
+        let now = Utc::now();
+        let usage = match &path {
+            Some(path) if path.exists() => Self::load(path)?,
+            _ => MonthlyUsage::of(now),
+        };
+
+        let quota = Self {
+            per_minute,
+            per_month,
+            policy,
+            path,
+            state: Mutex::new(QuotaState {
+                tokens: per_minute as f64,
+                refilled_at: now,
+                usage,
+                warned: 0,
+            }),
+        };
+        if let Ok(mut state) = quota.state.lock() {
+            state.warned = quota.reached(state.usage.calls);
+        }
+        Ok(quota)
+    }
+
+    /// Returns the [QuotaPolicy] applied once exhausted.
+    pub fn policy(&self) -> QuotaPolicy {
+        self.policy
+    }
+
+    /// Returns the usage of the current month.
+    pub fn usage(&self) -> MonthlyUsage {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        Self::roll(&mut state, Utc::now());
+        state.usage.clone()
+    }
+
+    /// Accounts for a call made now, see [acquire_at](Self::acquire_at).
+    pub fn acquire(&self) -> Result<(), String> {
+        self.acquire_at(Utc::now())
+    }
+
+    /// Accounts for a call made at `now`.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the quota is exhausted, unless the policy is
+    /// [QuotaPolicy::Allow].
+    pub fn acquire_at(&self, now: DateTime<Utc>) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        Self::roll(&mut state, now);
+
+        if self.per_minute > 0 {
+            let elapsed = (now - state.refilled_at).num_milliseconds().max(0) as f64 / 1000.0;
+            state.tokens = (state.tokens + elapsed * self.per_minute as f64 / 60.0).min(self.per_minute as f64);
+            state.refilled_at = now;
+        }
+
+        let exhausted = if self.per_minute > 0 && state.tokens < 1.0 {
+            Some(format!("{} calls/minute", self.per_minute))
+        } else if self.per_month > 0 && state.usage.calls >= self.per_month {
+            Some(format!("{} calls/month", self.per_month))
+        } else {
+            None
+        };
+        if let Some(exhausted) = exhausted {
+            if self.policy != QuotaPolicy::Allow {
+                return Err(format!("Weather quota of {} exhausted.", exhausted));
+            }
+            warn!(
+                "Weather quota of {} exhausted, call made anyway.",
+                exhausted
+            );
+        }
+
+        state.tokens = (state.tokens - 1.0).max(0.0);
+        state.usage.calls += 1;
+
+        let reached = self.reached(state.usage.calls);
+        if reached > state.warned {
+            state.warned = reached;
+            warn!(
+                "{}% of the monthly weather quota used ({}/{} calls in {}).",
+                reached, state.usage.calls, self.per_month, state.usage.month
+            );
+        }
+
+        if let Some(path) = &self.path {
+            if let Err(err) = Self::save(path, &state.usage) {
+                warn!("{}", err);
+            }
+        }
+        Ok(())
+    }
+
+    /// Resets the usage when the month of `now` is not the one counted.
+    fn roll(state: &mut QuotaState, now: DateTime<Utc>) {
+        let usage = MonthlyUsage::of(now);
+        if state.usage.month != usage.month {
+            state.usage = usage;
+            state.warned = 0;
+        }
+    }
+
+    /// Returns the highest of the [THRESHOLDS] reached by `calls`, or `0`.
+    fn reached(&self, calls: u64) -> u64 {
+        if self.per_month == 0 {
+            return 0;
+        }
+        THRESHOLDS
+            .into_iter()
+            .filter(|threshold| calls * 100 >= threshold * self.per_month)
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn load(path: &Path) -> Result<MonthlyUsage, String> {
+        let content = fs::read_to_string(path).map_err(|err| format!("Cannot read `{}`: {}", path.display(), err))?;
+        serde_json::from_str(&content).map_err(|err| format!("Cannot parse `{}`: {}", path.display(), err))
+    }
+
+    /// Writes `usage` to a temporary file, then renames it to `path`, so the
+    /// file is never half written.
+    fn save(path: &Path, usage: &MonthlyUsage) -> Result<(), String> {
+        let tmp = path.with_extension("tmp");
+        let content = serde_json::to_string(usage).map_err(|err| err.to_string())?;
+        fs::write(&tmp, content)
+            .and_then(|_| fs::rename(&tmp, path))
+            .map_err(|err| format!("Cannot write `{}`: {}", path.display(), err))
+    }
+}
@@ -0,0 +1,233 @@
+//! Timeouts, retries and circuit breaker around the calls to a
+//! [WeatherProvider], so a slow or failing weather API neither holds the
+//! validation requests nor gets hammered.
+
+use std::{
+    future::Future,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use log::warn;
+use ntex::time::{sleep, timeout};
+use promocode_models::forecast::Forecast;
+
+use super::{cache::WeatherCacheStats, quota::WeatherQuota, Location, WeatherFuture, WeatherProvider};
+
+/// State of a [CircuitBreaker].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CircuitState {
+    /// Calls are made.
+    Closed,
+    /// Calls fail immediately.
+    Open,
+    /// A trial call is made, closing the circuit if it succeeds.
+    HalfOpen,
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    /// Opening time replaced by the pending trial call, if any.
+    trial_of: Option<Instant>,
+}
+
+/// Circuit breaker opening after `failure_threshold` consecutive failures,
+/// for `open_for`, after which a single trial call is let through.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    open_for: Duration,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    /// Create a new [`CircuitBreaker`](Self). A `failure_threshold` of `0`
+    /// never opens it.
+    pub fn new(failure_threshold: u32, open_for: Duration) -> Self {
+        Self {
+            failure_threshold,
+            open_for,
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    /// Returns the current [CircuitState].
+    pub fn state(&self) -> CircuitState {
+        let state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        match state.opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if opened_at.elapsed() < self.open_for => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Returns [Ok] if a call can be made.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the circuit is open. Once half-open, the trial
+    /// call opens it again until recorded, so that a single call is let
+    /// through.
+    pub fn allow(&self) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        match state.opened_at {
+            None => Ok(()),
+            Some(opened_at) if opened_at.elapsed() < self.open_for => Err("Weather circuit breaker is open.".to_string()),
+            Some(opened_at) => {
+                state.trial_of = Some(opened_at);
+                state.opened_at = Some(Instant::now());
+                Ok(())
+            },
+        }
+    }
+
+    /// Gives back a call let through by [allow](Self::allow) but not made,
+    /// so that a half-open circuit lets the next call through.
+    pub fn release(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(trial_of) = state.trial_of.take() {
+            state.opened_at = Some(trial_of);
+        }
+    }
+
+    /// Records the outcome of a call let through by [allow](Self::allow).
+    pub fn record(&self, success: bool) {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        if success {
+            if state.opened_at.is_some() {
+                warn!("Weather circuit breaker closed.");
+            }
+            *state = BreakerState::default();
+            return;
+        }
+
+        state.trial_of = None;
+        state.consecutive_failures += 1;
+        let reopened = state.opened_at.is_some();
+        if reopened || (self.failure_threshold > 0 && state.consecutive_failures >= self.failure_threshold) {
+            warn!(
+                "Weather circuit breaker opened for {}s after {} consecutive failures.",
+                self.open_for.as_secs(),
+                state.consecutive_failures
+            );
+            state.opened_at = Some(Instant::now());
+        }
+    }
+}
+
+/// [WeatherProvider] calling another one with a timeout per attempt, retrying
+/// the failed calls with an exponential backoff, behind a [CircuitBreaker].
+///
+/// Each attempt is accounted for in its [WeatherQuota], if any. An attempt
+/// denied by the quota is not made: the call fails without retry, and is not
+/// counted as a failure by the circuit breaker unless a previous attempt
+/// failed.
+pub struct ResilientWeatherProvider {
+    inner: Box<dyn WeatherProvider>,
+    timeout: Duration,
+    retries: u32,
+    backoff: Duration,
+    breaker: CircuitBreaker,
+    quota: Option<WeatherQuota>,
+}
+
+impl ResilientWeatherProvider {
+    /// Create a new [`ResilientWeatherProvider`](Self) in front of `inner`.
+    ///
+    /// # Arguments
+    ///
+    /// - `timeout` - Maximum duration of each attempt.
+    /// - `retries` - Number of attempts after the first one.
+    /// - `backoff` - Delay before the first retry, doubled before each next
+    ///   one.
+    /// - `breaker` - [CircuitBreaker] of the calls, each one counting once
+    ///   whatever its number of attempts.
+    pub fn new(inner: Box<dyn WeatherProvider>, timeout: Duration, retries: u32, backoff: Duration, breaker: CircuitBreaker) -> Self {
+        Self {
+            inner,
+            timeout,
+            retries,
+            backoff,
+            breaker,
+            quota: None,
+        }
+    }
+
+    /// Accounts for each attempt in `quota`.
+    pub fn with_quota(mut self, quota: WeatherQuota) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+
+    /// Returns the [CircuitBreaker] of the calls.
+    pub fn breaker(&self) -> &CircuitBreaker {
+        &self.breaker
+    }
+
+    /// Returns the [WeatherQuota] of the attempts, if any.
+    pub fn quota(&self) -> Option<&WeatherQuota> {
+        self.quota.as_ref()
+    }
+
+    /// Calls `attempt` until it succeeds, at most `1 + retries` times.
+    async fn call<'a, T, F>(&'a self, attempt: impl Fn() -> F) -> Result<T, String>
+    where
+        F: Future<Output = Result<T, String>> + 'a,
+    {
+        self.breaker.allow()?;
+
+        let mut backoff = self.backoff;
+        let mut tries = 0;
+        let outcome = loop {
+            if let Some(Err(err)) = self.quota.as_ref().map(WeatherQuota::acquire) {
+                if tries == 0 {
+                    self.breaker.release();
+                    return Err(err);
+                }
+                break Err(err);
+            }
+
+            let err = match timeout(self.timeout, attempt()).await {
+                Ok(Ok(value)) => break Ok(value),
+                Ok(Err(err)) => err,
+                Err(_) => format!(
+                    "Weather call timed out after {}ms.",
+                    self.timeout.as_millis()
+                ),
+            };
+            if tries >= self.retries {
+                break Err(err);
+            }
+
+            tries += 1;
+            warn!(
+                "{} Retry {}/{} in {}ms.",
+                err,
+                tries,
+                self.retries,
+                backoff.as_millis()
+            );
+            sleep(backoff).await;
+            backoff *= 2;
+        };
+
+        self.breaker.record(outcome.is_ok());
+        outcome
+    }
+}
+
+impl WeatherProvider for ResilientWeatherProvider {
+    fn geocode<'a>(&'a self, town: &'a str) -> WeatherFuture<'a, Option<Location>> {
+        Box::pin(self.call(move || self.inner.geocode(town)))
+    }
+
+    fn forecast<'a>(&'a self, location: &'a Location) -> WeatherFuture<'a, Forecast> {
+        Box::pin(self.call(move || self.inner.forecast(location)))
+    }
+
+    fn cache_stats(&self) -> Option<WeatherCacheStats> {
+        self.inner.cache_stats()
+    }
+}
@@ -0,0 +1,159 @@
+use std::sync::Arc;
+
+use ntex::{
+    http::{header, StatusCode},
+    web::{test, App},
+};
+use promocode_models::promocode::name_policy::NamePolicy;
+use promocode_server::{
+    auth::{hash_api_key, ApiKey, ApiKeys, AuthError, Scope},
+    repository::{memory::InMemoryRepository, Repository},
+    server::routes,
+    signing_key::SigningKey,
+    weather::{NoWeatherProvider, Weather},
+};
+
+use common::promocode_request;
+
+mod common;
+
+fn api_keys() -> ApiKeys {
+    let keys = ApiKeys::parse(&format!(
+        r#"[
+            {{"name": "ops", "sha256": "{}", "scopes": ["read", "write"]}},
+            {{"name": "reporting", "sha256": "{}", "scopes": ["read"]}}
+        ]"#,
+        hash_api_key("ops-key"),
+        hash_api_key("reporting-key").to_uppercase(),
+    ))
+    .unwrap();
+
+    ApiKeys::new(keys, vec![Scope::Validate])
+}
+
+#[test]
+fn auth_hash_api_key() {
+    assert_eq!(
+        hash_api_key("abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+}
+
+#[test]
+fn auth_api_key_new() {
+    assert!(ApiKey::new("ops".to_string(), hash_api_key("key"), vec![Scope::Read]).is_ok());
+    assert!(ApiKey::new(" ".to_string(), hash_api_key("key"), vec![Scope::Read]).is_err());
+    assert!(ApiKey::new(
+        "ops".to_string(),
+        "not a hash".to_string(),
+        vec![Scope::Read]
+    )
+    .is_err());
+    assert!(ApiKeys::parse(r#"[{"name": "ops", "sha256": "0", "scopes": []}]"#).is_err());
+    assert!(ApiKeys::parse(r#"[{"name": "ops", "sha256": "0", "scopes": ["admin"]}]"#).is_err());
+}
+
+#[test]
+fn auth_authorize() {
+    let api_keys = api_keys();
+    assert_eq!(api_keys.len(), 2);
+
+    assert_eq!(api_keys.authorize(Some("ops-key"), Scope::Write), Ok(()));
+    assert_eq!(
+        api_keys.authorize(Some("reporting-key"), Scope::Read),
+        Ok(())
+    );
+    assert_eq!(api_keys.authorize(None, Scope::Validate), Ok(()));
+    assert_eq!(
+        api_keys.authorize(None, Scope::Read),
+        Err(AuthError::Unauthenticated)
+    );
+    assert_eq!(
+        api_keys.authorize(Some("unknown"), Scope::Read),
+        Err(AuthError::Unauthenticated)
+    );
+    assert_eq!(
+        api_keys.authorize(Some("reporting-key"), Scope::Write),
+        Err(AuthError::Forbidden {
+            name: "reporting".to_string(),
+            scope: Scope::Write
+        })
+    );
+}
+
+#[ntex::test]
+async fn auth_routes() {
+    let repository: Repository = Arc::new(InMemoryRepository::new());
+    let app = test::init_service(
+        common::test_app!(
+            repository,
+            Arc::new(NoWeatherProvider) as Weather,
+            NamePolicy::default()
+        )
+        .state(api_keys())
+        .configure(routes::services),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/promocodes").to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        res.headers().get(header::WWW_AUTHENTICATE).unwrap(),
+        "Bearer"
+    );
+
+    let req = test::TestRequest::get()
+        .uri("/promocodes")
+        .header(header::AUTHORIZATION, "Bearer unknown")
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let req = test::TestRequest::get()
+        .uri("/promocodes")
+        .header(header::AUTHORIZATION, "Bearer reporting-key")
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let req = test::TestRequest::delete()
+        .uri("/promocodes/missing")
+        .header(header::AUTHORIZATION, "Bearer reporting-key")
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    let req = test::TestRequest::delete()
+        .uri("/promocodes/missing")
+        .header(header::AUTHORIZATION, "Bearer ops-key")
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    // Validation is public by default.
+    let req = test::TestRequest::post()
+        .uri("/promocode/validate")
+        .set_json(&promocode_request("MISSING", 20, "Lyon"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_ne!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[ntex::test]
+async fn auth_not_configured() {
+    let repository: Repository = Arc::new(InMemoryRepository::new());
+    let app = test::init_service(
+        App::new()
+            .state(repository)
+            .state(Arc::new(NoWeatherProvider) as Weather)
+            .state(NamePolicy::default())
+            .state(SigningKey::default())
+            .configure(routes::services),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/promocodes").to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
@@ -0,0 +1,85 @@
+//! Helpers shared by the integration tests.
+//!
+//! Each test crate compiles its own copy and uses only some of them.
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use promocode_models::{
+    forecast::Observation,
+    promocode::{
+        avantage::Avantage,
+        name_policy::{CaseFolding, NamePolicy},
+        restriction::Restriction,
+        Promocode,
+    },
+    promocode_request::{arguments::Arguments, meteo::Meteo, PromocodeRequest},
+};
+use promocode_server::{
+    auth::{ApiKeys, Scope},
+    weather::{fetch_forecast, WeatherProvider},
+};
+
+/// Grants every scope without an API key.
+pub fn all_scopes() -> ApiKeys {
+    ApiKeys::new(vec![], vec![Scope::Read, Scope::Write, Scope::Validate])
+}
+
+/// Trims and uppercases the names, which the default policy does not.
+pub fn uppercase_policy() -> NamePolicy {
+    NamePolicy::new(true, CaseFolding::Upper, HashMap::new(), None, 1, 255).unwrap()
+}
+
+/// Returns a [Promocode] with a 1% avantage and no restrictions.
+pub fn promocode(id: &str, name: &str) -> Promocode {
+    promocode_with(id, name, 1, vec![])
+}
+
+/// Returns a [Promocode] with the given avantage and restrictions.
+pub fn promocode_with(id: &str, name: &str, percent: u8, restrictions: Vec<Result<Restriction, String>>) -> Promocode {
+    Promocode::new(
+        id.to_string(),
+        name.to_string(),
+        Avantage::new(percent),
+        restrictions,
+    )
+    .unwrap()
+}
+
+/// Returns a [PromocodeRequest] for `name`, by someone of `age` in `town`.
+pub fn promocode_request(name: &str, age: u8, town: &str) -> PromocodeRequest {
+    PromocodeRequest::new(
+        name.to_string(),
+        Arguments::new(age, Meteo::new(town.to_string())),
+    )
+    .unwrap()
+}
+
+/// Retrieves the current weather in `town` from `provider`, [None] if it
+/// fails.
+pub async fn get_current_observation(provider: &dyn WeatherProvider, town: &str) -> Option<Observation> {
+    fetch_forecast(provider, town)
+        .await
+        .ok()
+        .flatten()
+        .map(|forecast| forecast.current().observation.clone())
+}
+
+/// Returns an [App](ntex::web::App) with the state of the routes:
+/// `repository`, `weather` and `name_policy`, no signing key, and every scope
+/// granted without an API key. Any of them can be replaced with another
+/// `.state(...)`.
+#[allow(unused_macros)]
+macro_rules! test_app {
+    ($repository:expr, $weather:expr, $name_policy:expr) => {
+        ntex::web::App::new()
+            .state($repository)
+            .state($weather)
+            .state($name_policy)
+            .state(promocode_server::signing_key::SigningKey::default())
+            .state($crate::common::all_scopes())
+    };
+}
+
+#[allow(unused_imports)]
+pub(crate) use test_app;
@@ -0,0 +1,379 @@
+//! Conformance suite shared by every [PromocodeRepository].
+//!
+//! Each test starts from an empty repository, see [conformance_tests].
+
+use chrono::{TimeZone, Utc};
+use promocode_models::promocode::{
+    avantage::Avantage,
+    comparison::Comparison,
+    forecast_window::{Aggregation, ForecastWindow},
+    metadata::Metadata,
+    meteo_metrics::MeteoMetrics,
+    restriction::Restriction,
+    temp::Temp,
+    weather_policy::WeatherPolicy,
+    Promocode,
+};
+use promocode_server::repository::{PromocodeFilter, PromocodeRepository, RepositoryError, UniqueField};
+
+use crate::common::promocode;
+
+/// Returns `promocode` with its version incremented, as an update of the
+/// stored one.
+pub fn updated(mut promocode: Promocode) -> Promocode {
+    promocode
+        .metadata
+        .mark_updated(Utc.with_ymd_and_hms(2024, 6, 1, 8, 0, 0).unwrap());
+    promocode
+}
+
+pub fn use_case(repository: &dyn PromocodeRepository) {
+    assert_eq!(repository.list(&PromocodeFilter::default()), Ok(vec![]));
+
+    assert_eq!(repository.get_by_id("id"), Ok(None));
+    assert_eq!(repository.get_by_name("name"), Ok(None));
+
+    assert!(repository.insert(promocode("0", "name 0")).is_ok());
+    assert_eq!(
+        repository.get_by_id("0"),
+        Ok(Some(promocode("0", "name 0")))
+    );
+
+    assert!(repository.insert(promocode("1", "name 1")).is_ok());
+    assert_eq!(
+        repository.get_by_name("name 1"),
+        Ok(Some(promocode("1", "name 1")))
+    );
+
+    assert!(repository.insert(promocode("0", "name 2")).is_err());
+    assert!(repository.insert(promocode("2", "name 0")).is_err());
+
+    assert_eq!(
+        repository.list(&PromocodeFilter::default()),
+        Ok(vec![promocode("0", "name 0"), promocode("1", "name 1")])
+    );
+
+    assert_eq!(
+        repository.delete_by_id("0", None),
+        Ok(Some(promocode("0", "name 0")))
+    );
+    assert_eq!(repository.delete_by_id("0", None), Ok(None));
+    assert_eq!(repository.get_by_id("0"), Ok(None));
+    assert_eq!(
+        repository.list(&PromocodeFilter::default()),
+        Ok(vec![promocode("1", "name 1")])
+    );
+
+    assert_eq!(
+        repository.delete_by_name("name 1"),
+        Ok(Some(promocode("1", "name 1")))
+    );
+    assert_eq!(repository.get_by_name("name 1"), Ok(None));
+    assert_eq!(repository.list(&PromocodeFilter::default()), Ok(vec![]));
+}
+
+pub fn batch_use_case(repository: &dyn PromocodeRepository) {
+    assert!(repository.insert(promocode("0", "name 0")).is_ok());
+
+    assert!(repository
+        .insert_batch(vec![promocode("1", "name 1"), promocode("2", "name 0")])
+        .is_err());
+    assert!(repository
+        .insert_batch(vec![promocode("1", "name 1"), promocode("1", "name 2")])
+        .is_err());
+    assert!(repository
+        .insert_batch(vec![promocode("1", "name 1"), promocode("2", "name 1")])
+        .is_err());
+    assert_eq!(
+        repository.list(&PromocodeFilter::default()),
+        Ok(vec![promocode("0", "name 0")])
+    );
+
+    assert!(repository
+        .insert_batch(vec![promocode("1", "name 1"), promocode("2", "name 2")])
+        .is_ok());
+    assert_eq!(
+        repository.list(&PromocodeFilter::default()),
+        Ok(vec![
+            promocode("0", "name 0"),
+            promocode("1", "name 1"),
+            promocode("2", "name 2"),
+        ])
+    );
+}
+
+pub fn update_and_filter(repository: &dyn PromocodeRepository) {
+    let tagged = updated(
+        promocode("0", "name 0")
+            .with_metadata(Metadata::new(
+                None,
+                vec!["summer".to_string()],
+                None,
+                Some("mailing".to_string()),
+            ))
+            .unwrap(),
+    );
+
+    assert!(repository
+        .update(updated(promocode("0", "name 0")))
+        .is_err());
+    assert!(repository.insert(promocode("0", "name 0")).is_ok());
+    assert!(repository.insert(promocode("1", "name 1")).is_ok());
+
+    assert!(repository
+        .update(updated(promocode("0", "name 1")))
+        .is_err());
+    assert!(repository.update(tagged.clone()).is_ok());
+    assert_eq!(repository.get_by_id("0"), Ok(Some(tagged.clone())));
+
+    let filter = PromocodeFilter {
+        tags: vec!["summer".to_string()],
+        campaign: None,
+        ..PromocodeFilter::default()
+    };
+    assert_eq!(repository.list(&filter), Ok(vec![tagged.clone()]));
+
+    let filter = PromocodeFilter {
+        tags: vec![],
+        campaign: Some("other".to_string()),
+        ..PromocodeFilter::default()
+    };
+    assert_eq!(repository.list(&filter), Ok(vec![]));
+}
+
+pub fn list_pages(repository: &dyn PromocodeRepository) {
+    assert!(repository
+        .insert_batch(vec![
+            promocode("2", "name 2"),
+            promocode("0", "name 0"),
+            promocode("3", "other 3"),
+            promocode("1", "name 1"),
+        ])
+        .is_ok());
+
+    let all = PromocodeFilter::default();
+    assert_eq!(
+        repository.list_page(&all, None, 2),
+        Ok(vec![promocode("0", "name 0"), promocode("1", "name 1")])
+    );
+    assert_eq!(
+        repository.list_page(&all, Some("1"), 2),
+        Ok(vec![promocode("2", "name 2"), promocode("3", "other 3")])
+    );
+    assert_eq!(repository.list_page(&all, Some("3"), 2), Ok(vec![]));
+
+    // The page is filled with the promocodes passing the filter.
+    let names = PromocodeFilter {
+        name_prefix: Some("name".to_string()),
+        ..PromocodeFilter::default()
+    };
+    assert_eq!(
+        repository.list_page(&names, Some("1"), 2),
+        Ok(vec![promocode("2", "name 2")])
+    );
+    assert_eq!(
+        repository.list_page(&names, Some("00"), 2),
+        Ok(vec![promocode("1", "name 1"), promocode("2", "name 2")])
+    );
+}
+
+pub fn conflicts(repository: &dyn PromocodeRepository) {
+    let conflict = |field, value: &str| {
+        Err(RepositoryError::Conflict {
+            field,
+            value: value.to_string(),
+        })
+    };
+
+    assert!(repository.insert(promocode("0", "name 0")).is_ok());
+    assert!(repository.insert(promocode("1", "name 1")).is_ok());
+
+    assert_eq!(
+        repository.insert(promocode("0", "name 2")),
+        conflict(UniqueField::Id, "0")
+    );
+    assert_eq!(
+        repository.insert(promocode("2", "name 0")),
+        conflict(UniqueField::Name, "name 0")
+    );
+    assert_eq!(
+        repository.insert_batch(vec![promocode("2", "name 2"), promocode("3", "name 2")]),
+        conflict(UniqueField::Name, "name 2")
+    );
+
+    assert_eq!(
+        repository.update(updated(promocode("0", "name 1"))),
+        conflict(UniqueField::Name, "name 1")
+    );
+    assert_eq!(
+        repository.update(updated(promocode("2", "name 2"))),
+        Err(RepositoryError::NotFound {
+            id: "2".to_string()
+        })
+    );
+}
+
+pub fn versions(repository: &dyn PromocodeRepository) {
+    let mismatch = |version| {
+        Err(RepositoryError::VersionMismatch {
+            id: "0".to_string(),
+            version,
+        })
+    };
+
+    assert!(repository.insert(promocode("0", "name 0")).is_ok());
+
+    // Not based on the stored version.
+    assert_eq!(repository.update(promocode("0", "name 1")), mismatch(0));
+    assert_eq!(
+        repository.update(updated(updated(promocode("0", "name 1")))),
+        mismatch(0)
+    );
+
+    let first = updated(promocode("0", "name 1"));
+    assert!(repository.update(first.clone()).is_ok());
+    assert_eq!(repository.get_by_id("0"), Ok(Some(first)));
+
+    // A concurrent update based on the same version is lost.
+    assert_eq!(
+        repository.update(updated(promocode("0", "name 2"))),
+        mismatch(1)
+    );
+    assert_eq!(
+        repository.get_by_id("0").unwrap().map(|it| it.name()),
+        Some("name 1".to_string())
+    );
+}
+
+pub fn versioned_delete(repository: &dyn PromocodeRepository) {
+    assert!(repository.insert(promocode("0", "name 0")).is_ok());
+    assert!(repository.update(updated(promocode("0", "name 1"))).is_ok());
+
+    // Based on an outdated version.
+    assert_eq!(
+        repository.delete_by_id("0", Some(0)),
+        Err(RepositoryError::VersionMismatch {
+            id: "0".to_string(),
+            version: 1,
+        })
+    );
+    assert!(repository.get_by_id("0").unwrap().is_some());
+
+    assert_eq!(
+        repository.delete_by_id("0", Some(1)),
+        Ok(Some(updated(promocode("0", "name 1"))))
+    );
+    assert_eq!(repository.delete_by_id("0", Some(1)), Ok(None));
+}
+
+pub fn concurrent_inserts(repository: &dyn PromocodeRepository) {
+    let inserted = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..8)
+            .map(|index| scope.spawn(move || repository.insert(promocode(&index.to_string(), "same name"))))
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .filter(Result::is_ok)
+            .count()
+    });
+
+    assert_eq!(inserted, 1);
+    assert_eq!(
+        repository.list(&PromocodeFilter::default()).unwrap().len(),
+        1
+    );
+}
+
+pub fn weather_policy(repository: &dyn PromocodeRepository) {
+    let fail_open = promocode("0", "name 0").with_weather_policy(WeatherPolicy::FailOpen);
+
+    assert!(repository.insert(fail_open.clone()).is_ok());
+    assert_eq!(repository.get_by_id("0"), Ok(Some(fail_open.clone())));
+
+    let fail_closed = updated(fail_open).with_weather_policy(WeatherPolicy::FailClosed);
+    assert!(repository.update(fail_closed.clone()).is_ok());
+    assert_eq!(repository.get_by_name("name 0"), Ok(Some(fail_closed)));
+}
+
+pub fn meteo_restriction(repository: &dyn PromocodeRepository) {
+    let meteo = Promocode::new(
+        "0".to_string(),
+        "name 0".to_string(),
+        Avantage::new(1),
+        vec![Restriction::meteo_with(
+            "rain".to_string(),
+            Temp { gt: 10 },
+            Some(ForecastWindow::new(6, Aggregation::All, Aggregation::Min)),
+            MeteoMetrics::new(
+                None,
+                Some(Comparison::new(None, None, Some(70.0)).unwrap()),
+                Some(Comparison::new(Some(4.5), None, None).unwrap()),
+                None,
+            ),
+        )],
+    )
+    .unwrap();
+
+    assert!(repository.insert(meteo.clone()).is_ok());
+    assert_eq!(repository.get_by_id("0"), Ok(Some(meteo)));
+}
+
+/// Generates a test per case of the conformance suite.
+///
+/// `$new` builds an empty repository from the name of the test.
+macro_rules! conformance_tests {
+    ($new:expr) => {
+        #[test]
+        fn conformance_use_case() {
+            conformance::use_case(&$new("use_case"));
+        }
+
+        #[test]
+        fn conformance_batch_use_case() {
+            conformance::batch_use_case(&$new("batch_use_case"));
+        }
+
+        #[test]
+        fn conformance_update_and_filter() {
+            conformance::update_and_filter(&$new("update_and_filter"));
+        }
+
+        #[test]
+        fn conformance_list_pages() {
+            conformance::list_pages(&$new("list_pages"));
+        }
+
+        #[test]
+        fn conformance_conflicts() {
+            conformance::conflicts(&$new("conflicts"));
+        }
+
+        #[test]
+        fn conformance_versions() {
+            conformance::versions(&$new("versions"));
+        }
+
+        #[test]
+        fn conformance_versioned_delete() {
+            conformance::versioned_delete(&$new("versioned_delete"));
+        }
+
+        #[test]
+        fn conformance_weather_policy() {
+            conformance::weather_policy(&$new("weather_policy"));
+        }
+
+        #[test]
+        fn conformance_meteo_restriction() {
+            conformance::meteo_restriction(&$new("meteo_restriction"));
+        }
+
+        #[test]
+        fn conformance_concurrent_inserts() {
+            conformance::concurrent_inserts(&$new("concurrent_inserts"));
+        }
+    };
+}
+pub(crate) use conformance_tests;
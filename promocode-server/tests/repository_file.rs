@@ -0,0 +1,157 @@
+use std::{fs, io::Write, path::PathBuf};
+
+use promocode_server::repository::{
+    file::{Durability, FileRepository},
+    PromocodeFilter, PromocodeRepository,
+};
+
+use common::promocode;
+
+mod common;
+mod conformance;
+
+/// Returns an empty directory, unique to `test`.
+fn data_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("promocode-{}-{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+conformance::conformance_tests!(|test: &str| FileRepository::open(&data_dir(test), Durability::Buffered, 2).unwrap());
+
+#[test]
+fn repository_file_replay() {
+    let dir = data_dir("replay");
+
+    {
+        let repository = FileRepository::open(&dir, Durability::Fsync, 1000).unwrap();
+
+        assert!(repository
+            .insert_batch(vec![promocode("0", "name 0"), promocode("1", "name 1")])
+            .is_ok());
+        assert!(repository.insert(promocode("2", "name 0")).is_err());
+        assert!(repository
+            .update(conformance::updated(promocode("1", "name 2")))
+            .is_ok());
+        assert_eq!(
+            repository.delete_by_name("name 0"),
+            Ok(Some(promocode("0", "name 0")))
+        );
+        assert_eq!(repository.delete_by_id("0", None), Ok(None));
+    }
+
+    let repository = FileRepository::open(&dir, Durability::Fsync, 1000).unwrap();
+    assert_eq!(
+        repository.list(&PromocodeFilter::default()),
+        Ok(vec![conformance::updated(promocode("1", "name 2"))])
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn repository_file_compaction() {
+    let dir = data_dir("compaction");
+
+    {
+        let repository = FileRepository::open(&dir, Durability::Buffered, 2).unwrap();
+
+        for index in 0..5 {
+            assert!(repository
+                .insert(promocode(&index.to_string(), &format!("name {}", index)))
+                .is_ok());
+        }
+    }
+
+    // 2 compactions, then a single entry left in the journal.
+    assert_eq!(
+        fs::read_to_string(dir.join("journal.jsonl"))
+            .unwrap()
+            .lines()
+            .count(),
+        1
+    );
+
+    let repository = FileRepository::open(&dir, Durability::Buffered, 2).unwrap();
+    assert_eq!(
+        repository.list(&PromocodeFilter::default()).unwrap().len(),
+        5
+    );
+    assert_eq!(fs::read_to_string(dir.join("journal.jsonl")).unwrap(), "");
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn repository_file_truncated_journal() {
+    let dir = data_dir("truncated");
+
+    {
+        let repository = FileRepository::open(&dir, Durability::Flush, 1000).unwrap();
+        assert!(repository.insert(promocode("0", "name 0")).is_ok());
+    }
+
+    // A crash while writing the last entry.
+    fs::OpenOptions::new()
+        .append(true)
+        .open(dir.join("journal.jsonl"))
+        .unwrap()
+        .write_all(br#"{"op":"insert","promoco"#)
+        .unwrap();
+
+    let repository = FileRepository::open(&dir, Durability::Flush, 1000).unwrap();
+    assert_eq!(
+        repository.list(&PromocodeFilter::default()),
+        Ok(vec![promocode("0", "name 0")])
+    );
+    drop(repository);
+
+    // Corruption in the middle of the journal is not silently dropped.
+    fs::write(
+        dir.join("journal.jsonl"),
+        "{\"op\":\"delete\"}\n{\"op\":\"delete\",\"id\":\"0\"}\n",
+    )
+    .unwrap();
+    assert!(FileRepository::open(&dir, Durability::Flush, 1000).is_err());
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn repository_file_crash_after_snapshot() {
+    let dir = data_dir("crash_after_snapshot");
+
+    {
+        let repository = FileRepository::open(&dir, Durability::Fsync, 1000).unwrap();
+        assert!(repository.insert(promocode("0", "name 0")).is_ok());
+        assert!(repository
+            .update(conformance::updated(promocode("0", "name 1")))
+            .is_ok());
+    }
+    let journal = fs::read(dir.join("journal.jsonl")).unwrap();
+
+    // The reopening writes the snapshot, then empties the journal.
+    drop(FileRepository::open(&dir, Durability::Fsync, 1000).unwrap());
+
+    // A crash between the two: the snapshot already holds the journal.
+    fs::write(dir.join("journal.jsonl"), &journal).unwrap();
+
+    for _ in 0..2 {
+        let repository = FileRepository::open(&dir, Durability::Fsync, 1000).unwrap();
+        assert_eq!(
+            repository.list(&PromocodeFilter::default()),
+            Ok(vec![conformance::updated(promocode("0", "name 1"))])
+        );
+
+        // The next entries are still replayed.
+        assert_eq!(
+            repository.delete_by_id("0", None),
+            Ok(Some(conformance::updated(promocode("0", "name 1"))))
+        );
+        assert!(repository
+            .insert(conformance::updated(promocode("0", "name 1")))
+            .is_ok());
+    }
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,6 @@
+use promocode_server::repository::memory::InMemoryRepository;
+
+mod common;
+mod conformance;
+
+conformance::conformance_tests!(|_| InMemoryRepository::new());
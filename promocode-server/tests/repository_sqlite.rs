@@ -0,0 +1,82 @@
+use std::{fs, path::PathBuf};
+
+use chrono::Utc;
+use promocode_models::promocode::restriction::Restriction;
+use promocode_server::repository::{sqlite::SqliteRepository, PromocodeFilter, PromocodeRepository};
+
+use common::{promocode, promocode_with};
+
+mod common;
+mod conformance;
+
+conformance::conformance_tests!(|_| SqliteRepository::open_in_memory().unwrap());
+
+/// Returns the path of an absent database, unique to `test`.
+fn database_path(test: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("promocode-{}-{}.sqlite", test, std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn repository_sqlite_persistence() {
+    let path = database_path("persistence");
+    let stored = promocode_with(
+        "0",
+        "name 0",
+        1,
+        vec![Restriction::or(vec![
+            Restriction::age(None, Some(40), None),
+            Restriction::date("2024-01-01".to_string(), "2024-12-31".to_string()),
+        ])],
+    );
+
+    {
+        let repository = SqliteRepository::open(&path).unwrap();
+        assert_eq!(repository.schema_version(), Ok(2));
+        assert!(repository.insert(stored.clone()).is_ok());
+    }
+
+    // Migrations are not applied twice.
+    let repository = SqliteRepository::open(&path).unwrap();
+    assert_eq!(repository.schema_version(), Ok(2));
+    assert_eq!(
+        repository.list(&PromocodeFilter::default()),
+        Ok(vec![stored])
+    );
+    drop(repository);
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn repository_sqlite_audit_and_redemptions() {
+    let repository = SqliteRepository::open_in_memory().unwrap();
+
+    assert!(repository.insert(promocode("0", "name 0")).is_ok());
+    assert!(repository.insert(promocode("1", "name 0")).is_err());
+    assert!(repository
+        .update(conformance::updated(promocode("0", "name 1")))
+        .is_ok());
+    assert!(repository.delete_by_name("name 1").unwrap().is_some());
+
+    let audit: Vec<(String, String)> = repository
+        .audit()
+        .unwrap()
+        .into_iter()
+        .map(|entry| (entry.op, entry.promocode_id))
+        .collect();
+    assert_eq!(
+        audit,
+        vec![
+            ("insert".to_string(), "0".to_string()),
+            ("update".to_string(), "0".to_string()),
+            ("delete".to_string(), "0".to_string()),
+        ]
+    );
+
+    assert_eq!(repository.redemption_count("0"), Ok(0));
+    assert!(repository.record_redemption("0", Utc::now()).is_ok());
+    assert!(repository.record_redemption("0", Utc::now()).is_ok());
+    assert_eq!(repository.redemption_count("0"), Ok(2));
+}
@@ -0,0 +1,143 @@
+use std::{collections::HashMap, sync::Arc};
+
+use ntex::{http::StatusCode, web::test};
+use promocode_models::{
+    promocode::{
+        avantage::Avantage,
+        check_digit::CheckDigit,
+        name_policy::{CaseFolding, NamePolicy},
+        restriction::Restriction,
+        Promocode,
+    },
+    promocode_request::{arguments::Arguments, meteo::Meteo},
+    promocode_response::PromocodeResponse,
+};
+use promocode_server::{
+    repository::{memory::InMemoryRepository, Repository},
+    server::routes,
+    weather::{NoWeatherProvider, Weather},
+};
+
+use common::promocode_request;
+
+mod common;
+
+#[ntex::test]
+async fn routes_check_digit() {
+    let name_policy = NamePolicy::new(true, CaseFolding::Upper, HashMap::new(), None, 1, 255)
+        .unwrap()
+        .with_check_digit(Some(
+            CheckDigit::new("0123456789".to_string(), vec!["CARD-".to_string()], true).unwrap(),
+        ));
+
+    let repository: Repository = Arc::new(InMemoryRepository::new());
+    repository
+        .insert(
+            Promocode::new(
+                "id".to_string(),
+                "CARD-79927398713".to_string(),
+                Avantage::new(10),
+                vec![Restriction::age(None, None, Some(18))],
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    let app = test::init_service(
+        common::test_app!(
+            repository,
+            Arc::new(NoWeatherProvider) as Weather,
+            name_policy
+        )
+        .configure(routes::services),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/promocode/validate")
+        .set_json(&promocode_request("card-79927398713", 25, "Lyon"))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    // A typo is malformed, with the existing name as suggestion.
+    let req = test::TestRequest::post()
+        .uri("/promocode/validate")
+        .set_json(&promocode_request("card-79927398710", 25, "Lyon"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        serde_json::from_slice::<PromocodeResponse>(&test::read_body(res).await).unwrap(),
+        PromocodeResponse::malformed(
+            "card-79927398710".to_string(),
+            Some("CARD-79927398713".to_string())
+        )
+        .unwrap()
+    );
+
+    let req = test::TestRequest::post()
+        .uri("/promocode/CARD-79927398731/validate")
+        .set_json(&Arguments::new(25, Meteo::new("Lyon".to_string())).unwrap())
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+
+    let req = test::TestRequest::post()
+        .uri("/promocode/validate")
+        .set_payload(r#"{"promocode_name":"card-79927398713"}"#)
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::BAD_REQUEST
+    );
+}
+
+#[ntex::test]
+async fn routes_check_digit_prefix_in_alphabet() {
+    let check_digit = CheckDigit::new(
+        "ABCDEFGHJKLMNPQRSTUVWXYZ23456789".to_string(),
+        vec!["SUMMER-".to_string()],
+        true,
+    )
+    .unwrap();
+    let code = format!("SUMMER-4F7KQ2{}", check_digit.compute("SUMMER-4F7KQ2"));
+    let name_policy = NamePolicy::new(true, CaseFolding::Upper, HashMap::new(), None, 1, 255)
+        .unwrap()
+        .with_check_digit(Some(check_digit));
+
+    let repository: Repository = Arc::new(InMemoryRepository::new());
+    repository
+        .insert(
+            Promocode::new(
+                "id".to_string(),
+                code.clone(),
+                Avantage::new(10),
+                vec![Restriction::age(None, None, Some(18))],
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    let app = test::init_service(
+        common::test_app!(
+            repository,
+            Arc::new(NoWeatherProvider) as Weather,
+            name_policy
+        )
+        .configure(routes::services),
+    )
+    .await;
+
+    // The suggestion changes the characters after the prefix only.
+    let typo = code.replace("4F7", "4E7");
+    let req = test::TestRequest::post()
+        .uri("/promocode/validate")
+        .set_json(&promocode_request(&typo, 25, "Lyon"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        serde_json::from_slice::<PromocodeResponse>(&test::read_body(res).await).unwrap(),
+        PromocodeResponse::malformed(typo, Some(code)).unwrap()
+    );
+}
@@ -0,0 +1,111 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use ntex::{http::StatusCode, web::test};
+use promocode_models::{
+    forecast::{Forecast, Observation},
+    promocode::{avantage::Avantage, name_policy::NamePolicy, restriction::Restriction, temp::Temp, Promocode},
+};
+use promocode_server::{
+    repository::{memory::InMemoryRepository, Repository},
+    server::routes,
+    weather::{Location, Weather, WeatherFuture, WeatherProvider},
+};
+
+use common::promocode_request;
+
+mod common;
+
+static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+/// Sunny everywhere, counting its calls.
+struct SunnyProvider;
+
+impl WeatherProvider for SunnyProvider {
+    fn geocode<'a>(&'a self, town: &'a str) -> WeatherFuture<'a, Option<Location>> {
+        CALLS.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async move {
+            Ok(Some(Location {
+                name: town.to_string(),
+                lat: 0.0,
+                lon: 0.0,
+            }))
+        })
+    }
+
+    fn forecast<'a>(&'a self, _: &'a Location) -> WeatherFuture<'a, Forecast> {
+        CALLS.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async move { Ok(Forecast::from(Observation::new("clear".to_string(), 25.0))) })
+    }
+}
+
+#[ntex::test]
+async fn routes_lazy_weather() {
+    let repository: Repository = Arc::new(InMemoryRepository::new());
+    repository
+        .insert_batch(vec![
+            Promocode::new(
+                "age".to_string(),
+                "AGE".to_string(),
+                Avantage::new(10),
+                vec![Restriction::age(None, None, Some(18))],
+            )
+            .unwrap(),
+            Promocode::new(
+                "age-or-meteo".to_string(),
+                "AGE-OR-METEO".to_string(),
+                Avantage::new(10),
+                vec![
+                    Restriction::age(None, Some(30), None),
+                    Restriction::and(vec![
+                        Restriction::age(None, None, Some(18)),
+                        Restriction::meteo("clear".to_string(), Temp { gt: 15 }),
+                    ]),
+                ],
+            )
+            .unwrap(),
+        ])
+        .unwrap();
+    let app = test::init_service(
+        common::test_app!(
+            repository,
+            Arc::new(SunnyProvider) as Weather,
+            NamePolicy::default()
+        )
+        .configure(routes::services),
+    )
+    .await;
+
+    let validate = |name: &str, age: u8| {
+        test::TestRequest::post()
+            .uri("/promocode/validate")
+            .set_json(&promocode_request(name, age, "Lyon"))
+            .to_request()
+    };
+
+    // Unknown code, no weather restriction, or decided by the age.
+    for (name, age, status) in [
+        ("UNKNOWN", 20, StatusCode::BAD_REQUEST),
+        ("AGE", 20, StatusCode::OK),
+        ("AGE", 16, StatusCode::BAD_REQUEST),
+        ("AGE-OR-METEO", 30, StatusCode::OK),
+        ("AGE-OR-METEO", 16, StatusCode::BAD_REQUEST),
+    ] {
+        assert_eq!(
+            test::call_service(&app, validate(name, age)).await.status(),
+            status
+        );
+    }
+    assert_eq!(CALLS.load(Ordering::SeqCst), 0);
+
+    // Decided by the weather, fetched once.
+    assert_eq!(
+        test::call_service(&app, validate("AGE-OR-METEO", 20))
+            .await
+            .status(),
+        StatusCode::OK
+    );
+    assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+}
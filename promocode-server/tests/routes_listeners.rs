@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use ntex::{
+    http::{Method, StatusCode},
+    web::test,
+};
+use promocode_models::promocode::{avantage::Avantage, name_policy::NamePolicy, restriction::Restriction, Promocode};
+use promocode_server::{
+    repository::{memory::InMemoryRepository, Repository},
+    server::routes::{self, RouteSettings},
+    weather::{NoWeatherProvider, Weather},
+};
+
+use common::promocode_request;
+
+mod common;
+
+#[ntex::test]
+async fn routes_listeners() {
+    let repository: Repository = Arc::new(InMemoryRepository::new());
+    let settings = RouteSettings::default();
+    let public = test::init_service(
+        common::test_app!(
+            repository.clone(),
+            Arc::new(NoWeatherProvider) as Weather,
+            NamePolicy::default()
+        )
+        .configure(move |cfg| routes::public_services(cfg, &settings)),
+    )
+    .await;
+    let admin = test::init_service(
+        common::test_app!(
+            repository,
+            Arc::new(NoWeatherProvider) as Weather,
+            NamePolicy::default()
+        )
+        .configure(routes::admin_services),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/promocode/validate")
+        .set_json(&promocode_request("MISSING", 20, "Lyon"))
+        .to_request();
+    let res = test::call_service(&public, req).await;
+    assert_ne!(res.status(), StatusCode::NOT_FOUND);
+
+    let req = test::TestRequest::post()
+        .uri("/promocode/validate")
+        .set_json(&promocode_request("MISSING", 20, "Lyon"))
+        .to_request();
+    let res = test::call_service(&admin, req).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    for (method, uri) in [
+        (Method::GET, "/promocodes"),
+        (Method::GET, "/promocodes/id"),
+        (Method::DELETE, "/promocodes/id"),
+        (Method::DELETE, "/promocode"),
+        (Method::PUT, "/promocode"),
+        (Method::POST, "/promocode/batch"),
+        (Method::POST, "/promocode/sign"),
+    ] {
+        let req = test::TestRequest::default()
+            .method(method.clone())
+            .uri(uri)
+            .to_request();
+        let res = test::call_service(&public, req).await;
+        assert!(
+            res.status() == StatusCode::NOT_FOUND || res.status() == StatusCode::METHOD_NOT_ALLOWED,
+            "{} {} is served by the public listener",
+            method,
+            uri
+        );
+    }
+
+    // The listeners share the store.
+    let req = test::TestRequest::put()
+        .uri("/promocode")
+        .set_json(
+            &Promocode::new(
+                "id".to_string(),
+                "MISSING".to_string(),
+                Avantage::new(10),
+                vec![Restriction::age(None, None, Some(18))],
+            )
+            .unwrap(),
+        )
+        .to_request();
+    assert_eq!(
+        test::call_service(&admin, req).await.status(),
+        StatusCode::OK
+    );
+
+    let req = test::TestRequest::post()
+        .uri("/promocode/validate")
+        .set_json(&promocode_request("MISSING", 20, "Lyon"))
+        .to_request();
+    assert_eq!(
+        test::call_service(&public, req).await.status(),
+        StatusCode::OK
+    );
+}
@@ -0,0 +1,214 @@
+use std::sync::Arc;
+
+use ntex::{http::StatusCode, web::test};
+use promocode_models::{
+    promocode::{avantage::Avantage, name_policy::NamePolicy, restriction::Restriction, Promocode},
+    promocode_request::{arguments::Arguments, meteo::Meteo},
+};
+use promocode_server::{
+    repository::{memory::InMemoryRepository, sqlite::SqliteRepository, Repository},
+    server::routes::{self, RouteSettings},
+    weather::{NoWeatherProvider, Weather},
+};
+
+use common::{promocode_request, uppercase_policy};
+
+mod common;
+
+#[ntex::test]
+async fn routes_use_isolated_repository() {
+    let repository: Repository = Arc::new(InMemoryRepository::new());
+    let app = test::init_service(
+        common::test_app!(
+            repository.clone(),
+            Arc::new(NoWeatherProvider) as Weather,
+            uppercase_policy()
+        )
+        .configure(routes::services),
+    )
+    .await;
+
+    let promocode = Promocode::new(
+        "id".to_string(),
+        "adult".to_string(),
+        Avantage::new(10),
+        vec![Restriction::age(None, None, Some(18))],
+    )
+    .unwrap();
+
+    let req = test::TestRequest::put()
+        .uri("/promocode")
+        .set_json(&promocode)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    // Stored with the name normalized by the name policy.
+    assert_eq!(
+        repository.get_by_id("id").unwrap().map(|it| it.name()),
+        Some("ADULT".to_string())
+    );
+
+    // Retried: nothing changes.
+    let req = test::TestRequest::put()
+        .uri("/promocode")
+        .set_json(&promocode)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let other = Promocode::new(
+        "id".to_string(),
+        "other".to_string(),
+        Avantage::new(10),
+        vec![],
+    )
+    .unwrap();
+    let req = test::TestRequest::put()
+        .uri("/promocode")
+        .set_json(&other)
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::CONFLICT
+    );
+
+    let req = test::TestRequest::post()
+        .uri("/promocode/validate")
+        .set_json(&promocode_request("adult", 25, "Lyon"))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let req = test::TestRequest::post()
+        .uri("/promocode/validate")
+        .set_json(&promocode_request("adult", 16, "Lyon"))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::BAD_REQUEST
+    );
+
+    let req = test::TestRequest::post()
+        .uri("/promocode/validate")
+        .set_json(&promocode_request("unknown", 25, "Lyon"))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::BAD_REQUEST
+    );
+
+    // Name in the path.
+    let req = test::TestRequest::post()
+        .uri("/promocode/adult/validate")
+        .set_json(&Arguments::new(25, Meteo::new("Lyon".to_string())).unwrap())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let req = test::TestRequest::post()
+        .uri("/promocode/adult/validate")
+        .set_json(&Arguments::new(16, Meteo::new("Lyon".to_string())).unwrap())
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::BAD_REQUEST
+    );
+
+    let req = test::TestRequest::delete()
+        .uri("/promocode")
+        .set_json(&"adult")
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let req = test::TestRequest::delete()
+        .uri("/promocode")
+        .set_json(&"adult")
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[ntex::test]
+async fn routes_deprecated_get_validation() {
+    let repository: Repository = Arc::new(InMemoryRepository::new());
+    let app = test::init_service(
+        common::test_app!(
+            repository.clone(),
+            Arc::new(NoWeatherProvider) as Weather,
+            NamePolicy::default()
+        )
+        .configure(routes::services),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/promocode")
+        .set_json(&promocode_request("unknown", 25, "Lyon"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(res.headers().get("deprecation").unwrap(), "true");
+
+    let settings = RouteSettings {
+        deprecated_get_validation: false,
+    };
+    let app = test::init_service(
+        common::test_app!(
+            repository,
+            Arc::new(NoWeatherProvider) as Weather,
+            NamePolicy::default()
+        )
+        .configure(move |cfg| routes::services_with(cfg, &settings)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/promocode")
+        .set_json(&promocode_request("unknown", 25, "Lyon"))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[ntex::test]
+async fn routes_record_redemptions() {
+    let sqlite = Arc::new(SqliteRepository::open_in_memory().unwrap());
+    let app = test::init_service(
+        common::test_app!(
+            sqlite.clone() as Repository,
+            Arc::new(NoWeatherProvider) as Weather,
+            uppercase_policy()
+        )
+        .configure(routes::services),
+    )
+    .await;
+
+    let promocode = Promocode::new(
+        "id".to_string(),
+        "ADULT".to_string(),
+        Avantage::new(10),
+        vec![Restriction::age(None, None, Some(18))],
+    )
+    .unwrap();
+    let req = test::TestRequest::put()
+        .uri("/promocode")
+        .set_json(&promocode)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    for (age, status) in [
+        (25, StatusCode::OK),
+        (16, StatusCode::BAD_REQUEST),
+        (30, StatusCode::OK),
+    ] {
+        let req = test::TestRequest::post()
+            .uri("/promocode/validate")
+            .set_json(&promocode_request("adult", age, "Lyon"))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), status);
+    }
+
+    // Only the accepted validations are redemptions.
+    assert_eq!(sqlite.redemption_count("id"), Ok(2));
+}
@@ -0,0 +1,216 @@
+use std::sync::Arc;
+
+use ntex::{
+    http::{header, StatusCode},
+    web::test,
+};
+use promocode_models::promocode::{name_policy::NamePolicy, restriction::Restriction, Promocode};
+use promocode_server::{
+    repository::{memory::InMemoryRepository, Repository},
+    server::routes,
+    weather::{NoWeatherProvider, Weather},
+};
+use serde_json::Value;
+
+use common::promocode_with;
+
+mod common;
+
+fn ids(promocodes: &Value) -> Vec<&str> {
+    promocodes
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|it| it["_id"].as_str().unwrap())
+        .collect()
+}
+
+#[ntex::test]
+async fn routes_promocode_list() {
+    let repository: Repository = Arc::new(InMemoryRepository::new());
+    repository
+        .insert_batch(vec![
+            promocode_with(
+                "a",
+                "SUMMER10",
+                10,
+                vec![Restriction::date(
+                    "2000-06-01".to_string(),
+                    "2000-08-31".to_string(),
+                )],
+            ),
+            promocode_with(
+                "b",
+                "SUMMER30",
+                30,
+                vec![Restriction::age(None, None, Some(18))],
+            ),
+            promocode_with(
+                "c",
+                "WINTER20",
+                20,
+                vec![Restriction::date(
+                    "2000-01-01".to_string(),
+                    "9999-12-31".to_string(),
+                )],
+            ),
+            promocode_with("d", "SPRING5", 5, vec![]),
+        ])
+        .unwrap();
+
+    let app = test::init_service(
+        common::test_app!(
+            repository.clone(),
+            Arc::new(NoWeatherProvider) as Weather,
+            NamePolicy::default()
+        )
+        .configure(routes::services),
+    )
+    .await;
+
+    let get = |uri: &str| test::TestRequest::get().uri(uri).to_request();
+
+    // Without `limit` nor `cursor`, the list is a bare array.
+    let list: Value = serde_json::from_slice(&test::read_body(test::call_service(&app, get("/promocodes?sort=-percent")).await).await).unwrap();
+    assert_eq!(ids(&list), vec!["b", "c", "a", "d"]);
+
+    let page: Value = serde_json::from_slice(&test::read_body(test::call_service(&app, get("/promocodes?sort=-percent&limit=10")).await).await).unwrap();
+    assert_eq!(ids(&page["promocodes"]), vec!["b", "c", "a", "d"]);
+    assert!(page.get("next_cursor").is_none());
+
+    let list: Value = serde_json::from_slice(&test::read_body(test::call_service(&app, get("/promocodes?name_prefix=summer&sort=name")).await).await).unwrap();
+    assert_eq!(ids(&list), vec!["a", "b"]);
+
+    let list: Value = serde_json::from_slice(&test::read_body(test::call_service(&app, get("/promocodes?status=expired")).await).await).unwrap();
+    assert_eq!(ids(&list), vec!["a"]);
+
+    let list: Value = serde_json::from_slice(
+        &test::read_body(
+            test::call_service(
+                &app,
+                get("/promocodes?valid_on=2000-07-14&restriction=date&sort=name"),
+            )
+            .await,
+        )
+        .await,
+    )
+    .unwrap();
+    assert_eq!(ids(&list), vec!["a", "c"]);
+
+    // Walks the pages, while a promocode is deleted.
+    let page: Value = serde_json::from_slice(&test::read_body(test::call_service(&app, get("/promocodes?sort=name&limit=2")).await).await).unwrap();
+    assert_eq!(ids(&page["promocodes"]), vec!["d", "a"]);
+    let cursor = page["next_cursor"].as_str().unwrap().to_string();
+
+    repository.delete_by_id("d", None).unwrap();
+
+    let page: Value = serde_json::from_slice(
+        &test::read_body(
+            test::call_service(
+                &app,
+                get(&format!("/promocodes?sort=name&limit=2&cursor={}", cursor)),
+            )
+            .await,
+        )
+        .await,
+    )
+    .unwrap();
+    assert_eq!(ids(&page["promocodes"]), vec!["b", "c"]);
+    assert!(page.get("next_cursor").is_none());
+
+    // A cursor is bound to its sort.
+    let res = test::call_service(
+        &app,
+        get(&format!("/promocodes?sort=percent&cursor={}", cursor)),
+    )
+    .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = test::call_service(&app, get("/promocodes?sort=unknown")).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = test::call_service(&app, get("/promocodes?format=ndjson&sort=name")).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/x-ndjson"
+    );
+    let body = test::read_body(res).await;
+    let lines: Vec<Promocode> = std::str::from_utf8(&body)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(
+        lines.iter().map(|it| it._id()).collect::<Vec<_>>(),
+        vec!["a", "b", "c"]
+    );
+}
+
+#[ntex::test]
+async fn routes_promocode_list_large_store() {
+    let repository: Repository = Arc::new(InMemoryRepository::new());
+    repository
+        .insert_batch(
+            (0..1200)
+                .map(|i| {
+                    promocode_with(
+                        &format!("{:04}", i),
+                        &format!("NAME{:04}", i),
+                        (i % 100) as u8 + 1,
+                        vec![],
+                    )
+                })
+                .collect(),
+        )
+        .unwrap();
+
+    let app = test::init_service(
+        common::test_app!(
+            repository.clone(),
+            Arc::new(NoWeatherProvider) as Weather,
+            NamePolicy::default()
+        )
+        .configure(routes::services),
+    )
+    .await;
+
+    // The export goes through every page of the store.
+    let res = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/promocodes?format=ndjson")
+            .to_request(),
+    )
+    .await;
+    let body = test::read_body(res).await;
+    let lines: Vec<Promocode> = std::str::from_utf8(&body)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 1200);
+    assert_eq!(lines[1199]._id(), "1199");
+
+    // The pages are sorted across the whole store.
+    let mut cursor = String::new();
+    let mut seen = vec![];
+    loop {
+        let uri = format!("/promocodes?sort=-percent&limit=1000&cursor={}", cursor);
+        let uri = if cursor.is_empty() {
+            "/promocodes?sort=-percent&limit=1000"
+        } else {
+            uri.as_str()
+        };
+        let res = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        let page: Value = serde_json::from_slice(&test::read_body(res).await).unwrap();
+        seen.extend(ids(&page["promocodes"]).into_iter().map(str::to_string));
+        match page["next_cursor"].as_str() {
+            None => break,
+            Some(next) => cursor = next.to_string(),
+        }
+    }
+    assert_eq!(seen.len(), 1200);
+    assert_eq!(seen[..2], ["1199", "1099"]);
+    assert_eq!(seen[1199], "0000");
+}
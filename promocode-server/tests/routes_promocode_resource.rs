@@ -0,0 +1,266 @@
+use std::sync::Arc;
+
+use ntex::{
+    http::{header, StatusCode},
+    web::test,
+};
+use promocode_models::promocode::Promocode;
+use promocode_server::{
+    repository::{memory::InMemoryRepository, Repository},
+    server::routes,
+    weather::{NoWeatherProvider, Weather},
+};
+use serde_json::json;
+
+use common::{promocode_with, uppercase_policy};
+
+mod common;
+
+#[ntex::test]
+async fn routes_promocode_resource_put() {
+    let repository: Repository = Arc::new(InMemoryRepository::new());
+    let app = test::init_service(
+        common::test_app!(
+            repository.clone(),
+            Arc::new(NoWeatherProvider) as Weather,
+            uppercase_policy()
+        )
+        .configure(routes::services),
+    )
+    .await;
+
+    let put = |id: &str, promocode: &Promocode| {
+        test::TestRequest::put()
+            .uri(&format!("/promocodes/{}", id))
+            .set_json(promocode)
+    };
+
+    // The path id must be the one of the body.
+    let req = put("other", &promocode_with("id", "name", 10, vec![])).to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::BAD_REQUEST
+    );
+
+    // If-Match requires an existing promocode.
+    let req = put("id", &promocode_with("id", "name", 10, vec![]))
+        .header(header::IF_MATCH, "*")
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::PRECONDITION_FAILED
+    );
+
+    let req = put("id", &promocode_with("id", "name", 10, vec![])).to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    assert_eq!(res.headers().get(header::ETAG).unwrap(), "\"1\"");
+
+    // Idempotent.
+    let req = put("id", &promocode_with("id", "name", 10, vec![])).to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers().get(header::ETAG).unwrap(), "\"1\"");
+
+    let req = put("id", &promocode_with("id", "name", 20, vec![]))
+        .header(header::IF_MATCH, "\"1\"")
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers().get(header::ETAG).unwrap(), "\"2\"");
+
+    // Based on an outdated version: the update is lost.
+    let req = put("id", &promocode_with("id", "name", 30, vec![]))
+        .header(header::IF_MATCH, "\"1\"")
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::PRECONDITION_FAILED
+    );
+
+    let stored = repository.get_by_id("id").unwrap().unwrap();
+    assert_eq!(stored.avantage.percent.get(), 20);
+    assert_eq!(stored.metadata.version(), 2);
+    assert!(stored.metadata.created_at() <= stored.metadata.updated_at());
+
+    let req = put("other", &promocode_with("other", "name", 10, vec![])).to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::CONFLICT
+    );
+
+    // The prefix of the signed promocodes is reserved.
+    let req = put(
+        "signed",
+        &promocode_with("signed", " sig1.name", 10, vec![]),
+    )
+    .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::BAD_REQUEST
+    );
+}
+
+#[ntex::test]
+async fn routes_promocode_resource_patch() {
+    let repository: Repository = Arc::new(InMemoryRepository::new());
+    let app = test::init_service(
+        common::test_app!(
+            repository.clone(),
+            Arc::new(NoWeatherProvider) as Weather,
+            uppercase_policy()
+        )
+        .configure(routes::services),
+    )
+    .await;
+
+    let patch = |id: &str, patch: serde_json::Value| {
+        test::TestRequest::patch()
+            .uri(&format!("/promocodes/{}", id))
+            .set_json(&patch)
+    };
+
+    let req = patch("id", json!({ "avantage": { "percent": 20 } })).to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::NOT_FOUND
+    );
+
+    let req = test::TestRequest::put()
+        .uri("/promocodes/id")
+        .set_json(&promocode_with("id", "name", 10, vec![]))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::CREATED
+    );
+
+    let req = patch(
+        "id",
+        json!({ "avantage": { "percent": 20 }, "tags": ["summer"], "version": 42 }),
+    )
+    .header(header::IF_MATCH, "\"1\"")
+    .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers().get(header::ETAG).unwrap(), "\"2\"");
+
+    let stored = repository.get_by_id("id").unwrap().unwrap();
+    assert_eq!(stored.avantage.percent.get(), 20);
+    assert_eq!(stored.metadata.tags(), vec!["summer".to_string()]);
+    assert_eq!(stored.name(), "NAME");
+
+    let req = patch("id", json!({ "name": "sig1.name" })).to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(repository.get_by_id("id").unwrap().unwrap().name(), "NAME");
+
+    // `null` removes a field.
+    let req = patch("id", json!({ "tags": null })).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    assert!(repository
+        .get_by_id("id")
+        .unwrap()
+        .unwrap()
+        .metadata
+        .tags()
+        .is_empty());
+
+    let req = patch("id", json!({ "_id": "other" })).to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::BAD_REQUEST
+    );
+
+    let req = patch("id", json!({ "avantage": { "percent": 200 } })).to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::BAD_REQUEST
+    );
+
+    let req = patch("id", json!({ "avantage": { "percent": 30 } }))
+        .header(header::IF_MATCH, "\"2\"")
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::PRECONDITION_FAILED
+    );
+}
+
+#[ntex::test]
+async fn routes_promocode_resource_get_delete() {
+    let repository: Repository = Arc::new(InMemoryRepository::new());
+    let app = test::init_service(
+        common::test_app!(
+            repository.clone(),
+            Arc::new(NoWeatherProvider) as Weather,
+            uppercase_policy()
+        )
+        .configure(routes::services),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/promocodes/id").to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::NOT_FOUND
+    );
+
+    let req = test::TestRequest::put()
+        .uri("/promocodes/id")
+        .set_json(&promocode_with("id", "name", 10, vec![]))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::CREATED
+    );
+
+    let req = test::TestRequest::get().uri("/promocodes/id").to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers().get(header::ETAG).unwrap(), "\"1\"");
+    let fetched: Promocode = serde_json::from_slice(&test::read_body(res).await).unwrap();
+    assert_eq!(fetched.name(), "NAME");
+
+    // Normalized by the name policy.
+    let req = test::TestRequest::get()
+        .uri("/promocodes/by-name/name")
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let fetched_by_name: Promocode = serde_json::from_slice(&test::read_body(res).await).unwrap();
+    assert_eq!(fetched_by_name, fetched);
+
+    let req = test::TestRequest::get()
+        .uri("/promocodes/by-name/unknown")
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::NOT_FOUND
+    );
+
+    let req = test::TestRequest::delete()
+        .uri("/promocodes/id")
+        .header(header::IF_MATCH, "\"0\"")
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::PRECONDITION_FAILED
+    );
+
+    let req = test::TestRequest::delete()
+        .uri("/promocodes/id")
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    assert_eq!(repository.get_by_id("id"), Ok(None));
+
+    let req = test::TestRequest::delete()
+        .uri("/promocodes/id")
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::NOT_FOUND
+    );
+}
@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use ntex::{http::StatusCode, web::test};
+use promocode_models::{
+    promocode::{avantage::Avantage, name_policy::NamePolicy, Promocode},
+    signed_promocode::SignedPromocode,
+};
+use promocode_server::{
+    repository::{memory::InMemoryRepository, Repository},
+    server::routes,
+    signing_key::SigningKey,
+    weather::{NoWeatherProvider, Weather},
+};
+
+use common::{promocode_request, uppercase_policy};
+
+mod common;
+
+#[ntex::test]
+async fn routes_empty_restrictions() {
+    let repository: Repository = Arc::new(InMemoryRepository::new());
+    repository
+        .insert(
+            Promocode::new(
+                "id".to_string(),
+                "STORED".to_string(),
+                Avantage::new(10),
+                vec![],
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    let app = test::init_service(
+        common::test_app!(
+            repository,
+            Arc::new(NoWeatherProvider) as Weather,
+            NamePolicy::default()
+        )
+        .state(SigningKey::new("key".to_string()).unwrap())
+        .configure(routes::services),
+    )
+    .await;
+
+    // A stored promocode without restrictions denies every request.
+    let req = test::TestRequest::post()
+        .uri("/promocode/validate")
+        .set_json(&promocode_request("STORED", 25, "Lyon"))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::BAD_REQUEST
+    );
+
+    // A signed promocode without restrictions is accepted until it expires.
+    for (expires, status) in [
+        ("2999-12-31", StatusCode::OK),
+        ("2000-01-01", StatusCode::BAD_REQUEST),
+    ] {
+        let signed_promocode = SignedPromocode::new(
+            "SIGNED".to_string(),
+            Avantage::new(10),
+            expires.to_string(),
+            vec![],
+        )
+        .unwrap();
+        let req = test::TestRequest::post()
+            .uri("/promocode/sign")
+            .set_json(&signed_promocode)
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let code: String = serde_json::from_slice(&test::read_body(res).await).unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/promocode/validate")
+            .set_json(&promocode_request(&code, 25, "Lyon"))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), status);
+    }
+}
+
+#[ntex::test]
+async fn routes_signed_promocode_trimmed() {
+    let app = test::init_service(
+        common::test_app!(
+            Arc::new(InMemoryRepository::new()) as Repository,
+            Arc::new(NoWeatherProvider) as Weather,
+            uppercase_policy()
+        )
+        .state(SigningKey::new("key".to_string()).unwrap())
+        .configure(routes::services),
+    )
+    .await;
+
+    let signed_promocode = SignedPromocode::new(
+        "SIGNED".to_string(),
+        Avantage::new(10),
+        "2999-12-31".to_string(),
+        vec![],
+    )
+    .unwrap();
+    let req = test::TestRequest::post()
+        .uri("/promocode/sign")
+        .set_json(&signed_promocode)
+        .to_request();
+    let code: String = serde_json::from_slice(&test::read_body(test::call_service(&app, req).await).await).unwrap();
+
+    // Trimmed as the policy does, but not uppercased.
+    let req = test::TestRequest::post()
+        .uri("/promocode/validate")
+        .set_json(&promocode_request(&format!(" {} ", code), 25, "Lyon"))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+}
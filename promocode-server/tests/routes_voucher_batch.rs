@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use ntex::{http::StatusCode, web::test};
+use promocode_models::{
+    promocode::{avantage::Avantage, check_digit::CheckDigit, restriction::Restriction},
+    voucher_batch::{generator::CodeGenerator, GeneratedVoucherBatch, VoucherBatch},
+};
+use promocode_server::{
+    repository::{memory::InMemoryRepository, PromocodeFilter, Repository},
+    server::routes,
+    weather::{NoWeatherProvider, Weather},
+};
+
+use common::{promocode_request, uppercase_policy};
+
+mod common;
+
+const ALPHABET: &str = "ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+fn voucher_batch(prefix: &str, check_digit: bool) -> VoucherBatch {
+    VoucherBatch::new(
+        "campaign".to_string(),
+        3,
+        CodeGenerator::new(
+            ALPHABET.to_string(),
+            6,
+            Some(prefix.to_string()),
+            check_digit,
+        ),
+        Avantage::new(10),
+        vec![Restriction::age(None, None, Some(18))],
+    )
+    .unwrap()
+}
+
+#[ntex::test]
+async fn routes_voucher_batch_check_digit() {
+    let name_policy = uppercase_policy().with_check_digit(Some(
+        CheckDigit::new(ALPHABET.to_string(), vec!["SUMMER-".to_string()], false).unwrap(),
+    ));
+    let repository: Repository = Arc::new(InMemoryRepository::new());
+    let app = test::init_service(
+        common::test_app!(
+            repository.clone(),
+            Arc::new(NoWeatherProvider) as Weather,
+            name_policy
+        )
+        .configure(routes::services),
+    )
+    .await;
+
+    // The names would need a check digit to be redeemed.
+    let req = test::TestRequest::post()
+        .uri("/promocode/batch")
+        .set_json(&voucher_batch("SUMMER-", false))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(repository.list(&PromocodeFilter::default()), Ok(vec![]));
+
+    let req = test::TestRequest::post()
+        .uri("/promocode/batch")
+        .set_json(&voucher_batch("WINTER-", false))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let req = test::TestRequest::post()
+        .uri("/promocode/batch")
+        .set_json(&voucher_batch("SUMMER-", true))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let generated: GeneratedVoucherBatch = serde_json::from_slice(&test::read_body(res).await).unwrap();
+    assert_eq!(generated.codes.len(), 3);
+
+    for code in generated.codes {
+        let req = test::TestRequest::post()
+            .uri("/promocode/validate")
+            .set_json(&promocode_request(&code, 25, "Lyon"))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    }
+    assert_eq!(
+        repository.list(&PromocodeFilter::default()).unwrap().len(),
+        6
+    );
+}
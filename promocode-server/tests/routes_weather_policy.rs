@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use ntex::{http::StatusCode, web::test};
+use promocode_models::{
+    forecast::Forecast,
+    promocode::{avantage::Avantage, name_policy::NamePolicy, restriction::Restriction, temp::Temp, weather_policy::WeatherPolicy, Promocode},
+};
+use promocode_server::{
+    repository::{memory::InMemoryRepository, Repository},
+    server::routes,
+    weather::{Location, Weather, WeatherFuture, WeatherProvider},
+};
+
+use common::promocode_request;
+
+mod common;
+
+/// Weather API down, knowing no town but `Lyon`.
+struct DownProvider;
+
+impl WeatherProvider for DownProvider {
+    fn geocode<'a>(&'a self, town: &'a str) -> WeatherFuture<'a, Option<Location>> {
+        Box::pin(async move {
+            match town {
+                "Lyon" => Err("Weather API is down.".to_string()),
+                _ => Ok(None),
+            }
+        })
+    }
+
+    fn forecast<'a>(&'a self, _: &'a Location) -> WeatherFuture<'a, Forecast> {
+        Box::pin(async move { Err("Weather API is down.".to_string()) })
+    }
+}
+
+fn meteo_promocode(name: &str, weather_policy: WeatherPolicy) -> Promocode {
+    Promocode::new(
+        name.to_lowercase(),
+        name.to_string(),
+        Avantage::new(10),
+        vec![Restriction::and(vec![
+            Restriction::age(None, None, Some(18)),
+            Restriction::meteo("clear".to_string(), Temp { gt: 15 }),
+        ])],
+    )
+    .unwrap()
+    .with_weather_policy(weather_policy)
+}
+
+#[ntex::test]
+async fn routes_weather_policy() {
+    let repository: Repository = Arc::new(InMemoryRepository::new());
+    repository
+        .insert_batch(vec![
+            meteo_promocode("CLOSED", WeatherPolicy::FailClosed),
+            meteo_promocode("OPEN", WeatherPolicy::FailOpen),
+        ])
+        .unwrap();
+    let app = test::init_service(
+        common::test_app!(
+            repository,
+            Arc::new(DownProvider) as Weather,
+            NamePolicy::default()
+        )
+        .configure(routes::services),
+    )
+    .await;
+
+    for (name, town, status) in [
+        ("CLOSED", "Lyon", StatusCode::BAD_REQUEST),
+        ("OPEN", "Lyon", StatusCode::OK),
+        // An unknown town is not an unavailable weather.
+        ("OPEN", "Nowhere", StatusCode::BAD_REQUEST),
+    ] {
+        let request = test::TestRequest::post()
+            .uri("/promocode/validate")
+            .set_json(&promocode_request(name, 20, town))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, request).await.status(),
+            status,
+            "{} in {}",
+            name,
+            town
+        );
+    }
+}
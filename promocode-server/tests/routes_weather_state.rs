@@ -0,0 +1,81 @@
+use std::{sync::Arc, time::Duration};
+
+use ntex::{http::StatusCode, web::test};
+use promocode_models::{
+    forecast::Observation,
+    promocode::{avantage::Avantage, name_policy::NamePolicy, restriction::Restriction, temp::Temp, Promocode},
+    promocode_request::{arguments::Arguments, meteo::Meteo, PromocodeRequest},
+};
+use promocode_server::{
+    repository::{memory::InMemoryRepository, Repository},
+    server::routes,
+    weather::{
+        cache::CachedWeatherProvider,
+        fixtures::{FixturesWeatherProvider, TownFixture, WeatherFixtures},
+        NoWeatherProvider, Weather,
+    },
+};
+
+mod common;
+
+fn sunny() -> Weather {
+    let fixtures = WeatherFixtures {
+        towns: vec![TownFixture {
+            name: "Lyon".to_string(),
+            lat: 45.75,
+            lon: 4.85,
+            current: Observation::new("Clear".to_string(), 25.0),
+            forecast: vec![],
+        }],
+    };
+
+    Arc::new(CachedWeatherProvider::new(
+        Box::new(FixturesWeatherProvider::new(fixtures)),
+        Duration::from_secs(60),
+        Duration::from_secs(60),
+        10,
+    ))
+}
+
+#[ntex::test]
+async fn routes_weather_state() {
+    let repository: Repository = Arc::new(InMemoryRepository::new());
+    repository
+        .insert(
+            Promocode::new(
+                "id".to_string(),
+                "SUNNY".to_string(),
+                Avantage::new(20),
+                vec![Restriction::meteo("clear".to_string(), Temp { gt: 15 })],
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+    // Two servers in the same process, each with its own weather.
+    for (weather, validated, cache) in [
+        (sunny(), StatusCode::OK, StatusCode::OK),
+        (
+            Arc::new(NoWeatherProvider) as Weather,
+            StatusCode::BAD_REQUEST,
+            StatusCode::NOT_FOUND,
+        ),
+    ] {
+        let app = test::init_service(common::test_app!(repository.clone(), weather, NamePolicy::default()).configure(routes::services)).await;
+
+        let req = test::TestRequest::post()
+            .uri("/promocode/validate")
+            .set_json(
+                &PromocodeRequest::new(
+                    "SUNNY".to_string(),
+                    Arguments::new(25, Meteo::new("Lyon".to_string())),
+                )
+                .unwrap(),
+            )
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), validated);
+
+        let req = test::TestRequest::get().uri("/weather/cache").to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), cache);
+    }
+}
@@ -0,0 +1,178 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    sync::Arc,
+    time::Duration,
+};
+
+use futures_util::future;
+use ntex::time::{sleep, Millis};
+use promocode_models::forecast::{Forecast, Observation};
+use promocode_server::weather::{
+    cache::{CacheStats, CachedWeatherProvider},
+    fixtures::{FixturesWeatherProvider, TownFixture, WeatherFixtures},
+    Location, WeatherFuture, WeatherProvider,
+};
+
+use common::get_current_observation;
+
+mod common;
+
+/// [FixturesWeatherProvider] counting its calls, answering after 10 ms.
+#[derive(Clone, Default)]
+struct CountingProvider {
+    inner: FixturesWeatherProvider,
+    geocodes: Arc<AtomicUsize>,
+    forecasts: Arc<AtomicUsize>,
+}
+
+impl WeatherProvider for CountingProvider {
+    fn geocode<'a>(&'a self, town: &'a str) -> WeatherFuture<'a, Option<Location>> {
+        self.geocodes.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async move {
+            sleep(Millis(10)).await;
+            self.inner.geocode(town).await
+        })
+    }
+
+    fn forecast<'a>(&'a self, location: &'a Location) -> WeatherFuture<'a, Forecast> {
+        self.forecasts.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async move {
+            sleep(Millis(10)).await;
+            self.inner.forecast(location).await
+        })
+    }
+}
+
+fn counting_provider() -> CountingProvider {
+    let town = |name: &str, lat: f64| TownFixture {
+        name: name.to_string(),
+        lat,
+        lon: 4.85,
+        current: Observation::new("Clear".to_string(), 25.0),
+        forecast: vec![],
+    };
+
+    CountingProvider {
+        inner: FixturesWeatherProvider::new(WeatherFixtures {
+            towns: vec![
+                town("Lyon", 45.75),
+                town("Vienne", 45.52),
+                town("Valence", 44.93),
+            ],
+        }),
+        ..Default::default()
+    }
+}
+
+#[ntex::test]
+async fn weather_cache_hits() {
+    let provider = counting_provider();
+    let cached = CachedWeatherProvider::new(
+        Box::new(provider.clone()),
+        Duration::from_secs(60),
+        Duration::from_secs(60),
+        10,
+    );
+
+    for town in ["Lyon", " lyon", "LYON  "] {
+        assert_eq!(
+            get_current_observation(&cached, town).await,
+            Some(Observation::new("clear".to_string(), 25.0))
+        );
+    }
+    assert_eq!(get_current_observation(&cached, "Paris").await, None);
+    assert_eq!(get_current_observation(&cached, "Paris").await, None);
+
+    assert_eq!(provider.geocodes.load(Ordering::SeqCst), 2);
+    assert_eq!(provider.forecasts.load(Ordering::SeqCst), 1);
+
+    let stats = cached.cache_stats().unwrap();
+    assert_eq!(
+        stats.geocoding,
+        CacheStats {
+            hits: 3,
+            misses: 2,
+            coalesced: 0,
+            stale: 0,
+            evictions: 0,
+            entries: 2,
+        }
+    );
+    assert_eq!(stats.weather.hits, 2);
+    assert_eq!(stats.weather.misses, 1);
+}
+
+#[ntex::test]
+async fn weather_cache_ttl() {
+    let provider = counting_provider();
+    let cached = CachedWeatherProvider::new(
+        Box::new(provider.clone()),
+        Duration::from_secs(60),
+        Duration::ZERO,
+        10,
+    );
+
+    get_current_observation(&cached, "Lyon").await;
+    get_current_observation(&cached, "Lyon").await;
+
+    assert_eq!(provider.geocodes.load(Ordering::SeqCst), 1);
+    assert_eq!(provider.forecasts.load(Ordering::SeqCst), 2);
+}
+
+#[ntex::test]
+async fn weather_cache_coalescing() {
+    let provider = counting_provider();
+    let cached = CachedWeatherProvider::new(
+        Box::new(provider.clone()),
+        Duration::from_secs(60),
+        Duration::from_secs(60),
+        10,
+    );
+
+    let (left, right) = future::join(
+        get_current_observation(&cached, "Lyon"),
+        get_current_observation(&cached, "lyon"),
+    )
+    .await;
+    assert_eq!(left, right);
+    assert!(left.is_some());
+
+    assert_eq!(provider.geocodes.load(Ordering::SeqCst), 1);
+    assert_eq!(provider.forecasts.load(Ordering::SeqCst), 1);
+    let stats = cached.cache_stats().unwrap();
+    assert_eq!(stats.geocoding.coalesced, 1);
+    assert_eq!(stats.weather.coalesced, 1);
+}
+
+#[ntex::test]
+async fn weather_cache_errors_and_evictions() {
+    let provider = counting_provider();
+    let cached = CachedWeatherProvider::new(
+        Box::new(provider.clone()),
+        Duration::from_secs(60),
+        Duration::from_secs(60),
+        2,
+    );
+
+    // Errors are not cached.
+    let nowhere = Location {
+        name: "Nowhere".to_string(),
+        lat: 0.0,
+        lon: 0.0,
+    };
+    assert!(cached.forecast(&nowhere).await.is_err());
+    assert!(cached.forecast(&nowhere).await.is_err());
+    assert_eq!(provider.forecasts.load(Ordering::SeqCst), 2);
+
+    for town in ["Lyon", "Vienne", "Valence"] {
+        assert!(cached.geocode(town).await.unwrap().is_some());
+    }
+    let stats = cached.cache_stats().unwrap();
+    assert_eq!(stats.geocoding.entries, 2);
+    assert_eq!(stats.geocoding.evictions, 1);
+
+    // The oldest one was evicted.
+    cached.geocode("Valence").await.unwrap();
+    cached.geocode("Lyon").await.unwrap();
+    assert_eq!(provider.geocodes.load(Ordering::SeqCst), 4);
+}
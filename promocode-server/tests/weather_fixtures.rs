@@ -0,0 +1,86 @@
+use std::path::Path;
+
+use chrono::Duration;
+use promocode_models::forecast::Observation;
+use promocode_server::weather::{
+    fixtures::{FixturesWeatherProvider, TownFixture, WeatherFixtures},
+    Location, WeatherProvider,
+};
+
+use common::get_current_observation;
+
+mod common;
+
+fn provider() -> FixturesWeatherProvider {
+    FixturesWeatherProvider::new(WeatherFixtures {
+        towns: vec![TownFixture {
+            name: "Lyon".to_string(),
+            lat: 45.75,
+            lon: 4.85,
+            current: Observation::new("Clear".to_string(), 25.0),
+            forecast: vec![Observation::new("Rain".to_string(), 18.0)],
+        }],
+    })
+}
+
+#[ntex::test]
+async fn weather_fixtures_provider() {
+    let provider = provider();
+
+    let location = provider.geocode(" lyon ").await.unwrap().unwrap();
+    assert_eq!(
+        location,
+        Location {
+            name: "Lyon".to_string(),
+            lat: 45.75,
+            lon: 4.85,
+        }
+    );
+    // The current weather, then the next 3-hour steps.
+    let forecast = provider.forecast(&location).await.unwrap();
+    let steps: Vec<(&str, f64)> = forecast
+        .steps()
+        .iter()
+        .map(|step| (step.observation.condition.as_str(), step.observation.temp))
+        .collect();
+    assert_eq!(steps, vec![("clear", 25.0), ("rain", 18.0)]);
+    assert_eq!(
+        forecast.steps()[1].at - forecast.current().at,
+        Duration::hours(3)
+    );
+
+    assert_eq!(provider.geocode("Paris").await, Ok(None));
+    assert!(provider
+        .forecast(&Location {
+            name: "Nowhere".to_string(),
+            lat: 0.0,
+            lon: 0.0,
+        })
+        .await
+        .is_err());
+}
+
+#[ntex::test]
+async fn weather_get_current_observation() {
+    let provider = provider();
+
+    assert_eq!(
+        get_current_observation(&provider, "Lyon").await,
+        Some(Observation::new("clear".to_string(), 25.0))
+    );
+    assert_eq!(get_current_observation(&provider, "Paris").await, None);
+}
+
+#[test]
+fn weather_fixtures_load() {
+    let fixtures = WeatherFixtures::load(Path::new(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../etc/weather/fixtures.json"
+    )))
+    .unwrap();
+    let lyon = fixtures.town("LYON").unwrap();
+    assert_eq!(lyon.current.humidity, Some(40.0));
+    assert_eq!(lyon.forecast.len(), 2);
+
+    assert!(WeatherFixtures::load(Path::new("missing.json")).is_err());
+}
@@ -0,0 +1,170 @@
+use std::sync::Arc;
+
+use ntex::{
+    http::StatusCode,
+    web::{test, App},
+};
+use promocode_models::{
+    forecast::Observation,
+    promocode::{
+        avantage::Avantage,
+        comparison::Comparison,
+        forecast_window::{Aggregation, ForecastWindow},
+        meteo_metrics::MeteoMetrics,
+        name_policy::NamePolicy,
+        restriction::Restriction,
+        temp::Temp,
+        Promocode,
+    },
+};
+use promocode_server::{
+    repository::{memory::InMemoryRepository, Repository},
+    server::routes,
+    weather::{
+        fixtures::{TownFixture, WeatherFixtures},
+        mock::mock_services,
+        open_weather_sdk::{OpenWeatherClient, OpenWeatherProvider},
+        Weather, WeatherProvider,
+    },
+};
+
+use common::promocode_request;
+
+mod common;
+
+fn fixtures() -> WeatherFixtures {
+    WeatherFixtures {
+        towns: vec![TownFixture {
+            name: "Lyon".to_string(),
+            lat: 45.7578137,
+            lon: 4.8320114,
+            current: Observation::new("Clear".to_string(), 25.0)
+                .with_feels_like(26.5)
+                .with_humidity(40.0)
+                .with_wind(3.5),
+            forecast: vec![Observation::new("Rain".to_string(), 18.0).with_rain(2.5)],
+        }],
+    }
+}
+
+#[ntex::test]
+async fn weather_open_weather_mock() {
+    let mock = test::server(|| App::new().state(fixtures()).configure(mock_services));
+    let base_url = mock.url("/");
+
+    // Without API key, the mock fails as the API does.
+    let err = OpenWeatherClient::new_unchecked("".to_string(), base_url.clone())
+        .geocoding("Lyon", 1)
+        .await
+        .unwrap_err();
+    assert!(err.contains("401"), "{}", err);
+
+    let client = OpenWeatherClient::new("key".to_string(), base_url.clone()).unwrap();
+    assert_eq!(client.base_url(), mock.url("").trim_end_matches('/'));
+    assert!(client.geocoding("Paris", 1).await.unwrap().is_empty());
+
+    // Initialized once per server instance, not per process.
+    assert!(OpenWeatherClient::new(" ".to_string(), base_url.clone()).is_err());
+    let provider = OpenWeatherProvider::new(OpenWeatherClient::new("key".to_string(), base_url.clone()).unwrap());
+    let location = provider.geocode("lyon").await.unwrap().unwrap();
+    assert_eq!(location.name, "Lyon");
+    let forecast = provider.forecast(&location).await.unwrap();
+    let steps: Vec<(&str, f64)> = forecast
+        .steps()
+        .iter()
+        .map(|step| (step.observation.condition.as_str(), step.observation.temp))
+        .collect();
+    assert_eq!(steps, vec![("clear", 25.0), ("rain", 18.0)]);
+    // The other metrics, the API giving them all.
+    assert_eq!(
+        forecast.current().observation,
+        Observation::new("clear".to_string(), 25.0)
+            .with_feels_like(26.5)
+            .with_humidity(40.0)
+            .with_wind(3.5)
+            .with_rain(0.0)
+    );
+    assert_eq!(forecast.steps()[1].observation.rain, Some(2.5));
+
+    // Validation of a `@meteo` restriction, end-to-end.
+    let weather: Weather = Arc::new(OpenWeatherProvider::new(
+        OpenWeatherClient::new("key".to_string(), base_url).unwrap(),
+    ));
+    let repository: Repository = Arc::new(InMemoryRepository::new());
+    repository
+        .insert(
+            Promocode::new(
+                "id".to_string(),
+                "SUNNY".to_string(),
+                Avantage::new(20),
+                vec![Restriction::meteo("clear".to_string(), Temp { gt: 15 })],
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    repository
+        .insert(
+            Promocode::new(
+                "id - rain".to_string(),
+                "RAIN_SOON".to_string(),
+                Avantage::new(20),
+                vec![Restriction::meteo_forecast(
+                    "rain".to_string(),
+                    Temp { gt: 15 },
+                    ForecastWindow::new(6, Aggregation::Any, Aggregation::Min),
+                )],
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    repository
+        .insert(
+            Promocode::new(
+                "id - dry".to_string(),
+                "DRY_AND_CALM".to_string(),
+                Avantage::new(20),
+                vec![Restriction::meteo_metrics(
+                    "clear".to_string(),
+                    Temp { gt: 15 },
+                    MeteoMetrics::new(
+                        None,
+                        Some(Comparison::new(Some(60.0), None, None).unwrap()),
+                        Some(Comparison::new(Some(5.0), None, None).unwrap()),
+                        Some(Comparison::new(None, Some(0.0), None).unwrap()),
+                    ),
+                )],
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    let app = test::init_service(common::test_app!(repository, weather, NamePolicy::default()).configure(routes::services)).await;
+
+    let req = test::TestRequest::post()
+        .uri("/promocode/validate")
+        .set_json(&promocode_request("SUNNY", 20, "Lyon"))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let req = test::TestRequest::post()
+        .uri("/promocode/validate")
+        .set_json(&promocode_request("SUNNY", 20, "Paris"))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::BAD_REQUEST
+    );
+
+    // The rain of the next step, in the 6 hours window.
+    let req = test::TestRequest::post()
+        .uri("/promocode/validate")
+        .set_json(&promocode_request("RAIN_SOON", 20, "Lyon"))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    // The humidity, wind and rain of the current weather.
+    let req = test::TestRequest::post()
+        .uri("/promocode/validate")
+        .set_json(&promocode_request("DRY_AND_CALM", 20, "Lyon"))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+}
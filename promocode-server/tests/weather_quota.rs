@@ -0,0 +1,160 @@
+use std::{path::PathBuf, time::Duration};
+
+use chrono::{DateTime, TimeZone, Utc};
+use ntex::time::{sleep, Millis};
+use promocode_models::forecast::Observation;
+use promocode_server::weather::{
+    cache::CachedWeatherProvider,
+    fixtures::{FixturesWeatherProvider, TownFixture, WeatherFixtures},
+    quota::{MonthlyUsage, QuotaPolicy, WeatherQuota},
+    resilience::{CircuitBreaker, ResilientWeatherProvider},
+    WeatherProvider,
+};
+
+use common::get_current_observation;
+
+mod common;
+
+fn at(month: u32, day: u32, seconds: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, month, day, 12, 0, seconds)
+        .unwrap()
+}
+
+fn quota_file(test: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "promocode-quota-{}-{}.json",
+        test,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// Provider answering from fixtures, each attempt counting in `quota`.
+fn guarded(quota: WeatherQuota) -> ResilientWeatherProvider {
+    ResilientWeatherProvider::new(
+        Box::new(fixtures_provider()),
+        Duration::from_secs(1),
+        0,
+        Duration::ZERO,
+        CircuitBreaker::new(0, Duration::ZERO),
+    )
+    .with_quota(quota)
+}
+
+fn fixtures_provider() -> FixturesWeatherProvider {
+    FixturesWeatherProvider::new(WeatherFixtures {
+        towns: vec![TownFixture {
+            name: "Lyon".to_string(),
+            lat: 45.75,
+            lon: 4.85,
+            current: Observation::new("Clear".to_string(), 25.0),
+            forecast: vec![],
+        }],
+    })
+}
+
+#[test]
+fn weather_quota_per_minute() {
+    let quota = WeatherQuota::open(2, 0, QuotaPolicy::Deny, None).unwrap();
+
+    assert_eq!(quota.acquire_at(at(1, 1, 0)), Ok(()));
+    assert_eq!(quota.acquire_at(at(1, 1, 0)), Ok(()));
+    assert!(quota.acquire_at(at(1, 1, 1)).is_err());
+    // One call every 30 seconds.
+    assert_eq!(quota.acquire_at(at(1, 1, 30)), Ok(()));
+    assert!(quota.acquire_at(at(1, 1, 31)).is_err());
+}
+
+#[test]
+fn weather_quota_per_month() {
+    let quota = WeatherQuota::open(0, 3, QuotaPolicy::Deny, None).unwrap();
+
+    for _ in 0..3 {
+        assert_eq!(quota.acquire_at(at(1, 31, 0)), Ok(()));
+    }
+    assert!(quota.acquire_at(at(1, 31, 0)).is_err());
+
+    // The next month starts over.
+    assert_eq!(quota.acquire_at(at(2, 1, 0)), Ok(()));
+}
+
+#[test]
+fn weather_quota_allow() {
+    let quota = WeatherQuota::open(1, 1, QuotaPolicy::Allow, None).unwrap();
+
+    assert_eq!(quota.acquire_at(at(1, 1, 0)), Ok(()));
+    assert_eq!(quota.acquire_at(at(1, 1, 0)), Ok(()));
+}
+
+#[test]
+fn weather_quota_persisted() {
+    let path = quota_file("persisted");
+
+    let quota = WeatherQuota::open(0, 1_000, QuotaPolicy::Deny, Some(path.clone())).unwrap();
+    let month = quota.usage().month;
+    for _ in 0..3 {
+        quota.acquire().unwrap();
+    }
+
+    let quota = WeatherQuota::open(0, 1_000, QuotaPolicy::Deny, Some(path.clone())).unwrap();
+    assert_eq!(quota.usage(), MonthlyUsage { month, calls: 3 });
+
+    // The usage of a past month is not counted.
+    std::fs::write(&path, r#"{"month": "2000-01", "calls": 999}"#).unwrap();
+    let quota = WeatherQuota::open(0, 1_000, QuotaPolicy::Deny, Some(path.clone())).unwrap();
+    assert_eq!(quota.usage().calls, 0);
+
+    std::fs::write(&path, "not json").unwrap();
+    assert!(WeatherQuota::open(0, 1_000, QuotaPolicy::Deny, Some(path.clone())).is_err());
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[ntex::test]
+async fn weather_quota_provider() {
+    let quota = WeatherQuota::open(0, 2, QuotaPolicy::Deny, None).unwrap();
+    let provider = guarded(quota);
+
+    // A geocoding and a weather call.
+    assert_eq!(
+        get_current_observation(&provider, "Lyon").await,
+        Some(Observation::new("clear".to_string(), 25.0))
+    );
+    assert_eq!(provider.quota().unwrap().usage().calls, 2);
+    assert_eq!(get_current_observation(&provider, "Lyon").await, None);
+    assert_eq!(provider.quota().unwrap().usage().calls, 2);
+}
+
+#[ntex::test]
+async fn weather_quota_stale() {
+    for (policy, expected) in [
+        (
+            QuotaPolicy::Stale,
+            Some(Observation::new("clear".to_string(), 25.0)),
+        ),
+        (QuotaPolicy::Deny, None),
+    ] {
+        let quota = WeatherQuota::open(2, 0, policy, None).unwrap();
+        let cached = CachedWeatherProvider::new(
+            Box::new(guarded(quota)),
+            Duration::from_secs(60),
+            Duration::from_millis(20),
+            10,
+        )
+        .with_stale_if_error(policy == QuotaPolicy::Stale);
+
+        assert_eq!(
+            get_current_observation(&cached, "Lyon").await,
+            Some(Observation::new("clear".to_string(), 25.0))
+        );
+
+        // The weather expired, and the quota is exhausted.
+        sleep(Millis(30)).await;
+        assert_eq!(get_current_observation(&cached, "Lyon").await, expected);
+        assert_eq!(get_current_observation(&cached, "Lyon").await, expected);
+
+        let stats = cached.cache_stats().unwrap();
+        assert_eq!(stats.weather.stale, if expected.is_some() { 2 } else { 0 });
+    }
+}
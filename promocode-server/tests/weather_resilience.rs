@@ -0,0 +1,195 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use ntex::time::{sleep, Millis};
+use promocode_models::forecast::{Forecast, Observation};
+use promocode_server::weather::{
+    quota::{QuotaPolicy, WeatherQuota},
+    resilience::{CircuitBreaker, CircuitState, ResilientWeatherProvider},
+    Location, WeatherFuture, WeatherProvider,
+};
+
+use common::get_current_observation;
+
+mod common;
+
+/// Provider failing its first `failures` calls, each one answering after
+/// `delay` ms, and counting them.
+#[derive(Clone, Default)]
+struct FlakyProvider {
+    failures: usize,
+    delay: u32,
+    calls: Arc<AtomicUsize>,
+}
+
+impl FlakyProvider {
+    fn attempt<'a, T: 'a>(&'a self, value: T) -> WeatherFuture<'a, T> {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async move {
+            sleep(Millis(self.delay)).await;
+            if call < self.failures {
+                Err(format!("Call {} failed.", call))
+            } else {
+                Ok(value)
+            }
+        })
+    }
+}
+
+impl WeatherProvider for FlakyProvider {
+    fn geocode<'a>(&'a self, town: &'a str) -> WeatherFuture<'a, Option<Location>> {
+        self.attempt(Some(Location {
+            name: town.to_string(),
+            lat: 0.0,
+            lon: 0.0,
+        }))
+    }
+
+    fn forecast<'a>(&'a self, _: &'a Location) -> WeatherFuture<'a, Forecast> {
+        self.attempt(Forecast::from(Observation::new("clear".to_string(), 25.0)))
+    }
+}
+
+fn resilient(provider: &FlakyProvider, retries: u32, breaker: CircuitBreaker) -> ResilientWeatherProvider {
+    ResilientWeatherProvider::new(
+        Box::new(provider.clone()),
+        Duration::from_millis(100),
+        retries,
+        Duration::from_millis(1),
+        breaker,
+    )
+}
+
+#[ntex::test]
+async fn weather_resilience_retries() {
+    let provider = FlakyProvider {
+        failures: 2,
+        ..Default::default()
+    };
+    let resilient = resilient(&provider, 2, CircuitBreaker::new(0, Duration::ZERO));
+
+    assert_eq!(
+        get_current_observation(&resilient, "Lyon").await,
+        Some(Observation::new("clear".to_string(), 25.0))
+    );
+    // The geocoding succeeded on its third attempt.
+    assert_eq!(provider.calls.load(Ordering::SeqCst), 4);
+
+    let provider = FlakyProvider {
+        failures: 3,
+        ..Default::default()
+    };
+    let resilient = self::resilient(&provider, 2, CircuitBreaker::new(0, Duration::ZERO));
+
+    assert_eq!(get_current_observation(&resilient, "Lyon").await, None);
+    assert_eq!(provider.calls.load(Ordering::SeqCst), 3);
+}
+
+#[ntex::test]
+async fn weather_resilience_timeout() {
+    let provider = FlakyProvider {
+        delay: 500,
+        ..Default::default()
+    };
+    let resilient = resilient(&provider, 1, CircuitBreaker::new(0, Duration::ZERO));
+
+    let started = std::time::Instant::now();
+    assert!(resilient.geocode("Lyon").await.is_err());
+    assert!(started.elapsed() < Duration::from_millis(400));
+    assert_eq!(provider.calls.load(Ordering::SeqCst), 2);
+}
+
+#[ntex::test]
+async fn weather_resilience_circuit_breaker() {
+    let provider = FlakyProvider {
+        failures: 2,
+        ..Default::default()
+    };
+    let resilient = resilient(
+        &provider,
+        0,
+        CircuitBreaker::new(2, Duration::from_millis(50)),
+    );
+
+    assert!(resilient.geocode("Lyon").await.is_err());
+    assert_eq!(resilient.breaker().state(), CircuitState::Closed);
+    assert!(resilient.geocode("Lyon").await.is_err());
+    assert_eq!(resilient.breaker().state(), CircuitState::Open);
+
+    // Not called while open.
+    assert!(resilient.geocode("Lyon").await.is_err());
+    assert_eq!(provider.calls.load(Ordering::SeqCst), 2);
+
+    // A trial call once half-open, closing it.
+    sleep(Millis(60)).await;
+    assert_eq!(resilient.breaker().state(), CircuitState::HalfOpen);
+    assert!(resilient.geocode("Lyon").await.is_ok());
+    assert_eq!(resilient.breaker().state(), CircuitState::Closed);
+    assert_eq!(provider.calls.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn weather_resilience_circuit_breaker_trial() {
+    let breaker = CircuitBreaker::new(1, Duration::ZERO);
+
+    breaker.record(false);
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    assert_eq!(breaker.allow(), Ok(()));
+
+    // A failed trial opens it again.
+    breaker.record(false);
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    breaker.record(true);
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
+
+#[ntex::test]
+async fn weather_resilience_quota() {
+    let provider = FlakyProvider {
+        failures: 1,
+        ..Default::default()
+    };
+    let quota = WeatherQuota::open(0, 3, QuotaPolicy::Deny, None).unwrap();
+    let guarded = resilient(
+        &provider,
+        2,
+        CircuitBreaker::new(1, Duration::from_secs(60)),
+    )
+    .with_quota(quota);
+
+    // The geocoding is retried once, and each attempt counts.
+    assert!(guarded.geocode("Lyon").await.is_ok());
+    assert_eq!(provider.calls.load(Ordering::SeqCst), 2);
+    assert_eq!(guarded.quota().unwrap().usage().calls, 2);
+    assert!(guarded.geocode("Lyon").await.is_ok());
+    assert_eq!(guarded.quota().unwrap().usage().calls, 3);
+
+    // Denied calls answer at once, without attempt nor breaker failure.
+    let started = std::time::Instant::now();
+    for _ in 0..3 {
+        assert!(guarded.geocode("Lyon").await.is_err());
+    }
+    assert!(started.elapsed() < Duration::from_millis(50));
+    assert_eq!(provider.calls.load(Ordering::SeqCst), 3);
+    assert_eq!(guarded.breaker().state(), CircuitState::Closed);
+}
+
+#[ntex::test]
+async fn weather_resilience_quota_exhausted_by_retries() {
+    let provider = FlakyProvider {
+        failures: 5,
+        ..Default::default()
+    };
+    let quota = WeatherQuota::open(0, 2, QuotaPolicy::Deny, None).unwrap();
+    let guarded = resilient(&provider, 2, CircuitBreaker::new(0, Duration::ZERO)).with_quota(quota);
+
+    // The third attempt is denied, so it is not made.
+    assert!(guarded.geocode("Lyon").await.is_err());
+    assert_eq!(provider.calls.load(Ordering::SeqCst), 2);
+    assert_eq!(guarded.quota().unwrap().usage().calls, 2);
+}
@@ -42,6 +42,10 @@ impl<T> NonEmptyVec<T> {
     pub fn get(self) -> Vec<T> {
         self.0
     }
+    /// Returns an iterator over the values.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.0.iter()
+    }
 }
 
 impl<'de, T: Deserialize<'de>> Deserialize<'de> for NonEmptyVec<T> {